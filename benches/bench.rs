@@ -6,7 +6,7 @@ use criterion::{criterion_group, criterion_main, Criterion};
 #[allow(unused_imports)]
 use sort_test_tools::ffi_types::{FFIOneKibiByte, FFIString, F128};
 
-use sort_test_tools::patterns;
+use sort_test_tools::{adversary, patterns};
 
 #[allow(unused_imports)]
 use sort_research_rs::{stable, unstable};
@@ -179,6 +179,12 @@ fn bench_patterns<T: Ord + std::fmt::Debug>(
             patterns::saw_mixed(len, ((len as f64).log2().round()) as usize)
         }),
         ("pipe_organ", patterns::pipe_organ),
+        // Decided by the adversary while the std unstable sort runs. The sort benchmarks replace
+        // it with the input generated against `sort_by` of the benchmarked implementation and
+        // type, and benchmark `sort_by` for it, see `modules::sort::bench_fn`.
+        ("antiqsort", |len| {
+            adversary::antiqsort::<unstable::rust_std::SortImpl>(len).into_input()
+        }),
         ("random__div3", |len| {
             patterns::random_uniform(len, 0..=(((len as f64 / 3.0).round()) as i32))
         }),
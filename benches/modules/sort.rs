@@ -1,8 +1,9 @@
+use std::cell::OnceCell;
 use std::env;
 
 use criterion::{black_box, Criterion};
 
-use sort_test_tools::Sort;
+use sort_test_tools::{adversary, Sort};
 
 #[allow(unused_imports)]
 use sort_research_rs::{other, stable, unstable};
@@ -44,7 +45,18 @@ fn measure_comp_count<S: Sort, T: Ord + std::fmt::Debug>(
     // If there is on average less than a single comparison this will be wrong.
     // But that's such a corner case I don't care about it.
     let total = comp_count / (run_count as u64);
-    println!("{name}: mean comparisons: {total}");
+
+    // Any comparison based sort needs at least log2(n!) comparisons on average for inputs with
+    // distinct values. Inputs with duplicates or existing runs can need fewer.
+    let lower_bound: f64 = (2..=test_len).map(|i| (i as f64).log2()).sum();
+    if lower_bound > 0.0 {
+        println!(
+            "{name}: mean comparisons: {total} ({:.3} * log2(n!))",
+            total as f64 / lower_bound
+        );
+    } else {
+        println!("{name}: mean comparisons: {total}");
+    }
 }
 
 pub fn bench_fn<S: Sort, T: Ord + std::fmt::Debug>(
@@ -57,6 +69,31 @@ pub fn bench_fn<S: Sort, T: Ord + std::fmt::Debug>(
 ) {
     let bench_name = S::name();
 
+    // Comparison counting, the adversary and the key extraction functions all go through
+    // `S::sort_by`.
+    let uses_sort_by = pattern_name == "antiqsort"
+        || env::var("MEASURE_COMP").is_ok()
+        || env::var("BENCH_SORT_FN").is_ok();
+    if uses_sort_by && !S::supports_sort_by() {
+        return;
+    }
+
+    // The adversary input only triggers the worst case of the code path it was generated against,
+    // which is `S::sort_by` for elements of type `T`, see `generate_antiqsort_input`. It's generated lazily
+    // so that filtered out benchmarks don't pay for it, and replayed with `S::sort_by` for every
+    // run. The key extraction benchmarks sort indices instead, for them it's just another input.
+    let antiqsort_input = OnceCell::new();
+    let antiqsort_provider = |len| {
+        antiqsort_input
+            .get_or_init(|| generate_antiqsort_input::<S, T>(len, transform))
+            .clone()
+    };
+    let pattern_provider: &dyn Fn(usize) -> Vec<i32> = if pattern_name == "antiqsort" {
+        &antiqsort_provider
+    } else {
+        &pattern_provider
+    };
+
     if env::var("MEASURE_COMP").is_ok() {
         let name = format!(
             "{}-comp-{}-{}-{}",
@@ -66,6 +103,58 @@ pub fn bench_fn<S: Sort, T: Ord + std::fmt::Debug>(
         if util::should_run_benchmark(&name) {
             measure_comp_count::<S, T>(&name, test_len, transform, pattern_provider);
         }
+    } else if let Ok(sort_fn) = env::var("BENCH_SORT_FN") {
+        // The key extraction functions are benchmarked by sorting indices into the input, using
+        // the referenced element as key. This works for every type without requiring `Clone`, and
+        // models the common use-case of indirect sorting. The index allocation is part of the
+        // measurement.
+        match sort_fn.as_str() {
+            "sort_by_key" => util::bench_fn(
+                c,
+                test_len,
+                transform_name,
+                transform,
+                pattern_name,
+                pattern_provider,
+                &format!("{bench_name}_by_key"),
+                |v: &mut [T]| {
+                    let v_ref: &[T] = v;
+                    let mut indices: Vec<usize> = (0..v_ref.len()).collect();
+                    S::sort_by_key(&mut indices, move |i| &v_ref[*i]);
+                    black_box(indices);
+                },
+            ),
+            "sort_by_cached_key" => util::bench_fn(
+                c,
+                test_len,
+                transform_name,
+                transform,
+                pattern_name,
+                pattern_provider,
+                &format!("{bench_name}_by_cached_key"),
+                |v: &mut [T]| {
+                    let v_ref: &[T] = v;
+                    let mut indices: Vec<usize> = (0..v_ref.len()).collect();
+                    S::sort_by_cached_key(&mut indices, move |i| &v_ref[*i]);
+                    black_box(indices);
+                },
+            ),
+            _ => panic!(
+                "Unknown BENCH_SORT_FN value: '{}'. Valid values are sort_by_key and sort_by_cached_key.",
+                sort_fn
+            ),
+        }
+    } else if pattern_name == "antiqsort" {
+        util::bench_fn(
+            c,
+            test_len,
+            transform_name,
+            transform,
+            pattern_name,
+            pattern_provider,
+            &bench_name,
+            |v: &mut [T]| S::sort_by(v, |a, b| a.cmp(b)),
+        );
     } else {
         util::bench_fn(
             c,
@@ -80,6 +169,26 @@ pub fn bench_fn<S: Sort, T: Ord + std::fmt::Debug>(
     }
 }
 
+/// Returns the input McIlroy's adversary decides while `S::sort_by` sorts `len` elements of type
+/// `T`, see [`adversary`]. The elements are created from `0..len` with `transform`, and the
+/// adversary tells them apart by their position in that sequence. This requires `transform` to
+/// preserve the order of the values, which also makes `S::sort_by` perform the same comparisons
+/// when the returned input is transformed and sorted again.
+fn generate_antiqsort_input<S: Sort, T: Ord>(
+    len: usize,
+    transform: &fn(Vec<i32>) -> Vec<T>,
+) -> Vec<i32> {
+    let ranked = transform((0..len as i32).collect());
+    assert!(ranked.windows(2).all(|w| w[0] < w[1]));
+    let rank = |elem: &T| ranked.binary_search(elem).unwrap();
+
+    let mut adversary = adversary::Adversary::new(len);
+    let mut v = transform((0..len as i32).collect());
+    S::sort_by(&mut v, |a, b| adversary.compare(rank(a), rank(b)));
+
+    adversary.into_input()
+}
+
 pub fn bench<T: Ord + std::fmt::Debug>(
     c: &mut Criterion,
     test_len: usize,
@@ -156,6 +265,36 @@ pub fn bench<T: Ord + std::fmt::Debug>(
 
     bench_inst!(unstable::rust_ipnsort);
 
+    #[cfg(feature = "rust_ipnsort_capi")]
+    bench_inst!(unstable::rust_ipnsort_capi);
+
+    #[cfg(feature = "rust_ipnsort_par")]
+    bench_inst!(unstable::rust_ipnsort_par);
+
+    #[cfg(feature = "rust_ipnsort_min_cmp")]
+    bench_inst!(unstable::rust_ipnsort_min_cmp);
+
+    #[cfg(feature = "rust_ipnsort_appended")]
+    bench_inst!(unstable::rust_ipnsort_appended);
+
+    #[cfg(feature = "rust_ipnsort_config")]
+    bench_inst!(unstable::rust_ipnsort_config::small_sort_16);
+
+    #[cfg(feature = "rust_ipnsort_config")]
+    bench_inst!(unstable::rust_ipnsort_config::small_sort_64);
+
+    #[cfg(feature = "rust_ipnsort_config")]
+    bench_inst!(unstable::rust_ipnsort_config::no_insertion_sort);
+
+    #[cfg(feature = "rust_ipnsort_config")]
+    bench_inst!(unstable::rust_ipnsort_config::imbalance_limit_1);
+
+    #[cfg(feature = "rust_ipnsort_config")]
+    bench_inst!(unstable::rust_ipnsort_config::no_indirect_sort);
+
+    #[cfg(feature = "rust_ipnsort_config")]
+    bench_inst!(unstable::rust_ipnsort_config::bounded_stack);
+
     bench_inst!(unstable::rust_std);
 
     #[cfg(feature = "rust_std_vendored")]
@@ -72,8 +72,10 @@ pub fn bench_fn<T: Ord + std::fmt::Debug>(
 ) {
     // Pin the benchmark to the same core to improve repeatability. Doing it this way allows
     // criterion to do other stuff with other threads, which greatly impacts overall benchmark
-    // throughput.
-    pin_thread_to_core();
+    // throughput. Multi-threaded sorts would be limited to that core as well.
+    if !cfg!(feature = "rust_ipnsort_par") {
+        pin_thread_to_core();
+    }
 
     let batch_size = if test_len > 30 {
         BatchSize::LargeInput
@@ -0,0 +1,61 @@
+//! Measures how far the partitions are from the ideal split in half, and how often the heapsort
+//! fallback is taken, for a selection of input patterns.
+//!
+//! cargo run --release --features trace --example pivot_quality
+
+use ipnsort::trace::{self, Event, Observer};
+use sort_test_tools::patterns;
+
+type PatternFn = fn(usize) -> Vec<i32>;
+
+#[derive(Default)]
+struct PivotQuality {
+    /// Ideally each partition operation halves the input, the overshoot measures how far the
+    /// larger side is from that ideal, with 1.0 being a perfect split.
+    overshoots: Vec<f64>,
+    heapsort_fallbacks: usize,
+}
+
+impl Observer for PivotQuality {
+    fn event(&mut self, event: Event) {
+        match event {
+            Event::Partition { len, num_lt } => {
+                let larger_side = num_lt.max(len - num_lt - 1);
+                self.overshoots
+                    .push(larger_side as f64 / (len as f64 / 2.0));
+            }
+            Event::HeapsortFallback { .. } => self.heapsort_fallbacks += 1,
+            _ => {}
+        }
+    }
+}
+
+fn main() {
+    let test_len = 1_000_000;
+    let runs = 10;
+
+    let pattern_fns: [(&str, PatternFn); 5] = [
+        ("random", patterns::random),
+        ("random_d20", |len| patterns::random_uniform(len, 0..20)),
+        ("random_z1", |len| patterns::random_zipf(len, 1.0)),
+        ("random_s95", |len| patterns::random_sorted(len, 95.0)),
+        ("pipe_organ", patterns::pipe_organ),
+    ];
+
+    for (pattern_name, pattern_fn) in pattern_fns {
+        let mut quality = PivotQuality::default();
+
+        for _ in 0..runs {
+            let mut v = pattern_fn(test_len);
+            trace::with_observer(&mut quality, || ipnsort::sort(&mut v));
+        }
+
+        let partitions = quality.overshoots.len();
+        let mean_overshoot = quality.overshoots.iter().sum::<f64>() / partitions.max(1) as f64;
+
+        println!(
+            "{pattern_name}: partitions: {partitions} mean overshoot: {mean_overshoot:.2} heapsort fallbacks: {}",
+            quality.heapsort_fallbacks
+        );
+    }
+}
@@ -0,0 +1,134 @@
+//! Reporting of comparison functions that don't implement a total order, see
+//! [`crate::try_sort_by`].
+//!
+//! The merges of the small-sorts notice when their input was not consumed exactly once, which can
+//! only happen if the comparison function is inconsistent. They restore their input and report
+//! the affected region with [`ord_violation`]. Outside of [`observe_ord_violations`] that panics,
+//! inside it the first region is recorded and the sort continues.
+
+use core::fmt;
+
+/// The comparison function passed to [`crate::try_sort_by`] was observed to not implement a total
+/// order.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct OrdViolation {
+    left: usize,
+    right: usize,
+}
+
+impl OrdViolation {
+    /// Returns the indices `(left, right)` with `left < right` of the region of the slice that is
+    /// not sorted consistently, after [`crate::try_sort_by`] returned.
+    ///
+    /// If `right == left + 1` the two elements are out of order, `compare(&v[right], &v[left])`
+    /// returns [`Less`](core::cmp::Ordering::Less). Otherwise the comparison function contradicted
+    /// itself while sorting the elements that ended up in `v[left..=right]`, which are left in an
+    /// unspecified order.
+    pub fn indices(&self) -> (usize, usize) {
+        (self.left, self.right)
+    }
+
+    pub(crate) fn new(left: usize, right: usize) -> Self {
+        debug_assert!(left < right);
+        Self { left, right }
+    }
+}
+
+impl fmt::Display for OrdViolation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "comparison function does not implement a total order for the elements at indices \
+             {}..={}",
+            self.left, self.right
+        )
+    }
+}
+
+impl std::error::Error for OrdViolation {}
+
+#[cfg(not(feature = "stable"))]
+pub(crate) use observe::{observe_ord_violations, ord_violation};
+
+#[cfg(not(feature = "stable"))]
+mod observe {
+    use core::mem;
+    use std::cell::Cell;
+
+    use super::OrdViolation;
+
+    /// The slice sorted by the innermost [`observe_ord_violations`] on this thread, as address
+    /// range, and the first violation found in it.
+    #[derive(Copy, Clone)]
+    struct Observed {
+        start: usize,
+        end: usize,
+        elem_size: usize,
+        violation: Option<OrdViolation>,
+    }
+
+    thread_local! {
+        static OBSERVED: Cell<Option<Observed>> = const { Cell::new(None) };
+    }
+
+    /// Calls `sort` on `v`, and returns the first violation that was reported for a region of `v`
+    /// with [`ord_violation`] instead of panicking.
+    pub(crate) fn observe_ord_violations<T>(
+        v: &mut [T],
+        sort: impl FnOnce(&mut [T]),
+    ) -> Option<OrdViolation> {
+        let start = v.as_ptr() as usize;
+        let observed = Observed {
+            start,
+            end: start + mem::size_of_val(v),
+            elem_size: mem::size_of::<T>(),
+            violation: None,
+        };
+
+        let _guard = RestoreOnDrop(OBSERVED.replace(Some(observed)));
+
+        sort(v);
+
+        OBSERVED.get().and_then(|observed| observed.violation)
+    }
+
+    struct RestoreOnDrop(Option<Observed>);
+
+    impl Drop for RestoreOnDrop {
+        fn drop(&mut self) {
+            OBSERVED.set(self.0);
+        }
+    }
+
+    /// Reports that the elements in `region` were sorted by an inconsistent comparison function,
+    /// the region must hold all of its original elements. Panics unless `region` is part of the
+    /// slice sorted by [`observe_ord_violations`], e.g. for a sort called by the comparison
+    /// function itself.
+    #[cold]
+    #[inline(never)]
+    pub(crate) fn ord_violation<T>(region: &[T]) {
+        let start = region.as_ptr() as usize;
+        let end = start + mem::size_of_val(region);
+
+        match OBSERVED.get() {
+            Some(mut observed) if observed.start <= start && end <= observed.end => {
+                if observed.violation.is_none() && region.len() >= 2 {
+                    let left = (start - observed.start) / observed.elem_size;
+                    observed.violation = Some(OrdViolation::new(left, left + region.len() - 1));
+                    OBSERVED.set(Some(observed));
+                }
+            }
+            _ => panic!("Ord violation"),
+        }
+    }
+}
+
+/// The small-sorts of the `stable` feature don't merge, so there are no violations to observe.
+#[cfg(feature = "stable")]
+pub(crate) fn observe_ord_violations<T>(
+    v: &mut [T],
+    sort: impl FnOnce(&mut [T]),
+) -> Option<OrdViolation> {
+    sort(v);
+    None
+}
@@ -0,0 +1,92 @@
+/// Compile-time tuning parameters of the sort implementation, see [`crate::sort_with_config`].
+///
+/// All constants have default values, which are the ones used by [`crate::sort`], so an
+/// implementation only needs to override the ones that are being tuned.
+///
+/// With the `stable` feature the specialized small-sorts are not available, and
+/// `SMALL_SORT_GENERAL_THRESHOLD`, `SMALL_SORT_NETWORK_THRESHOLD` and `MAX_STACK_ARRAY_SIZE` have
+/// no effect.
+///
+/// # Examples
+///
+/// ```
+/// struct LargeSmallSort;
+///
+/// impl ipnsort::SortConfig for LargeSmallSort {
+///     const SMALL_SORT_GENERAL_THRESHOLD: usize = 64;
+///     const SMALL_SORT_NETWORK_THRESHOLD: usize = 64;
+/// }
+///
+/// let mut v = [-5, 4, 1, -3, 2];
+///
+/// ipnsort::sort_with_config::<LargeSmallSort, _>(&mut v);
+/// assert!(v == [-5, -3, 1, 2, 4]);
+/// ```
+pub trait SortConfig {
+    /// Inputs up to this length are always sorted with insertion sort, to keep the instruction
+    /// cache footprint of sorting small slices low.
+    const MAX_LEN_ALWAYS_INSERTION_SORT: usize = 20;
+
+    /// Sub-slices up to this length are sorted with the general small-sort, which is used for
+    /// types that have no interior mutability. Must be in the range `8..=128`.
+    const SMALL_SORT_GENERAL_THRESHOLD: usize = 32;
+
+    /// Sub-slices up to this length are sorted with the sorting-network based small-sort, which is
+    /// used for small `Copy` types without interior mutability. Must be in the range `8..=128`.
+    const SMALL_SORT_NETWORK_THRESHOLD: usize = 32;
+
+    /// Small-sorts that need a stack array are only used for types where the array is at most
+    /// this many bytes large.
+    const MAX_STACK_ARRAY_SIZE: usize = 4096;
+
+    /// The number of allowed imbalanced partitions before switching to heapsort is
+    /// `IMBALANCE_LIMIT_FACTOR * floor(log2(len))`.
+    const IMBALANCE_LIMIT_FACTOR: u32 = 2;
+
+    /// Types of at least this many bytes are sorted indirectly, by sorting references to the
+    /// elements and moving every element once at the end. Only applies to inputs longer than
+    /// `MAX_LEN_ALWAYS_INSERTION_SORT`. Smaller types profit as well for short inputs, but once
+    /// the elements don't fit into the cache the comparisons through references miss the cache,
+    /// while moving elements during partitioning is a linear scan.
+    const INDIRECT_SORT_MIN_SIZE: usize = 1024;
+
+    /// If `true`, the quicksort does not recurse. Instead it pushes the larger side of every
+    /// partition onto a fixed-size stack and continues with the smaller side, which bounds the
+    /// number of pending sub-slices by `log2(len)`. Otherwise it recurses into the left side,
+    /// which bounds the recursion depth only by the imbalance limit. Both produce the same output.
+    /// Defaults to `true` if the `bounded_stack` feature is enabled.
+    ///
+    /// Only the quicksort shared by [`crate::sort_by_with_config`] and the functions that use the
+    /// default configuration is affected. [`crate::sort_by_min_cmp`], [`crate::sort_dedup`],
+    /// [`crate::sort_pairs`] and [`crate::par_sort`] use quicksorts of their own, which always
+    /// recurse.
+    const BOUNDED_STACK: bool = cfg!(feature = "bounded_stack");
+}
+
+/// The configuration used by [`crate::sort`] and all other sort functions.
+pub struct DefaultConfig;
+
+impl SortConfig for DefaultConfig {}
+
+/// [`DefaultConfig`] without indirect sorting, for [`crate::try_sort_by`]. Violations are only
+/// observed in the slice passed to [`crate::checked::observe_ord_violations`], not in a slice of
+/// references to its elements.
+pub(crate) struct DirectConfig;
+
+impl SortConfig for DirectConfig {
+    const INDIRECT_SORT_MIN_SIZE: usize = usize::MAX;
+}
+
+/// Post-monomorphization check of the configuration, use as `const { check_config::<C>() }`.
+/// `choose_pivot` requires at least 8 elements, and the small-sort scratch buffers have a fixed
+/// maximum size.
+pub(crate) const fn check_config<C: SortConfig>() {
+    assert!(
+        matches!(C::SMALL_SORT_GENERAL_THRESHOLD, 8..=128),
+        "SortConfig::SMALL_SORT_GENERAL_THRESHOLD must be in the range 8..=128"
+    );
+    assert!(
+        matches!(C::SMALL_SORT_NETWORK_THRESHOLD, 8..=128),
+        "SortConfig::SMALL_SORT_NETWORK_THRESHOLD must be in the range 8..=128"
+    );
+}
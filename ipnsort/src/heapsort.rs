@@ -1,7 +1,8 @@
 use core::cmp;
-use core::intrinsics;
 use core::ptr;
 
+use crate::polyfill::intrinsics;
+
 /// Sorts `v` using heapsort, which guarantees *O*(*n* \* log(*n*)) worst-case.
 ///
 /// Never inline this, it sits the main hot-loop in `recurse` and is meant as unlikely algorithmic
@@ -75,3 +76,57 @@ where
         node = child;
     }
 }
+
+/// Sorts `v` using bottom-up heapsort, which performs about *n* \* log2(*n*) comparisons instead of
+/// the 2 \* *n* \* log2(*n*) of [`heapsort`], at the cost of walking every sift path twice.
+#[inline(never)]
+pub(crate) fn heapsort_bottom_up<T, F>(v: &mut [T], is_less: &mut F)
+where
+    F: FnMut(&T, &T) -> bool,
+{
+    let len = v.len();
+
+    for node in (0..len / 2).rev() {
+        sift_down_bottom_up(v, node, is_less);
+    }
+
+    for end in (1..len).rev() {
+        v.swap(0, end);
+        sift_down_bottom_up(&mut v[..end], 0, is_less);
+    }
+}
+
+// Same invariant as `sift_down`. Instead of comparing the sifted element with the greater child at
+// every level, this first descends to a leaf along the greater children, and then climbs back up to
+// where the sifted element belongs, which is usually close to the leaf.
+fn sift_down_bottom_up<T, F>(v: &mut [T], node: usize, is_less: &mut F)
+where
+    F: FnMut(&T, &T) -> bool,
+{
+    let len = v.len();
+
+    let mut pos = node;
+    loop {
+        let child = 2 * pos + 1;
+        if child >= len {
+            break;
+        }
+
+        pos = if child + 1 < len && is_less(&v[child], &v[child + 1]) {
+            child + 1
+        } else {
+            child
+        };
+    }
+
+    while pos != node && is_less(&v[pos], &v[node]) {
+        pos = (pos - 1) / 2;
+    }
+
+    // Move the sifted element to `pos`, and every element on the path from `node` to `pos` one
+    // level up.
+    while pos != node {
+        v.swap(node, pos);
+        pos = (pos - 1) / 2;
+    }
+}
@@ -1,120 +1,1177 @@
 //! Instruction-Parallel-Network Unstable Sort, ipnsort by Lukas Bergdoll
 #![allow(incomplete_features, internal_features, stable_features)]
-#![feature(
-    ptr_sub_ptr,
-    auto_traits,
-    negative_impls,
-    specialization,
-    core_intrinsics,
-    sized_type_properties,
-    const_mut_refs
+#![cfg_attr(
+    not(feature = "stable"),
+    feature(
+        ptr_sub_ptr,
+        auto_traits,
+        negative_impls,
+        specialization,
+        fn_traits,
+        unboxed_closures,
+        core_intrinsics,
+        sized_type_properties,
+        const_mut_refs
+    )
+)]
+#![cfg_attr(
+    all(
+        not(feature = "stable"),
+        any(
+            all(target_arch = "x86_64", target_feature = "ssse3"),
+            all(
+                target_arch = "aarch64",
+                target_feature = "neon",
+                target_endian = "little"
+            ),
+            all(target_arch = "wasm32", target_feature = "simd128"),
+        )
+    ),
+    feature(portable_simd)
 )]
 
 use core::cmp::Ordering;
-use core::intrinsics;
-use core::mem::SizedTypeProperties;
+use core::mem::{self, MaybeUninit};
+
+/// Emits a [`trace::Event`], e.g. `trace_event!(SmallSort { len })`. Expands to nothing without
+/// the `trace` feature, so the event fields are not even evaluated.
+#[cfg(feature = "trace")]
+macro_rules! trace_event {
+    ($($event:tt)*) => {
+        $crate::trace::emit(|| $crate::trace::Event::$($event)*)
+    };
+}
+
+#[cfg(not(feature = "trace"))]
+macro_rules! trace_event {
+    ($($event:tt)*) => {};
+}
+
+mod checked;
+mod config;
+// Only compiled without the `stable` feature, unstable syntax is linted even in disabled code, and
+// will eventually be rejected.
+#[cfg(not(feature = "stable"))]
+mod freeze;
+mod heapsort;
+mod merge;
+mod min_cmp;
+mod networks;
+mod pairs;
+mod parallel;
+mod permutation;
+mod pivot;
+mod polyfill;
+mod quicksort;
+#[cfg(not(feature = "stable"))]
+mod radix;
+mod select;
+mod smallsort;
+#[cfg(feature = "trace")]
+pub mod trace;
+
+pub use checked::OrdViolation;
+use config::DirectConfig;
+pub use config::{DefaultConfig, SortConfig};
+#[cfg(not(feature = "stable"))]
+use freeze::Freeze;
+use pivot::PivotRng;
+use polyfill::{intrinsics, SizedTypeProperties};
+
+/// Sorts the slice, but might not preserve the order of equal elements.
+///
+/// This sort is unstable (i.e., may reorder equal elements), in-place
+/// (i.e., does not allocate, except for types of at least
+/// [`SortConfig::INDIRECT_SORT_MIN_SIZE`] bytes), and *O*(*n* \* log(*n*)) worst-case.
+///
+/// # Current implementation
+///
+/// The current algorithm is based on [pattern-defeating quicksort][pdqsort] by Orson Peters,
+/// which combines the fast average case of randomized quicksort with the fast worst case of
+/// heapsort, while achieving linear time on slices with certain patterns. It uses some
+/// randomization to avoid degenerate cases, but with a fixed seed to always provide
+/// deterministic behavior.
+///
+/// It is typically faster than stable sorting, except in a few special cases, e.g., when the
+/// slice consists of several concatenated sorted sequences.
+///
+/// Slices of primitive integers that are long and look random are sorted with an in-place radix
+/// sort instead, which is not available with the `stable` feature.
+///
+/// Types of at least [`SortConfig::INDIRECT_SORT_MIN_SIZE`] bytes are sorted indirectly, by
+/// sorting references to the elements and then moving every element exactly once. This allocates
+/// two pointer-sized values per element.
+///
+/// # Examples
+///
+/// ```
+/// let mut v = [-5, 4, 1, -3, 2];
+///
+/// v.sort_unstable();
+/// assert!(v == [-5, -3, 1, 2, 4]);
+/// ```
+///
+/// [pdqsort]: https://github.com/orlp/pdqsort
+#[inline(always)]
+pub fn sort<T>(arr: &mut [T])
+where
+    T: Ord,
+{
+    #[cfg(not(feature = "stable"))]
+    if radix::RadixSort::radix_sort_if_random(arr) {
+        return;
+    }
+
+    unstable_sort(arr, polyfill::ord_lt());
+}
+
+/// Sorts the slice with a comparator function, but might not preserve the order of equal
+/// elements.
+///
+/// This sort is unstable (i.e., may reorder equal elements), in-place
+/// (i.e., does not allocate, except for types of at least
+/// [`SortConfig::INDIRECT_SORT_MIN_SIZE`] bytes), and *O*(*n* \* log(*n*)) worst-case.
+///
+/// The comparator function must define a total ordering for the elements in the slice. If
+/// the ordering is not total, the order of the elements is unspecified. An order is a
+/// total order if it is (for all `a`, `b` and `c`):
+///
+/// * total and antisymmetric: exactly one of `a < b`, `a == b` or `a > b` is true, and
+/// * transitive, `a < b` and `b < c` implies `a < c`. The same must hold for both `==` and `>`.
+///
+/// For example, while [`f64`] doesn't implement [`Ord`] because `NaN != NaN`, we can use
+/// `partial_cmp` as our sort function when we know the slice doesn't contain a `NaN`.
+///
+/// ```
+/// let mut floats = [5f64, 4.0, 1.0, 3.0, 2.0];
+/// floats.sort_unstable_by(|a, b| a.partial_cmp(b).unwrap());
+/// assert_eq!(floats, [1.0, 2.0, 3.0, 4.0, 5.0]);
+/// ```
+///
+/// # Current implementation
+///
+/// The current algorithm is based on [pattern-defeating quicksort][pdqsort] by Orson Peters,
+/// which combines the fast average case of randomized quicksort with the fast worst case of
+/// heapsort, while achieving linear time on slices with certain patterns. It uses some
+/// randomization to avoid degenerate cases, but with a fixed seed to always provide
+/// deterministic behavior.
+///
+/// It is typically faster than stable sorting, except in a few special cases, e.g., when the
+/// slice consists of several concatenated sorted sequences.
+///
+/// Large types are sorted indirectly, as described for [`sort`].
+///
+/// # Examples
+///
+/// ```
+/// let mut v = [5, 4, 1, 3, 2];
+/// v.sort_unstable_by(|a, b| a.cmp(b));
+/// assert!(v == [1, 2, 3, 4, 5]);
+///
+/// // reverse sorting
+/// v.sort_unstable_by(|a, b| b.cmp(a));
+/// assert!(v == [5, 4, 3, 2, 1]);
+/// ```
+///
+/// [pdqsort]: https://github.com/orlp/pdqsort
+#[inline(always)]
+pub fn sort_by<T, F>(arr: &mut [T], mut compare: F)
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    unstable_sort(arr, |a, b| compare(a, b) == Ordering::Less);
+}
+
+/// Sorts the slice with a comparator function like [`sort_by`], but reports a comparator that
+/// does not implement a total order as error instead of panicking.
+///
+/// If `Ok(())` is returned, `compare(&v[i + 1], &v[i])` did not return [`Ordering::Less`] for any
+/// `i` in the final check. Not every violation can be detected, so `Ok(())` does not prove that
+/// `compare` implements a total order. All original elements remain in the slice either way.
+///
+/// # Current implementation
+///
+/// See [`sort`]. Violations are detected where the sort can already observe them, when the
+/// merges of the small-sorts find their input was not consumed exactly once, and when the final
+/// check finds two adjacent elements out of order, e.g. because elements were placed on the wrong
+/// side of a partition. The final check adds `v.len() - 1` comparisons. Unlike [`sort`], types of
+/// at least [`SortConfig::INDIRECT_SORT_MIN_SIZE`] bytes are not sorted indirectly, so that the
+/// violations are found in the slice itself.
+///
+/// # Errors
+///
+/// Returns the first [`OrdViolation`] that was detected. The sort continues after a violation was
+/// detected, so the indices refer to the slice after this function returned.
+///
+/// # Examples
+///
+/// ```
+/// let mut v = [5, 4, 1, 3, 2];
+/// assert_eq!(ipnsort::try_sort_by(&mut v, |a, b| a.cmp(b)), Ok(()));
+/// assert!(v == [1, 2, 3, 4, 5]);
+///
+/// // Claims that every element is less than every other element.
+/// let result = ipnsort::try_sort_by(&mut v, |_, _| core::cmp::Ordering::Less);
+/// assert!(result.is_err());
+/// ```
+#[inline]
+pub fn try_sort_by<T, F>(arr: &mut [T], mut compare: F) -> Result<(), OrdViolation>
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    let mut is_less = |a: &T, b: &T| compare(a, b) == Ordering::Less;
+
+    if let Some(violation) = checked::observe_ord_violations(arr, |v| {
+        unstable_sort_with_config::<DirectConfig, T, _>(v, None, &mut is_less)
+    }) {
+        return Err(violation);
+    }
+
+    match arr.windows(2).position(|w| is_less(&w[1], &w[0])) {
+        Some(i) => Err(OrdViolation::new(i, i + 1)),
+        None => Ok(()),
+    }
+}
+
+/// Sorts the slice with a comparator function, calling it as few times as possible, but might not
+/// preserve the order of equal elements.
+///
+/// This sort is unstable (i.e., may reorder equal elements), in-place
+/// (i.e., does not allocate), and *O*(*n* \* log(*n*)) worst-case. Prefer it over [`sort_by`] if
+/// comparisons are expensive compared to moving elements, e.g. for locale-aware string collation
+/// or comparisons that need a lookup.
+///
+/// # Current implementation
+///
+/// A quicksort that picks the median of about 2 \* sqrt(*n*) elements as pivot, and uses binary
+/// insertion sort for small slices and bottom-up heapsort as fallback. On random inputs it needs
+/// about 1% more comparisons than the lower bound of log2(*n*!) for *n* = 10⁶, where [`sort_by`]
+/// needs about 11% more. It moves elements more often than [`sort_by`], and is slower unless
+/// comparisons are expensive.
+///
+/// # Examples
+///
+/// ```
+/// let mut v = ["delta", "Alpha", "charlie", "Bravo"];
+///
+/// ipnsort::sort_by_min_cmp(&mut v, |a, b| a.to_lowercase().cmp(&b.to_lowercase()));
+/// assert!(v == ["Alpha", "Bravo", "charlie", "delta"]);
+/// ```
+#[inline]
+pub fn sort_by_min_cmp<T, F>(arr: &mut [T], mut compare: F)
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    // Arrays of zero-sized types are always all-equal, and thus sorted.
+    if T::IS_ZST {
+        return;
+    }
+
+    min_cmp::sort_min_cmp(arr, &mut |a, b| compare(a, b) == Ordering::Less);
+}
+
+/// Sorts the array with a sorting network, but might not preserve the order of equal elements.
+///
+/// This sort is unstable (i.e., may reorder equal elements), in-place (i.e., does not allocate),
+/// and for `N <= 32` performs the same comparisons in the same order for every input, which makes
+/// it branchless for types where comparing and swapping is branchless.
+///
+/// # Current implementation
+///
+/// For `N <= 32` the array is sorted with a fixed sorting network. For `N <= 17` and `N` equal to
+/// 19, 20, 21, 23, 24, 27 and 29 to 32 it has the smallest known number of comparators, which is
+/// proven optimal for `N <= 12`. It needs one comparator more for `N` equal to 18, 22, 25, 26 and
+/// 28. Larger arrays are sorted with [`sort`].
+///
+/// # Examples
+///
+/// ```
+/// let mut v = [-5, 4, 1, -3, 2];
+///
+/// ipnsort::sort_array(&mut v);
+/// assert!(v == [-5, -3, 1, 2, 4]);
+/// ```
+#[inline]
+pub fn sort_array<T, const N: usize>(arr: &mut [T; N])
+where
+    T: Ord,
+{
+    array_sort(arr, polyfill::ord_lt());
+}
+
+/// Sorts the array with a sorting network and a comparator function, but might not preserve the
+/// order of equal elements.
+///
+/// See [`sort_array`] for the guarantees and the current implementation.
+///
+/// # Examples
+///
+/// ```
+/// let mut v = [5, 4, 1, 3, 2];
+///
+/// ipnsort::sort_array_by(&mut v, |a, b| b.cmp(a));
+/// assert!(v == [5, 4, 3, 2, 1]);
+/// ```
+#[inline]
+pub fn sort_array_by<T, F, const N: usize>(arr: &mut [T; N], mut compare: F)
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    array_sort(arr, |a, b| compare(a, b) == Ordering::Less);
+}
+
+/// Sorts the slice with pivots chosen based on a random seed, but might not preserve the order of
+/// equal elements.
+///
+/// This sort is unstable (i.e., may reorder equal elements), in-place
+/// (i.e., does not allocate, except for types of at least
+/// [`SortConfig::INDIRECT_SORT_MIN_SIZE`] bytes), and *O*(*n* \* log(*n*)) worst-case.
+///
+/// # Current implementation
+///
+/// Same as [`sort`], except that the pivots are sampled at positions drawn from a seed that is
+/// random for every call, and there is no radix sort. [`sort`] samples at fixed positions, which
+/// allows an attacker who knows the algorithm to craft inputs that reliably cause bad pivots,
+/// until the heapsort fallback is reached. With random positions no input is reliably bad.
+///
+/// The seed comes from [`RandomState`](std::hash::RandomState). As a consequence, the order of
+/// equal elements may differ between calls with the same input.
+///
+/// # Examples
+///
+/// ```
+/// let mut v = [-5, 4, 1, -3, 2];
+///
+/// ipnsort::sort_hardened(&mut v);
+/// assert!(v == [-5, -3, 1, 2, 4]);
+/// ```
+#[inline]
+pub fn sort_hardened<T>(arr: &mut [T])
+where
+    T: Ord,
+{
+    hardened_sort(arr, polyfill::ord_lt());
+}
+
+/// Sorts the slice with a comparator function and pivots chosen based on a random seed, but might
+/// not preserve the order of equal elements.
+///
+/// See [`sort_hardened`] for the guarantees and the current implementation.
+///
+/// # Examples
+///
+/// ```
+/// let mut v = [5, 4, 1, 3, 2];
+///
+/// ipnsort::sort_hardened_by(&mut v, |a, b| b.cmp(a));
+/// assert!(v == [5, 4, 3, 2, 1]);
+/// ```
+#[inline]
+pub fn sort_hardened_by<T, F>(arr: &mut [T], mut compare: F)
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    hardened_sort(arr, |a, b| compare(a, b) == Ordering::Less);
+}
+
+/// Sorts the slice of floats, but might not preserve the order of equal elements.
+///
+/// This sort is unstable (i.e., may reorder equal elements), in-place
+/// (i.e., does not allocate), and *O*(*n* \* log(*n*)) worst-case. It uses the ordering defined by
+/// [`f64::total_cmp`], which orders `NaN` values according to their sign.
+///
+/// # Current implementation
+///
+/// See [`sort`]. The partition compares multiple elements at once using SIMD instructions, which
+/// is not available with the `stable` feature.
+///
+/// # Examples
+///
+/// ```
+/// let mut v = [2.6, -5e-8, f64::NAN, 8.29, f64::INFINITY, -1.0, 0.0, -f64::INFINITY, -0.0];
+///
+/// ipnsort::sort_floats(&mut v);
+/// let sorted = [-f64::INFINITY, -1.0, -5e-8, -0.0, 0.0, 2.6, 8.29, f64::INFINITY];
+/// assert_eq!(&v[..8], &sorted);
+/// assert!(v[8].is_nan());
+/// ```
+#[inline(always)]
+pub fn sort_floats<T>(arr: &mut [T])
+where
+    T: Float,
+{
+    unstable_sort(arr, polyfill::total_lt());
+}
+
+/// Floating point types that can be sorted with [`sort_floats`].
+///
+/// This trait is sealed and implemented for [`f32`] and [`f64`].
+pub trait Float: Copy + private::Sealed {
+    #[doc(hidden)]
+    fn total_cmp(&self, other: &Self) -> Ordering;
+}
+
+macro_rules! impl_float {
+    ($($t:ty),*) => {
+        $(
+            impl private::Sealed for $t {}
+
+            impl Float for $t {
+                #[inline(always)]
+                fn total_cmp(&self, other: &Self) -> Ordering {
+                    <$t>::total_cmp(self, other)
+                }
+            }
+        )*
+    };
+}
+
+impl_float!(f32, f64);
+
+mod private {
+    pub trait Sealed {}
+}
+
+/// Sorts the slice with the tuning parameters of `C`, but might not preserve the order of equal
+/// elements.
+///
+/// Same as [`sort`], which uses [`DefaultConfig`]. Intended for tuning experiments, e.g.
+/// benchmarking different small-sort thresholds side by side.
+///
+/// # Panics
+///
+/// Fails to compile if a parameter of `C` is outside its valid range, see [`SortConfig`].
+///
+/// # Examples
+///
+/// ```
+/// struct LowImbalanceLimit;
+///
+/// impl ipnsort::SortConfig for LowImbalanceLimit {
+///     const IMBALANCE_LIMIT_FACTOR: u32 = 1;
+/// }
+///
+/// let mut v = [-5, 4, 1, -3, 2];
+///
+/// ipnsort::sort_with_config::<LowImbalanceLimit, _>(&mut v);
+/// assert!(v == [-5, -3, 1, 2, 4]);
+/// ```
+#[inline(always)]
+pub fn sort_with_config<C, T>(arr: &mut [T])
+where
+    C: SortConfig,
+    T: Ord,
+{
+    unstable_sort_with_config::<C, T, _>(arr, None, polyfill::ord_lt());
+}
+
+/// Sorts the slice with a comparator function and the tuning parameters of `C`, but might not
+/// preserve the order of equal elements.
+///
+/// See [`sort_with_config`] and [`sort_by`].
+#[inline(always)]
+pub fn sort_by_with_config<C, T, F>(arr: &mut [T], mut compare: F)
+where
+    C: SortConfig,
+    F: FnMut(&T, &T) -> Ordering,
+{
+    unstable_sort_with_config::<C, T, _>(arr, None, |a, b| compare(a, b) == Ordering::Less);
+}
+
+/// Sorts the slice with a key extraction function, but might not preserve the order of equal
+/// elements.
+///
+/// This sort is unstable (i.e., may reorder equal elements), in-place
+/// (i.e., does not allocate, except for types of at least
+/// [`SortConfig::INDIRECT_SORT_MIN_SIZE`] bytes), and *O*(*m* \* *n* \* log(*n*)) worst-case, where the
+/// key function is *O*(*m*).
+///
+/// # Current implementation
+///
+/// See [`sort`]. The key function is called twice per comparison, for expensive key functions
+/// [`sort_by_cached_key`] is likely to be faster.
+///
+/// # Examples
+///
+/// ```
+/// let mut v = [-5i32, 4, 1, -3, 2];
+///
+/// ipnsort::sort_by_key(&mut v, |k| k.abs());
+/// assert!(v == [1, 2, -3, 4, -5]);
+/// ```
+#[inline(always)]
+pub fn sort_by_key<T, K, F>(arr: &mut [T], mut f: F)
+where
+    F: FnMut(&T) -> K,
+    K: Ord,
+{
+    unstable_sort(arr, |a, b| f(a).lt(&f(b)));
+}
+
+/// Sorts the slice with a key extraction function, caching the keys.
+///
+/// The order of equal elements is preserved, because the cached keys are paired with the
+/// original index of each element.
+///
+/// This sort is *O*(*m* \* *n* + *n* \* log(*n*)) worst-case, where the key function is *O*(*m*).
+/// The key function is called exactly once per element.
+///
+/// # Current implementation
+///
+/// Allocates a temporary buffer of `(key, index)` pairs, sorts them with [`sort`] and then
+/// applies the resulting permutation to the slice in-place. The index type is chosen as the
+/// smallest unsigned integer that can represent all indices, to reduce the size of the
+/// allocation.
+///
+/// # Examples
+///
+/// ```
+/// let mut v = [-5i32, 4, 32, -3, 2];
+///
+/// ipnsort::sort_by_cached_key(&mut v, |k| k.to_string());
+/// assert!(v == [-3, -5, 2, 32, 4]);
+/// ```
+#[inline]
+pub fn sort_by_cached_key<T, K, F>(arr: &mut [T], f: F)
+where
+    F: FnMut(&T) -> K,
+    K: Ord,
+{
+    // Helper macro for indexing our vector by the smallest possible type, to reduce allocation.
+    macro_rules! sort_by_key {
+        ($t:ty, $slice:ident, $f:ident) => {{
+            let mut indices: Vec<_> = $slice
+                .iter()
+                .map($f)
+                .enumerate()
+                .map(|(i, k)| (k, i as $t))
+                .collect();
+            // The elements of `indices` are unique, as they are indexed, so any sort will be
+            // stable with respect to the original slice.
+            sort(&mut indices);
+            for i in 0..$slice.len() {
+                let mut index = indices[i].1;
+                while (index as usize) < i {
+                    index = indices[index as usize].1;
+                }
+                indices[i].1 = index;
+                $slice.swap(i, index as usize);
+            }
+        }};
+    }
+
+    let len = arr.len();
+    if len < 2 {
+        return;
+    }
+
+    let sz_u8 = mem::size_of::<(K, u8)>();
+    let sz_u16 = mem::size_of::<(K, u16)>();
+    let sz_u32 = mem::size_of::<(K, u32)>();
+    let sz_usize = mem::size_of::<(K, usize)>();
+
+    if sz_u8 < sz_u16 && len <= (u8::MAX as usize) {
+        return sort_by_key!(u8, arr, f);
+    }
+    if sz_u16 < sz_u32 && len <= (u16::MAX as usize) {
+        return sort_by_key!(u16, arr, f);
+    }
+    if sz_u32 < sz_usize && len <= (u32::MAX as usize) {
+        return sort_by_key!(u32, arr, f);
+    }
+    sort_by_key!(usize, arr, f)
+}
+
+/// Reorders the slice such that the element at `index` after the reordering is at its final
+/// sorted position.
+///
+/// This reordering has the additional property that any value at position `i < index` will be
+/// less than or equal to any value at a position `j > index`. Additionally, this reordering is
+/// unstable (i.e. any number of equal elements may end up at position `index`), in-place
+/// (i.e. does not allocate), and runs in *O*(*n*) time. This function is also known as "kth
+/// element" in other libraries.
+///
+/// It returns a triplet of the following from the reordered slice: the subslice prior to
+/// `index`, the element at `index`, and the subslice after `index`; accordingly, the values in
+/// those two subslices will respectively all be less-than-or-equal-to and
+/// greater-than-or-equal-to the value of the element at `index`.
+///
+/// # Current implementation
+///
+/// The current algorithm is an introselect implementation based on ipnsort, which is also the
+/// basis for [`sort`]. The fallback algorithm is Median of Medians using Tukey's Ninther for
+/// pivot selection, which guarantees linear runtime for all inputs.
+///
+/// If `is_less` panics or does not implement a total order, all original elements will remain in
+/// `v` and any possible modifications via interior mutability will be observable, the same way as
+/// with [`sort`].
+///
+/// # Panics
+///
+/// Panics when `index >= len()`, meaning it always panics on empty slices.
+///
+/// # Examples
+///
+/// ```
+/// let mut v = [-5i32, 4, 2, -3, 1];
+///
+/// // Find the items less than or equal to the median, the median, and greater than or equal to
+/// // the median.
+/// let (lesser, median, greater) = ipnsort::select_nth_unstable(&mut v, 2);
+///
+/// assert!(lesser == [-3, -5] || lesser == [-5, -3]);
+/// assert_eq!(median, &mut 1);
+/// assert!(greater == [4, 2] || greater == [2, 4]);
+/// ```
+#[inline]
+pub fn select_nth_unstable<T>(v: &mut [T], index: usize) -> (&mut [T], &mut T, &mut [T])
+where
+    T: Ord,
+{
+    select::partition_at_index(v, index, T::lt)
+}
+
+/// Reorders the slice with a comparator function such that the element at `index` after the
+/// reordering is at its final sorted position.
+///
+/// See [`select_nth_unstable`] for the guarantees and the current implementation.
+///
+/// # Panics
+///
+/// Panics when `index >= len()`, meaning it always panics on empty slices.
+///
+/// # Examples
+///
+/// ```
+/// let mut v = [-5i32, 4, 2, -3, 1];
+///
+/// // Find the items less than or equal to the median, the median, and greater than or equal to
+/// // the median as if the slice were sorted in descending order.
+/// let (lesser, median, greater) = ipnsort::select_nth_unstable_by(&mut v, 2, |a, b| b.cmp(a));
+///
+/// assert!(lesser == [4, 2] || lesser == [2, 4]);
+/// assert_eq!(median, &mut 1);
+/// assert!(greater == [-3, -5] || greater == [-5, -3]);
+/// ```
+#[inline]
+pub fn select_nth_unstable_by<T, F>(
+    v: &mut [T],
+    index: usize,
+    mut compare: F,
+) -> (&mut [T], &mut T, &mut [T])
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    select::partition_at_index(v, index, |a: &T, b: &T| compare(a, b) == Ordering::Less)
+}
+
+/// Reorders the slice with a key extraction function such that the element at `index` after the
+/// reordering is at its final sorted position.
+///
+/// See [`select_nth_unstable`] for the guarantees and the current implementation.
+///
+/// # Panics
+///
+/// Panics when `index >= len()`, meaning it always panics on empty slices.
+///
+/// # Examples
+///
+/// ```
+/// let mut v = [-5i32, 4, 1, -3, 2];
+///
+/// // Find the items less than or equal to the median, the median, and greater than or equal to
+/// // the median as if the slice were sorted according to absolute value.
+/// let (lesser, median, greater) = ipnsort::select_nth_unstable_by_key(&mut v, 2, |a| a.abs());
+///
+/// assert!(lesser == [1, 2] || lesser == [2, 1]);
+/// assert_eq!(median, &mut -3);
+/// assert!(greater == [4, -5] || greater == [-5, 4]);
+/// ```
+#[inline]
+pub fn select_nth_unstable_by_key<T, K, F>(
+    v: &mut [T],
+    index: usize,
+    mut f: F,
+) -> (&mut [T], &mut T, &mut [T])
+where
+    F: FnMut(&T) -> K,
+    K: Ord,
+{
+    select::partition_at_index(v, index, |a: &T, b: &T| f(a).lt(&f(b)))
+}
+
+/// Partially sorts the slice, such that `v[..k]` contains the `k` smallest elements in sorted
+/// order, but might not preserve the order of equal elements.
+///
+/// The order of the remaining elements in `v[k..]` is unspecified. If `k >= v.len()` the whole
+/// slice is sorted.
+///
+/// This sort is unstable (i.e., may reorder equal elements), in-place (i.e., does not allocate,
+/// except for types of at least [`SortConfig::INDIRECT_SORT_MIN_SIZE`] bytes if `k >= v.len()`),
+/// and *O*(*n* + *k* \* log(*k*)) worst-case.
+///
+/// # Current implementation
+///
+/// The current algorithm uses the same quicksort as [`sort`], but only recurses into partitions
+/// that overlap `[0, k)`. Should too many imbalanced partitions occur, it selects the `k` smallest
+/// elements in linear time and sorts them with heapsort.
+///
+/// # Examples
+///
+/// ```
+/// let mut v = [-5, 4, 1, -3, 2];
+///
+/// ipnsort::partial_sort(&mut v, 3);
+/// assert!(v[..3] == [-5, -3, 1]);
+/// ```
+#[inline]
+pub fn partial_sort<T>(v: &mut [T], k: usize)
+where
+    T: Ord,
+{
+    partial_unstable_sort(v, k, polyfill::ord_lt());
+}
+
+/// Partially sorts the slice with a comparator function, such that `v[..k]` contains the `k`
+/// smallest elements in sorted order, but might not preserve the order of equal elements.
+///
+/// See [`partial_sort`] for the guarantees and the current implementation.
+///
+/// # Examples
+///
+/// ```
+/// let mut v = [-5, 4, 1, -3, 2];
+///
+/// // Top 2 elements in descending order.
+/// ipnsort::partial_sort_by(&mut v, 2, |a, b| b.cmp(a));
+/// assert!(v[..2] == [4, 2]);
+/// ```
+#[inline]
+pub fn partial_sort_by<T, F>(v: &mut [T], k: usize, mut compare: F)
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    partial_unstable_sort(v, k, |a, b| compare(a, b) == Ordering::Less);
+}
+
+/// Sorts the slice and removes consecutive duplicates, returning the number of unique elements.
+///
+/// Afterwards `v[..n]` contains one element of each group of equal elements in sorted order,
+/// where `n` is the returned value. Which element of a group is kept, and the order of the
+/// duplicates in `v[n..]`, is unspecified. For a `Vec` this is equivalent to calling `sort` and
+/// `dedup`, followed by `v.truncate(n)`.
+///
+/// This is in-place (i.e., does not allocate), and *O*(*n* \* log(*n*)) worst-case.
+///
+/// # Current implementation
+///
+/// The current algorithm uses the same quicksort as [`sort`]. Whenever it finds a group of
+/// elements equal to an earlier pivot, which happens often for inputs with many duplicates, the
+/// group is discarded instead of sorted. The sub-slices are sorted from left to right, and their
+/// unique elements are moved to the front as soon as they are sorted, so each element is moved
+/// into its final place at most once.
+///
+/// # Examples
+///
+/// ```
+/// let mut v = vec![4, -5, 1, 4, -5, 2, 4];
+///
+/// let n = ipnsort::sort_dedup(&mut v);
+/// v.truncate(n);
+/// assert!(v == [-5, 1, 2, 4]);
+/// ```
+#[inline]
+pub fn sort_dedup<T>(v: &mut [T]) -> usize
+where
+    T: Ord,
+{
+    dedup_unstable_sort(v, polyfill::ord_lt())
+}
 
-mod heapsort;
-mod pivot;
-mod quicksort;
-mod smallsort;
+/// Sorts the slice with a comparator function and removes consecutive elements that compare
+/// equal, returning the number of unique elements.
+///
+/// See [`sort_dedup`] for the guarantees and the current implementation.
+///
+/// # Examples
+///
+/// ```
+/// let mut v = [4, -5, 1, 4, -5, 2, 4];
+///
+/// // Unique elements in descending order.
+/// let n = ipnsort::sort_dedup_by(&mut v, |a, b| b.cmp(a));
+/// assert!(v[..n] == [4, 2, 1, -5]);
+/// ```
+#[inline]
+pub fn sort_dedup_by<T, F>(v: &mut [T], mut compare: F) -> usize
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    dedup_unstable_sort(v, |a, b| compare(a, b) == Ordering::Less)
+}
 
-/// Sorts the slice, but might not preserve the order of equal elements.
+/// Sorts the slice, given that `v[..sorted_prefix_len]` is already sorted, but might not preserve
+/// the order of equal elements.
 ///
-/// This sort is unstable (i.e., may reorder equal elements), in-place
-/// (i.e., does not allocate), and *O*(*n* \* log(*n*)) worst-case.
+/// This is the common case of appending new elements to an already sorted vector. Only the
+/// appended elements are sorted, which are then merged with the prefix. If the prefix is not
+/// sorted, the order of the elements is unspecified.
+///
+/// This is in-place (i.e., does not allocate, except for types of at least
+/// [`SortConfig::INDIRECT_SORT_MIN_SIZE`] bytes), and *O*(*n* \* log(*k*)) worst-case, where *k*
+/// is the number of appended elements. With a scratch buffer for *k* elements, see
+/// [`sort_appended_with_scratch`], it is *O*(*n* + *k* \* log(*k*)) worst-case.
 ///
 /// # Current implementation
 ///
-/// The current algorithm is based on [pattern-defeating quicksort][pdqsort] by Orson Peters,
-/// which combines the fast average case of randomized quicksort with the fast worst case of
-/// heapsort, while achieving linear time on slices with certain patterns. It uses some
-/// randomization to avoid degenerate cases, but with a fixed seed to always provide
-/// deterministic behavior.
+/// The appended elements are sorted with [`sort`]. The merge skips elements at the start of the
+/// prefix that are not greater than all appended elements with a binary search. The rest is
+/// merged in place by exchanging parts of both sides with rotations, and merging the resulting
+/// pairs of sub-slices separately.
 ///
-/// It is typically faster than stable sorting, except in a few special cases, e.g., when the
-/// slice consists of several concatenated sorted sequences.
+/// # Panics
+///
+/// Panics if `sorted_prefix_len > v.len()`.
 ///
 /// # Examples
 ///
 /// ```
-/// let mut v = [-5, 4, 1, -3, 2];
+/// let mut v = vec![-5, -3, 1, 4];
+/// v.extend([2, -4]);
 ///
-/// v.sort_unstable();
-/// assert!(v == [-5, -3, 1, 2, 4]);
+/// ipnsort::sort_appended(&mut v, 4);
+/// assert!(v == [-5, -4, -3, 1, 2, 4]);
 /// ```
+#[inline]
+pub fn sort_appended<T>(v: &mut [T], sorted_prefix_len: usize)
+where
+    T: Ord,
+{
+    sort_appended_with_scratch(v, sorted_prefix_len, &mut []);
+}
+
+/// Sorts the slice with a comparator function, given that `v[..sorted_prefix_len]` is already
+/// sorted with it, but might not preserve the order of equal elements.
 ///
-/// [pdqsort]: https://github.com/orlp/pdqsort
-#[inline(always)]
-pub fn sort<T>(arr: &mut [T])
+/// See [`sort_appended`] for the guarantees and the current implementation.
+///
+/// # Panics
+///
+/// Panics if `sorted_prefix_len > v.len()`.
+///
+/// # Examples
+///
+/// ```
+/// let mut v = vec![4, 1, -3, -5];
+/// v.extend([2, -4]);
+///
+/// // Reverse sorting.
+/// ipnsort::sort_appended_by(&mut v, 4, |a, b| b.cmp(a));
+/// assert!(v == [4, 2, 1, -3, -4, -5]);
+/// ```
+#[inline]
+pub fn sort_appended_by<T, F>(v: &mut [T], sorted_prefix_len: usize, compare: F)
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    sort_appended_by_with_scratch(v, sorted_prefix_len, &mut [], compare);
+}
+
+/// Sorts the slice like [`sort_appended`], using `scratch` to merge the appended elements.
+///
+/// If `scratch` can hold as many elements as the shorter of the prefix and the appended part,
+/// typically the appended part, the merge moves every element once. With a shorter `scratch` it
+/// is used for the parts of the in-place merge that fit into it. The contents of `scratch` are
+/// unspecified afterwards.
+///
+/// # Panics
+///
+/// Panics if `sorted_prefix_len > v.len()`.
+///
+/// # Examples
+///
+/// ```
+/// use std::mem::MaybeUninit;
+///
+/// let mut v = vec![-5, -3, 1, 4];
+/// v.extend([2, -4]);
+///
+/// let mut scratch = [MaybeUninit::uninit(); 2];
+/// ipnsort::sort_appended_with_scratch(&mut v, 4, &mut scratch);
+/// assert!(v == [-5, -4, -3, 1, 2, 4]);
+/// ```
+#[inline]
+pub fn sort_appended_with_scratch<T>(
+    v: &mut [T],
+    sorted_prefix_len: usize,
+    scratch: &mut [MaybeUninit<T>],
+) where
+    T: Ord,
+{
+    assert!(
+        sorted_prefix_len <= v.len(),
+        "sorted prefix length {sorted_prefix_len} out of range for slice of length {}",
+        v.len()
+    );
+
+    sort(&mut v[sorted_prefix_len..]);
+    merge::merge(v, sorted_prefix_len, scratch, &mut polyfill::ord_lt());
+}
+
+/// Sorts the slice with a comparator function like [`sort_appended_by`], using `scratch` to merge
+/// the appended elements.
+///
+/// See [`sort_appended_with_scratch`] for the use of `scratch`.
+///
+/// # Panics
+///
+/// Panics if `sorted_prefix_len > v.len()`.
+#[inline]
+pub fn sort_appended_by_with_scratch<T, F>(
+    v: &mut [T],
+    sorted_prefix_len: usize,
+    scratch: &mut [MaybeUninit<T>],
+    mut compare: F,
+) where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    assert!(
+        sorted_prefix_len <= v.len(),
+        "sorted prefix length {sorted_prefix_len} out of range for slice of length {}",
+        v.len()
+    );
+
+    let mut is_less = |a: &T, b: &T| compare(a, b) == Ordering::Less;
+    unstable_sort(&mut v[sorted_prefix_len..], &mut is_less);
+    merge::merge(v, sorted_prefix_len, scratch, &mut is_less);
+}
+
+/// Returns the permutation that sorts the slice, without moving any of its elements.
+///
+/// The returned `perm` satisfies `v[perm[i]] <= v[perm[i + 1]]` for all valid `i`. The relative
+/// order of indices that refer to equal elements is unspecified.
+///
+/// This is *O*(*n* \* log(*n*)) worst-case, and allocates the returned index vector.
+///
+/// # Current implementation
+///
+/// Sorts the indices `0..v.len()` with [`sort_by`], comparing the referenced elements. The
+/// permutation can be applied to `v` and any number of companion slices with
+/// [`apply_permutation`].
+///
+/// # Examples
+///
+/// ```
+/// let v = [-5, 4, 1, -3, 2];
+///
+/// let perm = ipnsort::argsort(&v);
+/// assert!(perm == [0, 3, 2, 4, 1]);
+/// ```
+#[inline]
+pub fn argsort<T>(v: &[T]) -> Vec<usize>
 where
     T: Ord,
 {
-    unstable_sort(arr, |a, b| a.lt(b));
+    argsort_by(v, T::cmp)
 }
 
-/// Sorts the slice with a comparator function, but might not preserve the order of equal
-/// elements.
+/// Returns the permutation that sorts the slice with a comparator function, without moving any
+/// of its elements.
 ///
-/// This sort is unstable (i.e., may reorder equal elements), in-place
-/// (i.e., does not allocate), and *O*(*n* \* log(*n*)) worst-case.
+/// See [`argsort`] for the guarantees and the current implementation.
 ///
-/// The comparator function must define a total ordering for the elements in the slice. If
-/// the ordering is not total, the order of the elements is unspecified. An order is a
-/// total order if it is (for all `a`, `b` and `c`):
+/// # Examples
 ///
-/// * total and antisymmetric: exactly one of `a < b`, `a == b` or `a > b` is true, and
-/// * transitive, `a < b` and `b < c` implies `a < c`. The same must hold for both `==` and `>`.
+/// ```
+/// let v = [-5, 4, 1, -3, 2];
 ///
-/// For example, while [`f64`] doesn't implement [`Ord`] because `NaN != NaN`, we can use
-/// `partial_cmp` as our sort function when we know the slice doesn't contain a `NaN`.
+/// // Permutation for descending order.
+/// let perm = ipnsort::argsort_by(&v, |a, b| b.cmp(a));
+/// assert!(perm == [1, 4, 2, 3, 0]);
+/// ```
+#[inline]
+pub fn argsort_by<T, F>(v: &[T], mut compare: F) -> Vec<usize>
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    let mut perm: Vec<usize> = (0..v.len()).collect();
+    unstable_sort(&mut perm, |&a, &b| compare(&v[a], &v[b]) == Ordering::Less);
+    perm
+}
+
+/// Rearranges the slice in-place according to `perm`, such that afterwards `v[i]` holds the
+/// element that was previously at `v[perm[i]]`.
+///
+/// This is the gather order produced by [`argsort`], so applying its result sorts `v`. `perm`
+/// is temporarily modified to mark already placed elements, and restored before returning. This
+/// makes it possible to apply the same permutation to several companion slices.
+///
+/// This is *O*(*n*) and does not allocate. Every element is moved exactly once.
+///
+/// # Panics
+///
+/// Panics if `perm.len() != v.len()`, or if `perm` is not a permutation of `0..v.len()`. Both
+/// `v` and `perm` remain unmodified in that case.
+///
+/// # Examples
 ///
 /// ```
-/// let mut floats = [5f64, 4.0, 1.0, 3.0, 2.0];
-/// floats.sort_unstable_by(|a, b| a.partial_cmp(b).unwrap());
-/// assert_eq!(floats, [1.0, 2.0, 3.0, 4.0, 5.0]);
+/// let mut keys = [3, 1, 2];
+/// let mut names = ["c", "a", "b"];
+///
+/// let mut perm = ipnsort::argsort(&keys);
+/// ipnsort::apply_permutation(&mut keys, &mut perm);
+/// ipnsort::apply_permutation(&mut names, &mut perm);
+///
+/// assert!(keys == [1, 2, 3]);
+/// assert!(names == ["a", "b", "c"]);
+/// ```
+#[inline]
+pub fn apply_permutation<T>(v: &mut [T], perm: &mut [usize]) {
+    permutation::apply_permutation(v, perm);
+}
+
+/// Sorts `keys` and applies the same reordering to `values`, but might not preserve the order of
+/// equal keys.
+///
+/// This is equivalent to sorting a slice of `(key, value)` pairs by key, for data that is stored
+/// as two separate slices. Only the keys are compared.
+///
+/// This sort is unstable (i.e., may reorder equal elements), in-place (i.e., does not allocate),
+/// and *O*(*n* \* log(*n*)) worst-case.
+///
+/// # Current implementation
+///
+/// The same algorithm as [`sort`], with every element move in `keys` mirrored in `values`. Pivot
+/// selection and partitioning only read from `keys`, which keeps the memory accessed for
+/// comparisons dense.
+///
+/// # Panics
+///
+/// Panics if `keys.len() != values.len()`.
+///
+/// # Examples
+///
+/// ```
+/// let mut keys = [3, 1, 2];
+/// let mut values = ["c", "a", "b"];
+///
+/// ipnsort::sort_pairs(&mut keys, &mut values);
+/// assert!(keys == [1, 2, 3]);
+/// assert!(values == ["a", "b", "c"]);
+/// ```
+#[inline]
+pub fn sort_pairs<K, V>(keys: &mut [K], values: &mut [V])
+where
+    K: Ord,
+{
+    pairs::sort_pairs(keys, values, &mut K::lt);
+}
+
+/// Sorts `keys` with a comparator function and applies the same reordering to `values`, but
+/// might not preserve the order of equal keys.
+///
+/// See [`sort_pairs`] for the guarantees and the current implementation.
+///
+/// # Panics
+///
+/// Panics if `keys.len() != values.len()`.
+///
+/// # Examples
+///
+/// ```
+/// let mut keys = [3, 1, 2];
+/// let mut values = ["c", "a", "b"];
+///
+/// ipnsort::sort_pairs_by(&mut keys, &mut values, |a, b| b.cmp(a));
+/// assert!(keys == [3, 2, 1]);
+/// assert!(values == ["c", "b", "a"]);
 /// ```
+#[inline]
+pub fn sort_pairs_by<K, V, F>(keys: &mut [K], values: &mut [V], mut compare: F)
+where
+    F: FnMut(&K, &K) -> Ordering,
+{
+    pairs::sort_pairs(keys, values, &mut |a, b| compare(a, b) == Ordering::Less);
+}
+
+/// Sorts the slice using multiple threads, but might not preserve the order of equal elements.
+///
+/// This sort is unstable (i.e., may reorder equal elements), and *O*(*n* \* log(*n*))
+/// worst-case. It allocates a small amount of memory to keep track of the spawned threads.
 ///
 /// # Current implementation
 ///
-/// The current algorithm is based on [pattern-defeating quicksort][pdqsort] by Orson Peters,
-/// which combines the fast average case of randomized quicksort with the fast worst case of
-/// heapsort, while achieving linear time on slices with certain patterns. It uses some
-/// randomization to avoid degenerate cases, but with a fixed seed to always provide
-/// deterministic behavior.
+/// The same algorithm as [`sort`], with the two sides of each partition sorted concurrently. At
+/// most [`std::thread::available_parallelism`] threads are used, spawned with
+/// [`std::thread::scope`]. Slices and sub-slices below a length threshold are sorted on the
+/// current thread, so there is no overhead for small inputs. Longer slices of types of at least
+/// [`SortConfig::INDIRECT_SORT_MIN_SIZE`] bytes are sorted directly instead of indirectly, which
+/// can order equal elements differently. For smaller types the result is the same as that of
+/// [`sort`].
 ///
-/// It is typically faster than stable sorting, except in a few special cases, e.g., when the
-/// slice consists of several concatenated sorted sequences.
+/// # Examples
+///
+/// ```
+/// let mut v = [-5, 4, 1, -3, 2];
+///
+/// ipnsort::par_sort(&mut v);
+/// assert!(v == [-5, -3, 1, 2, 4]);
+/// ```
+#[inline]
+pub fn par_sort<T>(v: &mut [T])
+where
+    T: Ord + Send,
+{
+    parallel::par_unstable_sort(v, &T::lt);
+}
+
+/// Sorts the slice with a comparator function using multiple threads, but might not preserve the
+/// order of equal elements.
+///
+/// The comparator function is called concurrently from multiple threads, and must define a total
+/// ordering for the elements in the slice, see [`sort_by`]. If a call to the comparator function
+/// panics, the panic is propagated once all threads have finished, and the slice retains all
+/// original elements in unspecified order.
+///
+/// See [`par_sort`] for the guarantees and the current implementation.
 ///
 /// # Examples
 ///
 /// ```
 /// let mut v = [5, 4, 1, 3, 2];
-/// v.sort_unstable_by(|a, b| a.cmp(b));
-/// assert!(v == [1, 2, 3, 4, 5]);
-///
-/// // reverse sorting
-/// v.sort_unstable_by(|a, b| b.cmp(a));
+/// ipnsort::par_sort_by(&mut v, |a, b| b.cmp(a));
 /// assert!(v == [5, 4, 3, 2, 1]);
 /// ```
-///
-/// [pdqsort]: https://github.com/orlp/pdqsort
-#[inline(always)]
-pub fn sort_by<T, F>(arr: &mut [T], mut compare: F)
+#[inline]
+pub fn par_sort_by<T, F>(v: &mut [T], compare: F)
 where
-    F: FnMut(&T, &T) -> Ordering,
+    T: Send,
+    F: Fn(&T, &T) -> Ordering + Sync,
 {
-    unstable_sort(arr, |a, b| compare(a, b) == Ordering::Less);
+    parallel::par_unstable_sort(v, &|a: &T, b: &T| compare(a, b) == Ordering::Less);
 }
 
 // --- IMPL ---
 
 /// Sorts `v` using pattern-defeating quicksort, which is *O*(*n* \* log(*n*)) worst-case.
 #[inline(always)]
-fn unstable_sort<T, F>(v: &mut [T], mut is_less: F)
+fn unstable_sort<T, F>(v: &mut [T], is_less: F)
+where
+    F: FnMut(&T, &T) -> bool,
+{
+    unstable_sort_with_config::<DefaultConfig, T, F>(v, None, is_less);
+}
+
+/// See [`unstable_sort`], `rng` is passed on to [`quicksort::quicksort`].
+#[inline(always)]
+fn unstable_sort_with_config<C, T, F>(v: &mut [T], rng: Option<&mut PivotRng>, mut is_less: F)
 where
+    C: SortConfig,
     F: FnMut(&T, &T) -> bool,
 {
+    const { config::check_config::<C>() };
+
     // Arrays of zero-sized types are always all-equal, and thus sorted.
     if T::IS_ZST {
         return;
@@ -133,23 +1190,57 @@ where
     // modern processors is very valuable, and for a single sort call in general
     // purpose code any gains from an advanced method are cancelled by i-cache
     // misses during the sort, and thrashing the i-cache for surrounding code.
-    const MAX_LEN_ALWAYS_INSERTION_SORT: usize = 20;
-    if intrinsics::likely(len <= MAX_LEN_ALWAYS_INSERTION_SORT) {
+    if intrinsics::likely(len <= C::MAX_LEN_ALWAYS_INSERTION_SORT) {
+        trace_event!(SmallSort { len });
         smallsort::insertion_sort_shift_left(v, 1, &mut is_less);
         return;
     }
 
-    ipnsort(v, &mut is_less);
+    if const { mem::size_of::<T>() >= C::INDIRECT_SORT_MIN_SIZE } {
+        indirect_sort::<C, T, F>(v, rng, &mut is_less);
+        return;
+    }
+
+    ipnsort::<C, T, F>(v, rng, &mut is_less);
+}
+
+/// Sorts `v` by sorting references to its elements with [`ipnsort`], and applying the resulting
+/// permutation with [`permutation::apply_permutation`], which moves every element exactly once.
+/// For large types moving the elements dominates the cost of sorting, and the quicksort would move
+/// each of them *O*(log(*n*)) times.
+#[inline(never)]
+fn indirect_sort<C, T, F>(v: &mut [T], rng: Option<&mut PivotRng>, is_less: &mut F)
+where
+    C: SortConfig,
+    F: FnMut(&T, &T) -> bool,
+{
+    let mut refs: Vec<&T> = v.iter().collect();
+    ipnsort::<C, &T, _>(&mut refs, rng, &mut |a, b| is_less(a, b));
+
+    let v_base = v.as_ptr();
+    // SAFETY: Every reference points into `v`.
+    let mut perm: Vec<usize> = refs
+        .into_iter()
+        .map(|elem| unsafe { (elem as *const T).offset_from_unsigned(v_base) })
+        .collect();
+
+    permutation::apply_permutation(v, &mut perm);
 }
 
 /// TODO explain and link explanation.
 #[inline(never)]
-fn ipnsort<T, F>(v: &mut [T], is_less: &mut F)
+fn ipnsort<C, T, F>(v: &mut [T], rng: Option<&mut PivotRng>, is_less: &mut F)
 where
+    C: SortConfig,
     F: FnMut(&T, &T) -> bool,
 {
     let len = v.len();
     let (run_len, was_reversed) = find_existing_run(v, is_less);
+    trace_event!(ExistingRun {
+        len,
+        run_len,
+        descending: was_reversed
+    });
 
     // SAFETY: find_existing_run promises to return a valid run_len.
     unsafe { intrinsics::assume(run_len <= len) };
@@ -164,10 +1255,89 @@ where
         return;
     }
 
-    // Limit the number of imbalanced partitions to `2 * floor(log2(len))`.
+    // Limit the number of imbalanced partitions to `2 * floor(log2(len))` by default.
     // The binary OR by one is used to eliminate the zero-check in the logarithm.
+    let limit = C::IMBALANCE_LIMIT_FACTOR * (len | 1).ilog2();
+    crate::quicksort::quicksort::<C, _, _>(v, None, limit, rng, is_less);
+}
+
+/// Sorts `v` with a sorting network for up to [`networks::MAX_LEN`] elements, and like
+/// [`unstable_sort`] otherwise.
+#[inline(always)]
+fn array_sort<T, F, const N: usize>(v: &mut [T; N], mut is_less: F)
+where
+    F: FnMut(&T, &T) -> bool,
+{
+    if const { N <= networks::MAX_LEN } {
+        networks::sort_array(v, &mut is_less);
+    } else {
+        unstable_sort(v, is_less);
+    }
+}
+
+/// Sorts `v` like [`unstable_sort`], with the pivots sampled based on a random seed.
+#[inline(always)]
+fn hardened_sort<T, F>(v: &mut [T], is_less: F)
+where
+    F: FnMut(&T, &T) -> bool,
+{
+    use std::hash::{BuildHasher, RandomState};
+
+    // The keys of `RandomState` are random per thread and change for every instance.
+    let mut rng = PivotRng::new(RandomState::new().hash_one(v.as_ptr()));
+    unstable_sort_with_config::<DefaultConfig, T, F>(v, Some(&mut rng), is_less);
+}
+
+/// Sorts `v[..k]` using partial pattern-defeating quicksort, which is *O*(*n* + *k* \* log(*k*))
+/// worst-case.
+#[inline(always)]
+fn partial_unstable_sort<T, F>(v: &mut [T], k: usize, mut is_less: F)
+where
+    F: FnMut(&T, &T) -> bool,
+{
+    if k >= v.len() {
+        unstable_sort(v, is_less);
+        return;
+    }
+
+    // Arrays of zero-sized types are always all-equal, and thus sorted.
+    if T::IS_ZST || k == 0 {
+        return;
+    }
+
+    // See `ipnsort` for the choice of limit.
+    let limit = 2 * (v.len() | 1).ilog2();
+    crate::quicksort::partial_quicksort(v, k, None, limit, &mut is_less);
+}
+
+/// Sorts `v` and moves its unique elements to the front using deduplicating pattern-defeating
+/// quicksort, which is *O*(*n* \* log(*n*)) worst-case.
+#[inline(always)]
+fn dedup_unstable_sort<T, F>(v: &mut [T], mut is_less: F) -> usize
+where
+    F: FnMut(&T, &T) -> bool,
+{
+    // Arrays of zero-sized types are always all-equal.
+    if T::IS_ZST {
+        return v.len().min(1);
+    }
+
+    let len = v.len();
+    if len < 2 {
+        return len;
+    }
+
+    let (run_len, was_reversed) = find_existing_run(v, &mut is_less);
+    if run_len == len {
+        if was_reversed {
+            v.reverse();
+        }
+        return crate::quicksort::append_unique(v, 0, len, 0, &mut is_less);
+    }
+
+    // See `ipnsort` for the choice of limit.
     let limit = 2 * (len | 1).ilog2();
-    crate::quicksort::quicksort(v, None, limit, is_less);
+    crate::quicksort::dedup_quicksort(v, 0, len, 0, limit, &mut is_less)
 }
 
 /// Finds a run of sorted elements starting at the beginning of the slice.
@@ -199,19 +1369,3 @@ fn find_existing_run<T, F: FnMut(&T, &T) -> bool>(v: &[T], is_less: &mut F) -> (
         (run_len, strictly_descending)
     }
 }
-
-// // #[rustc_unsafe_specialization_marker]
-// trait Freeze {}
-
-// Can the type have interior mutability, this is checked by testing if T is Freeze. If the type can
-// have interior mutability it may alter itself during comparison in a way that must be observed
-// after the sort operation concludes. Otherwise a type like Mutex<Option<Box<str>>> could lead to
-// double free.
-unsafe auto trait Freeze {}
-
-impl<T: ?Sized> !Freeze for core::cell::UnsafeCell<T> {}
-unsafe impl<T: ?Sized> Freeze for core::marker::PhantomData<T> {}
-unsafe impl<T: ?Sized> Freeze for *const T {}
-unsafe impl<T: ?Sized> Freeze for *mut T {}
-unsafe impl<T: ?Sized> Freeze for &T {}
-unsafe impl<T: ?Sized> Freeze for &mut T {}
@@ -0,0 +1,196 @@
+//! Merging of two sorted sub-slices, see [`crate::sort_appended`].
+
+use core::cmp;
+use core::mem::MaybeUninit;
+use core::ptr;
+
+/// Merges the sorted `v[..mid]` and `v[mid..]`.
+///
+/// Elements at the start of the left side and at the end of the right side that are already in
+/// place are skipped with binary searches. If the shorter side of the remaining part fits into
+/// `scratch`, it is merged with [`merge_buffered`], which moves every element once. Otherwise
+/// both sides are cut at a common value, the inner parts are exchanged with a rotation, and the
+/// resulting two pairs of sorted sub-slices are merged separately. Without scratch this needs
+/// *O*(*n* \* log(*m*)) moves, where *m* is the length of the shorter side.
+///
+/// If `is_less` does not implement a total order or panics, the order of the elements is
+/// unspecified, but all original elements remain in `v`.
+pub(crate) fn merge<T, F>(
+    mut v: &mut [T],
+    mut mid: usize,
+    scratch: &mut [MaybeUninit<T>],
+    is_less: &mut F,
+) where
+    F: FnMut(&T, &T) -> bool,
+{
+    loop {
+        if mid == 0 || mid >= v.len() {
+            return;
+        }
+
+        let start = v[..mid].partition_point(|elem| !is_less(&v[mid], elem));
+        let end = mid + v[mid..].partition_point(|elem| is_less(elem, &v[mid - 1]));
+        v = &mut v[start..end];
+        mid -= start;
+
+        if mid == 0 || mid == v.len() {
+            return;
+        }
+
+        let len_right = v.len() - mid;
+        if cmp::min(mid, len_right) <= scratch.len() {
+            // SAFETY: `v[..mid]` and `v[mid..]` are non-empty, and the shorter one fits into
+            // `scratch`.
+            unsafe { merge_buffered(v, mid, scratch.as_mut_ptr().cast(), is_less) };
+            return;
+        }
+
+        // Cut the longer side in the middle, and the shorter side where that element belongs.
+        let (cut_left, cut_right) = if mid >= len_right {
+            let cut_left = mid / 2;
+            let cut_right = mid + v[mid..].partition_point(|elem| is_less(elem, &v[cut_left]));
+            (cut_left, cut_right)
+        } else {
+            let cut_right = mid + len_right / 2;
+            let cut_left = v[..mid].partition_point(|elem| !is_less(&v[cut_right], elem));
+            (cut_left, cut_right)
+        };
+
+        // [lo_left | hi_left | lo_right | hi_right] -> [lo_left | lo_right | hi_left | hi_right]
+        v[cut_left..cut_right].rotate_left(mid - cut_left);
+        let split = cut_left + (cut_right - mid);
+        let (lo, hi) = v.split_at_mut(split);
+        let mid_hi = mid - cut_left;
+
+        // Recurse into the shorter part, which bounds the recursion depth by `log2(v.len())`.
+        if lo.len() <= hi.len() {
+            merge(lo, cut_left, scratch, is_less);
+            v = hi;
+            mid = mid_hi;
+        } else {
+            merge(hi, mid_hi, scratch, is_less);
+            v = lo;
+            mid = cut_left;
+        }
+    }
+}
+
+/// Merges the sorted `v[..mid]` and `v[mid..]`, by moving the shorter side into `scratch` and
+/// merging it back into `v`.
+///
+/// If the shorter side is much shorter, the position of each of its elements on the longer side is
+/// found with a binary search, and the elements of the longer side in between are moved as a block.
+/// Otherwise the elements are merged one at a time.
+///
+/// # Safety
+///
+/// Both sides must be non-empty, and `scratch` must be valid for writes of as many elements as
+/// the shorter side contains, without aliasing `v`.
+unsafe fn merge_buffered<T, F>(v: &mut [T], mid: usize, scratch: *mut T, is_less: &mut F)
+where
+    F: FnMut(&T, &T) -> bool,
+{
+    const MIN_GALLOP_RATIO: usize = 64;
+
+    let len = v.len();
+    let v_base = v.as_mut_ptr();
+
+    // SAFETY: All pointers stay in-bounds of `v` and the part of `scratch` that holds the shorter
+    // side. The elements in `scratch` are owned by the gap guard, and every element of `v` outside
+    // of the gap is initialized. If `is_less` panics, the guard moves the remaining elements from
+    // `scratch` into the gap, which has exactly their size. Every element exists exactly once
+    // outside of the gap, so modifications through interior mutability remain visible.
+    unsafe {
+        if mid <= len - mid {
+            // Merge front to back. The gap is in front of the remaining right side.
+            ptr::copy_nonoverlapping(v_base, scratch, mid);
+            let mut gap = MergeGap {
+                start: scratch,
+                end: scratch.add(mid),
+                dst: v_base,
+            };
+
+            let mut right = v_base.add(mid);
+            let v_end = v_base.add(len);
+            if mid * MIN_GALLOP_RATIO <= len - mid {
+                while gap.start < gap.end {
+                    let right_len = v_end.offset_from_unsigned(right);
+                    let num_lt = (&*ptr::slice_from_raw_parts(right, right_len))
+                        .partition_point(|elem| is_less(elem, &*gap.start));
+                    ptr::copy(right, gap.dst, num_lt);
+                    right = right.add(num_lt);
+                    gap.dst = gap.dst.add(num_lt);
+
+                    ptr::copy_nonoverlapping(gap.start, gap.dst, 1);
+                    gap.start = gap.start.add(1);
+                    gap.dst = gap.dst.add(1);
+                }
+            } else {
+                while gap.start < gap.end && right < v_end {
+                    let take_right = is_less(&*right, &*gap.start);
+                    let src = if take_right { right } else { gap.start };
+                    ptr::copy_nonoverlapping(src, gap.dst, 1);
+                    right = right.add(take_right as usize);
+                    gap.start = gap.start.add(!take_right as usize);
+                    gap.dst = gap.dst.add(1);
+                }
+            }
+        } else {
+            // Merge back to front. The gap is behind the remaining left side.
+            let len_right = len - mid;
+            ptr::copy_nonoverlapping(v_base.add(mid), scratch, len_right);
+            let mut gap = MergeGap {
+                start: scratch,
+                end: scratch.add(len_right),
+                dst: v_base.add(mid),
+            };
+
+            let mut out = v_base.add(len);
+            if len_right * MIN_GALLOP_RATIO <= mid {
+                while gap.start < gap.end {
+                    let last = gap.end.sub(1);
+                    let left_len = gap.dst.offset_from_unsigned(v_base);
+                    let num_le = (&*ptr::slice_from_raw_parts(v_base, left_len))
+                        .partition_point(|elem| !is_less(&*last, elem));
+                    let num_gt = left_len - num_le;
+                    out = out.sub(num_gt);
+                    ptr::copy(v_base.add(num_le), out, num_gt);
+                    gap.dst = v_base.add(num_le);
+
+                    out = out.sub(1);
+                    ptr::copy_nonoverlapping(last, out, 1);
+                    gap.end = last;
+                }
+            } else {
+                while v_base < gap.dst && gap.start < gap.end {
+                    let left = gap.dst.sub(1);
+                    let right = gap.end.sub(1);
+                    let take_left = is_less(&*right, &*left);
+                    let src = if take_left { left } else { right };
+                    out = out.sub(1);
+                    ptr::copy_nonoverlapping(src, out, 1);
+                    gap.dst = gap.dst.sub(take_left as usize);
+                    gap.end = gap.end.sub(!take_left as usize);
+                }
+            }
+        }
+    }
+}
+
+/// Moves the elements in `start..end` to `dst` when dropped, which fills the gap in the merged
+/// slice.
+struct MergeGap<T> {
+    start: *mut T,
+    end: *mut T,
+    dst: *mut T,
+}
+
+impl<T> Drop for MergeGap<T> {
+    fn drop(&mut self) {
+        // SAFETY: See `merge_buffered`.
+        unsafe {
+            let len = self.end.offset_from_unsigned(self.start);
+            ptr::copy_nonoverlapping(self.start, self.dst, len);
+        }
+    }
+}
@@ -0,0 +1,180 @@
+//! Sorting with as few comparisons as possible, see [`crate::sort_by_min_cmp`].
+//!
+//! Any comparison based sort needs at least `log2(n!) ~= n * log2(n) - 1.44 * n` comparisons on
+//! average. The quicksort used by [`crate::sort`] needs about 11% more than that for 10⁶ random
+//! elements, trading comparisons for branchless partitioning and small-sorts that are fast for
+//! cheap comparisons. The quicksort here gets within about 1% of the bound, at the cost of moving
+//! elements more often:
+//!
+//! - The pivot is the median of a sample of about `2 * sqrt(len)` elements, sorted with binary
+//!   insertion sort. A precise pivot means every comparison during partitioning yields close to
+//!   one bit of information. The sample elements are not compared with the pivot again, and the
+//!   sorted parts of the sample on either side of the pivot are reused for the next partitions.
+//! - Small slices are sorted with binary insertion sort, which needs `ceil(log2(i + 1))`
+//!   comparisons to insert the `i + 1`-th element.
+//! - The fallback is bottom-up heapsort, which needs about `n * log2(n)` comparisons instead of
+//!   `2 * n * log2(n)`.
+
+use crate::heapsort;
+use crate::quicksort;
+
+/// Slices up to this length are sorted with binary insertion sort. It needs fewer comparisons than
+/// the partitions above it, but moves *O*(`len`²) elements.
+const SMALL_SORT_THRESHOLD: usize = 32;
+
+/// Sorts `v` with a minimal number of calls to `is_less`.
+pub(crate) fn sort_min_cmp<T, F>(v: &mut [T], is_less: &mut F)
+where
+    F: FnMut(&T, &T) -> bool,
+{
+    let len = v.len();
+    if len <= SMALL_SORT_THRESHOLD {
+        binary_insertion_sort(v, 0, is_less);
+        return;
+    }
+
+    let (run_len, was_reversed) = crate::find_existing_run(v, is_less);
+    if run_len == len {
+        if was_reversed {
+            v.reverse();
+        }
+        return;
+    }
+
+    // See `ipnsort` for the choice of limit.
+    let limit = 2 * (len | 1).ilog2();
+    quicksort_min_cmp(v, 0, None, limit, is_less);
+}
+
+/// Sorts `v` like [`quicksort::quicksort`], with the pivot chosen by [`partition_sampled`].
+fn quicksort_min_cmp<'a, T, F>(
+    mut v: &'a mut [T],
+    mut num_sorted: usize,
+    mut ancestor_pivot: Option<&'a T>,
+    mut limit: u32,
+    is_less: &mut F,
+) where
+    F: FnMut(&T, &T) -> bool,
+{
+    loop {
+        if v.len() <= SMALL_SORT_THRESHOLD {
+            binary_insertion_sort(v, num_sorted, is_less);
+            return;
+        }
+
+        if limit == 0 {
+            heapsort::heapsort_bottom_up(v, is_less);
+            return;
+        }
+
+        limit -= 1;
+
+        let sample_len = sample_len(v.len()).max(num_sorted);
+        let Some(num_lt) = partition_sampled(v, sample_len, num_sorted, ancestor_pivot, is_less)
+        else {
+            // The pivot is equal to the ancestor pivot, which makes it the smallest element in the
+            // slice. The sorted sample up to the pivot is all equal, so only the elements after it
+            // have to be partitioned into elements equal to and greater than the pivot.
+            let mid = sample_len / 2;
+            let num_eq = quicksort::partition(&mut v[mid..], 0, &mut |a, b| !is_less(b, a));
+            v = &mut v[(mid + num_eq + 1)..];
+            num_sorted = 0;
+            ancestor_pivot = None;
+            continue;
+        };
+
+        let (left, right) = v.split_at_mut(num_lt);
+        let (pivot, right) = right.split_at_mut(1);
+        let pivot = &pivot[0];
+
+        // The sample elements on either side are still sorted, and form a sample of that side.
+        let mid = sample_len / 2;
+        quicksort_min_cmp(left, mid, ancestor_pivot, limit, is_less);
+
+        v = right;
+        num_sorted = sample_len - 1 - mid;
+        ancestor_pivot = Some(pivot);
+    }
+}
+
+/// An odd sample size of about `2 * sqrt(len)`, so the median is well defined. Larger samples
+/// choose more precise pivots, but need more comparisons to sort. The sample elements are reused
+/// for the partitions below, which makes relatively large samples worth it.
+fn sample_len(len: usize) -> usize {
+    (4 * len).isqrt() | 1
+}
+
+/// Partitions `v` around the median of `sample_len` elements, and returns the number of elements
+/// less than the pivot, which is placed after them. The sample consists of the `num_sorted`
+/// elements at the front of `v`, which must be sorted, and elements gathered from the rest of `v`.
+/// The parts of the sample on either side of the pivot remain sorted, at the front of each side.
+///
+/// Returns `None` without partitioning if the pivot is not greater than `ancestor_pivot`. The
+/// sorted sample is left at the front of `v` in that case.
+fn partition_sampled<T, F>(
+    v: &mut [T],
+    sample_len: usize,
+    num_sorted: usize,
+    ancestor_pivot: Option<&T>,
+    is_less: &mut F,
+) -> Option<usize>
+where
+    F: FnMut(&T, &T) -> bool,
+{
+    // Gather the rest of the sample after the sorted elements, spread out evenly to get a
+    // representative sample of inputs with patterns. Position `num_sorted + i * step` is at least
+    // `num_sorted + i`, and has not been touched by an earlier swap.
+    let num_new = sample_len - num_sorted;
+    if let Some(step) = (v.len() - num_sorted).checked_div(num_new) {
+        for i in 1..num_new {
+            v.swap(num_sorted + i, num_sorted + i * step);
+        }
+    }
+
+    binary_insertion_sort(&mut v[..sample_len], num_sorted, is_less);
+
+    let mid = sample_len / 2;
+    if let Some(p) = ancestor_pivot {
+        if !is_less(p, &v[mid]) {
+            return None;
+        }
+    }
+
+    // Layout: [sample_lo | pivot | sample_hi | rest]. The elements in `sample_lo` are not greater
+    // and the ones in `sample_hi` not less than the pivot, so only `rest` is compared with it.
+    // Move the pivot in front of `rest` and partition it, which yields
+    // [sample_lo | sample_hi | rest_lt | pivot | rest_ge].
+    v[mid..sample_len].rotate_left(1);
+    let pivot_pos = sample_len - 1;
+    let num_rest_lt = quicksort::partition(&mut v[pivot_pos..], 0, is_less);
+
+    // Move `sample_hi` behind the pivot, yielding [sample_lo | rest_lt | pivot | sample_hi | rest_ge].
+    let num_hi = sample_len - 1 - mid;
+    v[mid..pivot_pos + num_rest_lt + 1].rotate_left(num_hi);
+
+    Some(mid + num_rest_lt)
+}
+
+/// Sorts `v` with binary insertion sort, assuming `v[..offset]` is already sorted. Needs at most
+/// `ceil(log2(i + 1))` comparisons to insert the element at position `i`.
+fn binary_insertion_sort<T, F>(v: &mut [T], offset: usize, is_less: &mut F)
+where
+    F: FnMut(&T, &T) -> bool,
+{
+    for i in offset.max(1)..v.len() {
+        // Find the first element greater than `v[i]`, which keeps equal elements in order.
+        // `slice::partition_point` would compare with the final position once more.
+        let mut lo = 0;
+        let mut hi = i;
+        while lo < hi {
+            let mid = lo + (hi - lo) / 2;
+            if is_less(&v[i], &v[mid]) {
+                hi = mid;
+            } else {
+                lo = mid + 1;
+            }
+        }
+
+        v[lo..=i].rotate_right(1);
+    }
+}
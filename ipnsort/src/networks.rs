@@ -0,0 +1,456 @@
+//! Sorting networks for up to 32 elements, see [`crate::sort_array`].
+//!
+//! A network is a list of comparators `(a, b)` with `a < b`, that each place the smaller of the
+//! elements at positions `a` and `b` at position `a`. The networks are tables, most of them with
+//! the smallest known number of comparators, see
+//! <https://bertdobbelaere.github.io/sorting_networks.html>. Those for 18, 22, 25, 26 and 28
+//! elements need one comparator more than the best known.
+
+use crate::polyfill::intrinsics;
+use crate::smallsort::swap_if_less;
+
+/// Arrays up to this length are sorted with a sorting network.
+pub(crate) const MAX_LEN: usize = 32;
+
+/// Returns the network for `len` elements, or an empty one if `len < 2` or `len > MAX_LEN`.
+pub(crate) const fn network(len: usize) -> &'static [(u8, u8)] {
+    match len {
+        2 => NETWORK_2,
+        3 => NETWORK_3,
+        4 => NETWORK_4,
+        5 => NETWORK_5,
+        6 => NETWORK_6,
+        7 => NETWORK_7,
+        8 => NETWORK_8,
+        9 => NETWORK_9,
+        10 => NETWORK_10,
+        11 => NETWORK_11,
+        12 => NETWORK_12,
+        13 => NETWORK_13,
+        14 => NETWORK_14,
+        15 => NETWORK_15,
+        16 => NETWORK_16,
+        17 => NETWORK_17,
+        18 => NETWORK_18,
+        19 => NETWORK_19,
+        20 => NETWORK_20,
+        21 => NETWORK_21,
+        22 => NETWORK_22,
+        23 => NETWORK_23,
+        24 => NETWORK_24,
+        25 => NETWORK_25,
+        26 => NETWORK_26,
+        27 => NETWORK_27,
+        28 => NETWORK_28,
+        29 => NETWORK_29,
+        30 => NETWORK_30,
+        31 => NETWORK_31,
+        32 => NETWORK_32,
+        _ => &[],
+    }
+}
+
+/// Sorts `v` by applying the network for `N` elements, which requires `N <= MAX_LEN`.
+#[inline(always)]
+pub(crate) fn sort_array<T, F, const N: usize>(v: &mut [T; N], is_less: &mut F)
+where
+    F: FnMut(&T, &T) -> bool,
+{
+    if N > MAX_LEN {
+        intrinsics::abort();
+    }
+
+    let v_base = v.as_mut_ptr();
+
+    // SAFETY: The comparators of the network for `N` elements are all less than `N`.
+    for_each_comparator::<_, N>(&mut |a, b| unsafe { swap_if_less(v_base, a, b, is_less) });
+}
+
+/// Calls `cmp_swap(a, b)` for every comparator `(a, b)` of the network for `N` elements, with
+/// `a < b < N`.
+#[inline(always)]
+pub(crate) fn for_each_comparator<S: FnMut(usize, usize), const N: usize>(cmp_swap: &mut S) {
+    // LLVM doesn't unroll loops over the larger networks completely, so apply them in fixed-size
+    // chunks that are unrolled. The network is a constant, so everything past its size is removed,
+    // and what remains calls `cmp_swap` with constant positions.
+    macro_rules! apply_chunks {
+        ($($start:literal)*) => {
+            $(apply_chunk::<S, N, $start>(cmp_swap);)*
+        };
+    }
+
+    apply_chunks!(0 8 16 24 32 40 48 56 64 72 80 88 96 104 112 120 128 136 144 152 160 168 176 184);
+}
+
+/// Number of comparators applied by each [`apply_chunk`].
+const CHUNK_SIZE: usize = 8;
+
+// The chunks applied by `for_each_comparator` have to cover the largest network.
+const _: () = assert!(network(MAX_LEN).len() <= 24 * CHUNK_SIZE);
+
+/// Applies the comparators `START..START + CHUNK_SIZE` of the network for `N` elements, as far as
+/// they exist.
+#[inline(always)]
+fn apply_chunk<S: FnMut(usize, usize), const N: usize, const START: usize>(cmp_swap: &mut S) {
+    let network = const { network(N) };
+
+    let mut i = START;
+    while i < START + CHUNK_SIZE && i < network.len() {
+        let (a, b) = network[i];
+        cmp_swap(a as usize, b as usize);
+        i += 1;
+    }
+}
+
+// The networks for up to 17 elements and for 19, 20, 21, 23, 24, 27 and 29 to 32 elements have the
+// smallest known number of comparators. Those for 5, 6, 7, 11, 14 and 15 elements are derived from
+// the one for the next larger length by removing a line, assuming its input is less or greater
+// than all others, which keeps the best known size. The ones for 22 and 23 elements are derived
+// the same way from the one for 24, which needs one comparator more than the best known for 22.
+// The ones for 25 to 32 elements sort two parts with the networks for up to 16 elements and merge
+// them with Batcher's odd-even merge, which needs one comparator more than the best known for 25,
+// 26 and 28. The one for 18 elements was found with a local search that starts from hypercube
+// layers on both halves. It needs one comparator more than the best known, but one less than any
+// derived from the ones for 19 to 21 elements.
+#[rustfmt::skip]
+const NETWORK_2: &[(u8, u8)] = &[(0, 1)];
+#[rustfmt::skip]
+const NETWORK_3: &[(u8, u8)] = &[(0, 1), (1, 2), (0, 1)];
+#[rustfmt::skip]
+const NETWORK_4: &[(u8, u8)] = &[(0, 2), (1, 3), (0, 1), (2, 3), (1, 2)];
+#[rustfmt::skip]
+const NETWORK_5: &[(u8, u8)] = &[
+    (1, 3), (2, 4), (0, 2), (0, 1), (3, 4), (2, 3), (1, 3), (1, 2), (3, 4),
+];
+#[rustfmt::skip]
+const NETWORK_6: &[(u8, u8)] = &[
+    (2, 4), (3, 5), (0, 2), (1, 3), (0, 1), (4, 5), (2, 3), (2, 4), (3, 5), (1, 4), (1, 2), (3, 4),
+];
+#[rustfmt::skip]
+const NETWORK_7: &[(u8, u8)] = &[
+    (0, 2), (3, 5), (4, 6), (1, 3), (0, 4), (2, 6), (0, 1), (2, 5), (3, 4), (2, 3), (4, 5), (1, 3),
+    (4, 6), (1, 2), (3, 4), (5, 6),
+];
+#[rustfmt::skip]
+const NETWORK_8: &[(u8, u8)] = &[
+    (0, 2), (1, 3), (4, 6), (5, 7), (0, 4), (1, 5), (2, 6), (3, 7), (0, 1), (2, 3), (4, 5), (6, 7),
+    (2, 4), (3, 5), (1, 4), (3, 6), (1, 2), (3, 4), (5, 6),
+];
+#[rustfmt::skip]
+const NETWORK_9: &[(u8, u8)] = &[
+    (0, 3), (1, 7), (2, 5), (4, 8), (0, 7), (2, 4), (3, 8), (5, 6), (0, 2), (1, 3), (4, 5), (7, 8),
+    (1, 4), (3, 6), (5, 7), (0, 1), (2, 4), (3, 5), (6, 8), (2, 3), (4, 5), (6, 7), (1, 2), (3, 4),
+    (5, 6),
+];
+#[rustfmt::skip]
+const NETWORK_10: &[(u8, u8)] = &[
+    (0, 8), (1, 9), (2, 7), (3, 5), (4, 6), (0, 2), (1, 4), (5, 8), (7, 9), (0, 3), (2, 4), (5, 7),
+    (6, 9), (0, 1), (3, 6), (8, 9), (1, 5), (2, 3), (4, 8), (6, 7), (1, 2), (3, 5), (4, 6), (7, 8),
+    (2, 3), (4, 5), (6, 7), (3, 4), (5, 6),
+];
+#[rustfmt::skip]
+const NETWORK_11: &[(u8, u8)] = &[
+    (0, 6), (1, 5), (2, 10), (3, 9), (4, 8), (1, 4), (2, 3), (5, 8), (6, 7), (9, 10), (0, 5),
+    (4, 9), (8, 10), (0, 1), (3, 5), (4, 6), (7, 10), (8, 9), (0, 3), (2, 4), (5, 7), (6, 9),
+    (0, 2), (1, 4), (5, 8), (7, 9), (1, 2), (3, 4), (5, 6), (7, 8), (3, 5), (4, 6), (2, 3), (4, 5),
+    (6, 7),
+];
+#[rustfmt::skip]
+const NETWORK_12: &[(u8, u8)] = &[
+    (0, 8), (1, 7), (2, 6), (3, 11), (4, 10), (5, 9), (0, 1), (2, 5), (3, 4), (6, 9), (7, 8),
+    (10, 11), (0, 2), (1, 6), (5, 10), (9, 11), (0, 3), (1, 2), (4, 6), (5, 7), (8, 11), (9, 10),
+    (1, 4), (3, 5), (6, 8), (7, 10), (1, 3), (2, 5), (6, 9), (8, 10), (2, 3), (4, 5), (6, 7),
+    (8, 9), (4, 6), (5, 7), (3, 4), (5, 6), (7, 8),
+];
+#[rustfmt::skip]
+const NETWORK_13: &[(u8, u8)] = &[
+    (0, 12), (1, 10), (2, 9), (3, 7), (5, 11), (6, 8), (1, 6), (2, 3), (4, 11), (7, 9), (8, 10),
+    (0, 4), (1, 2), (3, 6), (7, 8), (9, 10), (11, 12), (4, 6), (5, 9), (8, 11), (10, 12), (0, 5),
+    (3, 8), (4, 7), (6, 11), (9, 10), (0, 1), (2, 5), (6, 9), (7, 8), (10, 11), (1, 3), (2, 4),
+    (5, 6), (9, 10), (1, 2), (3, 4), (5, 7), (6, 8), (2, 3), (4, 5), (6, 7), (8, 9), (3, 4), (5, 6),
+];
+#[rustfmt::skip]
+const NETWORK_14: &[(u8, u8)] = &[
+    (0, 13), (1, 12), (2, 6), (3, 4), (5, 9), (7, 8), (3, 11), (5, 10), (0, 7), (1, 2), (6, 12),
+    (8, 13), (3, 5), (0, 1), (2, 11), (4, 6), (7, 10), (8, 9), (12, 13), (0, 3), (1, 5), (2, 8),
+    (9, 11), (4, 7), (6, 10), (1, 3), (5, 12), (2, 4), (7, 9), (6, 8), (10, 11), (1, 2), (3, 4),
+    (6, 7), (8, 9), (10, 13), (2, 3), (4, 5), (10, 12), (11, 13), (4, 6), (5, 7), (8, 10), (9, 12),
+    (3, 4), (5, 6), (7, 8), (9, 10), (11, 12), (6, 7), (8, 9),
+];
+#[rustfmt::skip]
+const NETWORK_15: &[(u8, u8)] = &[
+    (0, 11), (1, 14), (2, 13), (3, 7), (4, 5), (6, 10), (8, 9), (4, 12), (0, 6), (1, 8), (2, 3),
+    (7, 13), (9, 14), (10, 11), (0, 4), (1, 2), (3, 12), (5, 7), (6, 8), (9, 10), (13, 14), (0, 1),
+    (2, 4), (3, 9), (10, 12), (5, 6), (7, 8), (11, 13), (1, 2), (4, 11), (3, 5), (6, 10), (7, 9),
+    (8, 12), (13, 14), (1, 3), (2, 5), (6, 7), (9, 10), (8, 13), (12, 14), (2, 3), (4, 5), (8, 11),
+    (12, 13), (4, 6), (5, 7), (8, 9), (10, 11), (3, 4), (5, 6), (7, 8), (9, 10), (11, 12), (6, 7),
+    (8, 9),
+];
+#[rustfmt::skip]
+const NETWORK_16: &[(u8, u8)] = &[
+    (0, 13), (1, 12), (2, 15), (3, 14), (4, 8), (5, 6), (7, 11), (9, 10), (0, 5), (1, 7), (2, 9),
+    (3, 4), (6, 13), (8, 14), (10, 15), (11, 12), (0, 1), (2, 3), (4, 5), (6, 8), (7, 9), (10, 11),
+    (12, 13), (14, 15), (0, 2), (1, 3), (4, 10), (5, 11), (6, 7), (8, 9), (12, 14), (13, 15),
+    (1, 2), (3, 12), (4, 6), (5, 7), (8, 10), (9, 11), (13, 14), (1, 4), (2, 6), (5, 8), (7, 10),
+    (9, 13), (11, 14), (2, 4), (3, 6), (9, 12), (11, 13), (3, 5), (6, 8), (7, 9), (10, 12), (3, 4),
+    (5, 6), (7, 8), (9, 10), (11, 12), (6, 7), (8, 9),
+];
+#[rustfmt::skip]
+const NETWORK_17: &[(u8, u8)] = &[
+    (0, 11), (1, 15), (2, 10), (3, 5), (4, 6), (8, 12), (9, 16), (13, 14), (0, 6), (1, 13), (2, 8),
+    (4, 14), (5, 15), (7, 11), (0, 8), (3, 7), (4, 9), (6, 16), (10, 11), (12, 14), (0, 2), (1, 4),
+    (5, 6), (7, 13), (8, 9), (10, 12), (11, 14), (15, 16), (0, 3), (2, 5), (6, 11), (7, 10),
+    (9, 13), (12, 15), (14, 16), (0, 1), (3, 4), (5, 10), (6, 9), (7, 8), (11, 15), (13, 14),
+    (1, 2), (3, 7), (4, 8), (6, 12), (11, 13), (14, 15), (1, 3), (2, 7), (4, 5), (9, 11), (10, 12),
+    (13, 14), (2, 3), (4, 6), (5, 7), (8, 10), (3, 4), (6, 8), (7, 9), (10, 12), (5, 6), (7, 8),
+    (9, 10), (11, 12), (4, 5), (6, 7), (8, 9), (10, 11), (12, 13),
+];
+#[rustfmt::skip]
+const NETWORK_18: &[(u8, u8)] = &[
+    (0, 1), (2, 3), (4, 5), (6, 7), (8, 9), (10, 11), (12, 13), (14, 15), (16, 17), (0, 2), (1, 3),
+    (4, 6), (5, 7), (10, 12), (11, 13), (14, 16), (15, 17), (0, 4), (1, 5), (2, 6), (3, 7),
+    (10, 14), (11, 15), (12, 16), (13, 17), (0, 10), (1, 11), (2, 12), (3, 13), (4, 14), (5, 15),
+    (6, 16), (7, 17), (1, 2), (3, 14), (5, 12), (6, 11), (13, 15), (1, 4), (2, 10), (6, 8), (9, 11),
+    (4, 6), (8, 9), (11, 16), (2, 4), (3, 8), (6, 10), (7, 11), (9, 14), (15, 16), (0, 2), (5, 9),
+    (7, 13), (11, 15), (12, 14), (16, 17), (3, 5), (7, 9), (8, 12), (13, 14), (3, 4), (8, 10),
+    (9, 12), (11, 13), (14, 15), (1, 3), (4, 6), (5, 8), (7, 10), (11, 12), (13, 14), (15, 16),
+    (1, 2), (3, 4), (5, 6), (7, 8), (9, 10),
+];
+#[rustfmt::skip]
+const NETWORK_19: &[(u8, u8)] = &[
+    (0, 12), (1, 4), (2, 8), (3, 5), (6, 17), (7, 11), (9, 14), (10, 13), (15, 16), (0, 2), (1, 7),
+    (3, 6), (4, 11), (5, 17), (8, 12), (10, 15), (13, 16), (14, 18), (3, 10), (4, 14), (5, 15),
+    (6, 13), (7, 9), (11, 17), (16, 18), (0, 7), (1, 10), (4, 6), (9, 15), (11, 16), (12, 17),
+    (13, 14), (0, 3), (2, 6), (5, 7), (8, 11), (12, 16), (1, 8), (2, 9), (3, 4), (6, 15), (7, 13),
+    (10, 11), (12, 18), (1, 3), (2, 5), (6, 9), (7, 12), (8, 10), (11, 14), (17, 18), (0, 1),
+    (2, 3), (4, 8), (6, 10), (9, 12), (14, 15), (16, 17), (1, 2), (5, 8), (6, 7), (9, 11), (10, 13),
+    (14, 16), (15, 17), (3, 6), (4, 5), (7, 9), (8, 10), (11, 12), (13, 14), (15, 16), (3, 4),
+    (5, 6), (7, 8), (9, 10), (11, 13), (12, 14), (2, 3), (4, 5), (6, 7), (8, 9), (10, 11), (12, 13),
+    (14, 15),
+];
+#[rustfmt::skip]
+const NETWORK_20: &[(u8, u8)] = &[
+    (0, 3), (1, 7), (2, 5), (4, 8), (6, 9), (10, 13), (11, 15), (12, 18), (14, 17), (16, 19),
+    (0, 14), (1, 11), (2, 16), (3, 17), (4, 12), (5, 19), (6, 10), (7, 15), (8, 18), (9, 13),
+    (0, 4), (1, 2), (3, 8), (5, 7), (11, 16), (12, 14), (15, 19), (17, 18), (1, 6), (2, 12), (3, 5),
+    (4, 11), (7, 17), (8, 15), (13, 18), (14, 16), (0, 1), (2, 6), (7, 10), (9, 12), (13, 17),
+    (18, 19), (1, 6), (5, 9), (7, 11), (8, 12), (10, 14), (13, 18), (3, 5), (4, 7), (8, 10),
+    (9, 11), (12, 15), (14, 16), (1, 3), (2, 4), (5, 7), (6, 10), (9, 13), (12, 14), (15, 17),
+    (16, 18), (1, 2), (3, 4), (6, 7), (8, 9), (10, 11), (12, 13), (15, 16), (17, 18), (2, 3),
+    (4, 6), (5, 8), (7, 9), (10, 12), (11, 14), (13, 15), (16, 17), (4, 5), (6, 8), (7, 10),
+    (9, 12), (11, 13), (14, 15), (3, 4), (5, 6), (7, 8), (9, 10), (11, 12), (13, 14), (15, 16),
+];
+#[rustfmt::skip]
+const NETWORK_21: &[(u8, u8)] = &[
+    (0, 7), (1, 10), (3, 5), (4, 8), (6, 13), (9, 19), (11, 14), (12, 17), (15, 16), (18, 20),
+    (0, 11), (1, 15), (2, 12), (3, 4), (5, 8), (6, 9), (7, 14), (10, 16), (13, 19), (17, 20),
+    (0, 6), (1, 3), (2, 18), (4, 15), (5, 10), (8, 16), (11, 17), (12, 13), (14, 20), (2, 6),
+    (5, 12), (7, 18), (8, 14), (9, 11), (10, 17), (13, 19), (16, 20), (1, 2), (4, 7), (5, 9),
+    (6, 17), (10, 13), (11, 12), (14, 19), (15, 18), (0, 2), (3, 6), (4, 5), (7, 10), (8, 11),
+    (9, 15), (12, 16), (13, 18), (14, 17), (19, 20), (0, 1), (2, 3), (5, 9), (6, 12), (7, 8),
+    (11, 14), (13, 15), (16, 19), (17, 18), (1, 2), (3, 9), (6, 13), (10, 11), (12, 15), (16, 17),
+    (18, 19), (1, 4), (2, 5), (3, 7), (6, 10), (8, 9), (11, 12), (13, 14), (17, 18), (2, 4), (5, 6),
+    (7, 8), (9, 11), (10, 13), (12, 15), (14, 16), (3, 4), (5, 7), (6, 8), (9, 10), (11, 13),
+    (12, 14), (15, 16), (4, 5), (6, 7), (8, 9), (10, 11), (12, 13), (14, 15), (16, 17),
+];
+#[rustfmt::skip]
+const NETWORK_22: &[(u8, u8)] = &[
+    (0, 19), (1, 12), (3, 21), (5, 10), (7, 16), (8, 14), (9, 15), (11, 20), (13, 18), (6, 17),
+    (0, 3), (1, 11), (2, 7), (4, 6), (5, 13), (8, 9), (10, 18), (12, 20), (14, 15), (19, 21),
+    (0, 1), (2, 4), (3, 12), (5, 8), (9, 17), (7, 10), (11, 19), (13, 16), (6, 14), (15, 18),
+    (20, 21), (2, 5), (4, 8), (9, 11), (6, 7), (16, 17), (12, 14), (1, 8), (3, 7), (4, 6), (16, 19),
+    (10, 12), (11, 13), (15, 20), (0, 6), (1, 5), (3, 4), (9, 11), (8, 15), (7, 16), (10, 13),
+    (12, 14), (17, 21), (18, 20), (0, 2), (1, 9), (4, 6), (5, 7), (8, 10), (13, 15), (16, 18),
+    (17, 19), (14, 20), (2, 3), (4, 5), (8, 9), (6, 7), (10, 11), (12, 13), (16, 17), (14, 15),
+    (18, 19), (1, 2), (3, 8), (4, 10), (6, 9), (7, 11), (12, 16), (13, 19), (14, 17), (15, 21),
+    (2, 3), (5, 10), (6, 8), (7, 9), (13, 18), (14, 16), (15, 17), (20, 21), (3, 4), (5, 8),
+    (10, 12), (11, 13), (15, 18), (19, 20), (4, 6), (7, 10), (9, 12), (11, 14), (13, 16), (17, 19),
+    (5, 6), (7, 8), (9, 10), (11, 12), (13, 14), (15, 16), (17, 18),
+];
+#[rustfmt::skip]
+const NETWORK_23: &[(u8, u8)] = &[
+    (1, 12), (2, 16), (3, 20), (4, 6), (5, 10), (7, 21), (8, 14), (9, 15), (11, 22), (13, 18),
+    (17, 19), (0, 3), (1, 11), (2, 7), (4, 17), (5, 13), (6, 19), (8, 9), (10, 18), (12, 22),
+    (14, 15), (16, 21), (0, 1), (2, 4), (3, 12), (5, 8), (6, 9), (7, 10), (11, 20), (13, 16),
+    (14, 17), (15, 18), (19, 21), (2, 5), (4, 8), (6, 11), (7, 14), (9, 16), (12, 17), (15, 19),
+    (18, 21), (1, 8), (3, 14), (4, 7), (9, 20), (10, 12), (11, 13), (15, 22), (16, 19), (0, 7),
+    (1, 5), (3, 4), (6, 11), (8, 15), (9, 14), (10, 13), (12, 17), (18, 22), (19, 20), (0, 2),
+    (1, 6), (4, 7), (5, 9), (8, 10), (13, 15), (14, 18), (16, 19), (17, 22), (2, 3), (4, 5), (6, 8),
+    (7, 9), (10, 11), (12, 13), (14, 16), (15, 17), (18, 19), (20, 21), (1, 2), (3, 6), (4, 10),
+    (7, 8), (9, 11), (12, 14), (13, 19), (15, 16), (17, 20), (21, 22), (2, 3), (5, 10), (6, 7),
+    (8, 9), (13, 18), (14, 15), (16, 17), (20, 21), (3, 4), (5, 7), (10, 12), (11, 13), (16, 18),
+    (19, 20), (4, 6), (8, 10), (9, 12), (11, 14), (13, 15), (17, 19), (5, 6), (7, 8), (9, 10),
+    (11, 12), (13, 14), (15, 16), (17, 18),
+];
+#[rustfmt::skip]
+const NETWORK_24: &[(u8, u8)] = &[
+    (0, 20), (1, 12), (2, 16), (3, 23), (4, 6), (5, 10), (7, 21), (8, 14), (9, 15), (11, 22),
+    (13, 18), (17, 19), (0, 3), (1, 11), (2, 7), (4, 17), (5, 13), (6, 19), (8, 9), (10, 18),
+    (12, 22), (14, 15), (16, 21), (20, 23), (0, 1), (2, 4), (3, 12), (5, 8), (6, 9), (7, 10),
+    (11, 20), (13, 16), (14, 17), (15, 18), (19, 21), (22, 23), (2, 5), (4, 8), (6, 11), (7, 14),
+    (9, 16), (12, 17), (15, 19), (18, 21), (1, 8), (3, 14), (4, 7), (9, 20), (10, 12), (11, 13),
+    (15, 22), (16, 19), (0, 7), (1, 5), (3, 4), (6, 11), (8, 15), (9, 14), (10, 13), (12, 17),
+    (16, 23), (18, 22), (19, 20), (0, 2), (1, 6), (4, 7), (5, 9), (8, 10), (13, 15), (14, 18),
+    (16, 19), (17, 22), (21, 23), (2, 3), (4, 5), (6, 8), (7, 9), (10, 11), (12, 13), (14, 16),
+    (15, 17), (18, 19), (20, 21), (1, 2), (3, 6), (4, 10), (7, 8), (9, 11), (12, 14), (13, 19),
+    (15, 16), (17, 20), (21, 22), (2, 3), (5, 10), (6, 7), (8, 9), (13, 18), (14, 15), (16, 17),
+    (20, 21), (3, 4), (5, 7), (10, 12), (11, 13), (16, 18), (19, 20), (4, 6), (8, 10), (9, 12),
+    (11, 14), (13, 15), (17, 19), (5, 6), (7, 8), (9, 10), (11, 12), (13, 14), (15, 16), (17, 18),
+];
+#[rustfmt::skip]
+const NETWORK_25: &[(u8, u8)] = &[
+    (0, 8), (1, 7), (2, 6), (3, 11), (4, 10), (5, 9), (0, 1), (2, 5), (3, 4), (6, 9), (7, 8),
+    (10, 11), (0, 2), (1, 6), (5, 10), (9, 11), (0, 3), (1, 2), (4, 6), (5, 7), (8, 11), (9, 10),
+    (1, 4), (3, 5), (6, 8), (7, 10), (1, 3), (2, 5), (6, 9), (8, 10), (2, 3), (4, 5), (6, 7),
+    (8, 9), (4, 6), (5, 7), (3, 4), (5, 6), (7, 8), (12, 24), (13, 22), (14, 21), (15, 19),
+    (17, 23), (18, 20), (13, 18), (14, 15), (16, 23), (19, 21), (20, 22), (12, 16), (13, 14),
+    (15, 18), (19, 20), (21, 22), (23, 24), (16, 18), (17, 21), (20, 23), (22, 24), (12, 17),
+    (15, 20), (16, 19), (18, 23), (21, 22), (12, 13), (14, 17), (18, 21), (19, 20), (22, 23),
+    (13, 15), (14, 16), (17, 18), (21, 22), (13, 14), (15, 16), (17, 19), (18, 20), (14, 15),
+    (16, 17), (18, 19), (20, 21), (15, 16), (17, 18), (0, 12), (1, 13), (2, 14), (3, 15), (4, 16),
+    (5, 17), (6, 18), (7, 19), (8, 20), (9, 21), (10, 22), (11, 23), (8, 12), (9, 13), (10, 14),
+    (11, 15), (16, 24), (4, 8), (5, 9), (6, 10), (7, 11), (12, 16), (13, 17), (14, 18), (15, 19),
+    (20, 24), (2, 4), (3, 5), (6, 8), (7, 9), (10, 12), (11, 13), (14, 16), (15, 17), (18, 20),
+    (19, 21), (22, 24), (1, 2), (3, 4), (5, 6), (7, 8), (9, 10), (11, 12), (13, 14), (15, 16),
+    (17, 18), (19, 20), (21, 22), (23, 24),
+];
+#[rustfmt::skip]
+const NETWORK_26: &[(u8, u8)] = &[
+    (0, 12), (1, 10), (2, 9), (3, 7), (5, 11), (6, 8), (1, 6), (2, 3), (4, 11), (7, 9), (8, 10),
+    (0, 4), (1, 2), (3, 6), (7, 8), (9, 10), (11, 12), (4, 6), (5, 9), (8, 11), (10, 12), (0, 5),
+    (3, 8), (4, 7), (6, 11), (9, 10), (0, 1), (2, 5), (6, 9), (7, 8), (10, 11), (1, 3), (2, 4),
+    (5, 6), (9, 10), (1, 2), (3, 4), (5, 7), (6, 8), (2, 3), (4, 5), (6, 7), (8, 9), (3, 4), (5, 6),
+    (13, 25), (14, 23), (15, 22), (16, 20), (18, 24), (19, 21), (14, 19), (15, 16), (17, 24),
+    (20, 22), (21, 23), (13, 17), (14, 15), (16, 19), (20, 21), (22, 23), (24, 25), (17, 19),
+    (18, 22), (21, 24), (23, 25), (13, 18), (16, 21), (17, 20), (19, 24), (22, 23), (13, 14),
+    (15, 18), (19, 22), (20, 21), (23, 24), (14, 16), (15, 17), (18, 19), (22, 23), (14, 15),
+    (16, 17), (18, 20), (19, 21), (15, 16), (17, 18), (19, 20), (21, 22), (16, 17), (18, 19),
+    (0, 13), (1, 14), (2, 15), (3, 16), (4, 17), (5, 18), (6, 19), (7, 20), (8, 21), (9, 22),
+    (10, 23), (11, 24), (12, 25), (8, 13), (9, 14), (10, 15), (11, 16), (12, 17), (4, 8), (5, 9),
+    (6, 10), (7, 11), (12, 13), (14, 18), (15, 19), (16, 20), (17, 21), (2, 4), (3, 5), (6, 8),
+    (7, 9), (10, 12), (11, 14), (13, 15), (16, 18), (17, 19), (20, 22), (21, 23), (1, 2), (3, 4),
+    (5, 6), (7, 8), (9, 10), (11, 12), (13, 14), (15, 16), (17, 18), (19, 20), (21, 22), (23, 24),
+];
+#[rustfmt::skip]
+const NETWORK_27: &[(u8, u8)] = &[
+    (0, 6), (1, 5), (2, 10), (3, 9), (4, 8), (1, 4), (2, 3), (5, 8), (6, 7), (9, 10), (0, 5),
+    (4, 9), (8, 10), (0, 1), (3, 5), (4, 6), (7, 10), (8, 9), (0, 3), (2, 4), (5, 7), (6, 9),
+    (0, 2), (1, 4), (5, 8), (7, 9), (1, 2), (3, 4), (5, 6), (7, 8), (3, 5), (4, 6), (2, 3), (4, 5),
+    (6, 7), (11, 24), (12, 23), (13, 26), (14, 25), (15, 19), (16, 17), (18, 22), (20, 21),
+    (11, 16), (12, 18), (13, 20), (14, 15), (17, 24), (19, 25), (21, 26), (22, 23), (11, 12),
+    (13, 14), (15, 16), (17, 19), (18, 20), (21, 22), (23, 24), (25, 26), (11, 13), (12, 14),
+    (15, 21), (16, 22), (17, 18), (19, 20), (23, 25), (24, 26), (12, 13), (14, 23), (15, 17),
+    (16, 18), (19, 21), (20, 22), (24, 25), (12, 15), (13, 17), (16, 19), (18, 21), (20, 24),
+    (22, 25), (13, 15), (14, 17), (20, 23), (22, 24), (14, 16), (17, 19), (18, 20), (21, 23),
+    (14, 15), (16, 17), (18, 19), (20, 21), (22, 23), (17, 18), (19, 20), (0, 11), (1, 12), (2, 13),
+    (3, 14), (4, 15), (5, 16), (6, 17), (7, 18), (8, 19), (9, 20), (10, 21), (8, 11), (9, 12),
+    (10, 13), (14, 22), (15, 23), (16, 24), (17, 25), (18, 26), (4, 8), (5, 9), (6, 10), (7, 14),
+    (11, 15), (12, 16), (13, 17), (18, 22), (19, 23), (20, 24), (21, 25), (2, 4), (3, 5), (6, 8),
+    (7, 9), (10, 11), (12, 14), (13, 15), (16, 18), (17, 19), (20, 22), (21, 23), (24, 26), (1, 2),
+    (3, 4), (5, 6), (7, 8), (9, 10), (11, 12), (13, 14), (15, 16), (17, 18), (19, 20), (21, 22),
+    (23, 24), (25, 26),
+];
+#[rustfmt::skip]
+const NETWORK_28: &[(u8, u8)] = &[
+    (0, 8), (1, 7), (2, 6), (3, 11), (4, 10), (5, 9), (0, 1), (2, 5), (3, 4), (6, 9), (7, 8),
+    (10, 11), (0, 2), (1, 6), (5, 10), (9, 11), (0, 3), (1, 2), (4, 6), (5, 7), (8, 11), (9, 10),
+    (1, 4), (3, 5), (6, 8), (7, 10), (1, 3), (2, 5), (6, 9), (8, 10), (2, 3), (4, 5), (6, 7),
+    (8, 9), (4, 6), (5, 7), (3, 4), (5, 6), (7, 8), (12, 25), (13, 24), (14, 27), (15, 26),
+    (16, 20), (17, 18), (19, 23), (21, 22), (12, 17), (13, 19), (14, 21), (15, 16), (18, 25),
+    (20, 26), (22, 27), (23, 24), (12, 13), (14, 15), (16, 17), (18, 20), (19, 21), (22, 23),
+    (24, 25), (26, 27), (12, 14), (13, 15), (16, 22), (17, 23), (18, 19), (20, 21), (24, 26),
+    (25, 27), (13, 14), (15, 24), (16, 18), (17, 19), (20, 22), (21, 23), (25, 26), (13, 16),
+    (14, 18), (17, 20), (19, 22), (21, 25), (23, 26), (14, 16), (15, 18), (21, 24), (23, 25),
+    (15, 17), (18, 20), (19, 21), (22, 24), (15, 16), (17, 18), (19, 20), (21, 22), (23, 24),
+    (18, 19), (20, 21), (0, 12), (1, 13), (2, 14), (3, 15), (4, 16), (5, 17), (6, 18), (7, 19),
+    (8, 20), (9, 21), (10, 22), (11, 23), (8, 12), (9, 13), (10, 14), (11, 15), (16, 24), (17, 25),
+    (18, 26), (19, 27), (4, 8), (5, 9), (6, 10), (7, 11), (12, 16), (13, 17), (14, 18), (15, 19),
+    (20, 24), (21, 25), (22, 26), (23, 27), (2, 4), (3, 5), (6, 8), (7, 9), (10, 12), (11, 13),
+    (14, 16), (15, 17), (18, 20), (19, 21), (22, 24), (23, 25), (1, 2), (3, 4), (5, 6), (7, 8),
+    (9, 10), (11, 12), (13, 14), (15, 16), (17, 18), (19, 20), (21, 22), (23, 24), (25, 26),
+];
+#[rustfmt::skip]
+const NETWORK_29: &[(u8, u8)] = &[
+    (0, 12), (1, 10), (2, 9), (3, 7), (5, 11), (6, 8), (1, 6), (2, 3), (4, 11), (7, 9), (8, 10),
+    (0, 4), (1, 2), (3, 6), (7, 8), (9, 10), (11, 12), (4, 6), (5, 9), (8, 11), (10, 12), (0, 5),
+    (3, 8), (4, 7), (6, 11), (9, 10), (0, 1), (2, 5), (6, 9), (7, 8), (10, 11), (1, 3), (2, 4),
+    (5, 6), (9, 10), (1, 2), (3, 4), (5, 7), (6, 8), (2, 3), (4, 5), (6, 7), (8, 9), (3, 4), (5, 6),
+    (13, 26), (14, 25), (15, 28), (16, 27), (17, 21), (18, 19), (20, 24), (22, 23), (13, 18),
+    (14, 20), (15, 22), (16, 17), (19, 26), (21, 27), (23, 28), (24, 25), (13, 14), (15, 16),
+    (17, 18), (19, 21), (20, 22), (23, 24), (25, 26), (27, 28), (13, 15), (14, 16), (17, 23),
+    (18, 24), (19, 20), (21, 22), (25, 27), (26, 28), (14, 15), (16, 25), (17, 19), (18, 20),
+    (21, 23), (22, 24), (26, 27), (14, 17), (15, 19), (18, 21), (20, 23), (22, 26), (24, 27),
+    (15, 17), (16, 19), (22, 25), (24, 26), (16, 18), (19, 21), (20, 22), (23, 25), (16, 17),
+    (18, 19), (20, 21), (22, 23), (24, 25), (19, 20), (21, 22), (0, 13), (1, 14), (2, 15), (3, 16),
+    (4, 17), (5, 18), (6, 19), (7, 20), (8, 21), (9, 22), (10, 23), (11, 24), (12, 25), (8, 13),
+    (9, 14), (10, 15), (11, 16), (12, 17), (18, 26), (19, 27), (20, 28), (4, 8), (5, 9), (6, 10),
+    (7, 11), (12, 13), (14, 18), (15, 19), (16, 20), (17, 21), (22, 26), (23, 27), (24, 28), (2, 4),
+    (3, 5), (6, 8), (7, 9), (10, 12), (11, 14), (13, 15), (16, 18), (17, 19), (20, 22), (21, 23),
+    (24, 26), (25, 27), (1, 2), (3, 4), (5, 6), (7, 8), (9, 10), (11, 12), (13, 14), (15, 16),
+    (17, 18), (19, 20), (21, 22), (23, 24), (25, 26), (27, 28),
+];
+#[rustfmt::skip]
+const NETWORK_30: &[(u8, u8)] = &[
+    (0, 11), (1, 14), (2, 13), (3, 7), (4, 5), (6, 10), (8, 9), (4, 12), (0, 6), (1, 8), (2, 3),
+    (7, 13), (9, 14), (10, 11), (0, 4), (1, 2), (3, 12), (5, 7), (6, 8), (9, 10), (13, 14), (0, 1),
+    (2, 4), (3, 9), (10, 12), (5, 6), (7, 8), (11, 13), (1, 2), (4, 11), (3, 5), (6, 10), (7, 9),
+    (8, 12), (13, 14), (1, 3), (2, 5), (6, 7), (9, 10), (8, 13), (12, 14), (2, 3), (4, 5), (8, 11),
+    (12, 13), (4, 6), (5, 7), (8, 9), (10, 11), (3, 4), (5, 6), (7, 8), (9, 10), (11, 12), (6, 7),
+    (8, 9), (15, 26), (16, 29), (17, 28), (18, 22), (19, 20), (21, 25), (23, 24), (19, 27),
+    (15, 21), (16, 23), (17, 18), (22, 28), (24, 29), (25, 26), (15, 19), (16, 17), (18, 27),
+    (20, 22), (21, 23), (24, 25), (28, 29), (15, 16), (17, 19), (18, 24), (25, 27), (20, 21),
+    (22, 23), (26, 28), (16, 17), (19, 26), (18, 20), (21, 25), (22, 24), (23, 27), (28, 29),
+    (16, 18), (17, 20), (21, 22), (24, 25), (23, 28), (27, 29), (17, 18), (19, 20), (23, 26),
+    (27, 28), (19, 21), (20, 22), (23, 24), (25, 26), (18, 19), (20, 21), (22, 23), (24, 25),
+    (26, 27), (21, 22), (23, 24), (0, 15), (1, 16), (2, 17), (3, 18), (4, 19), (5, 20), (6, 21),
+    (7, 22), (8, 23), (9, 24), (10, 25), (11, 26), (12, 27), (13, 28), (14, 29), (8, 15), (9, 16),
+    (10, 17), (11, 18), (12, 19), (13, 20), (14, 21), (4, 8), (5, 9), (6, 10), (7, 11), (12, 15),
+    (13, 16), (14, 17), (18, 22), (19, 23), (20, 24), (21, 25), (2, 4), (3, 5), (6, 8), (7, 9),
+    (10, 12), (11, 13), (14, 15), (16, 18), (17, 19), (20, 22), (21, 23), (24, 26), (25, 27),
+    (1, 2), (3, 4), (5, 6), (7, 8), (9, 10), (11, 12), (13, 14), (15, 16), (17, 18), (19, 20),
+    (21, 22), (23, 24), (25, 26), (27, 28),
+];
+#[rustfmt::skip]
+const NETWORK_31: &[(u8, u8)] = &[
+    (0, 11), (1, 14), (2, 13), (3, 7), (4, 5), (6, 10), (8, 9), (4, 12), (0, 6), (1, 8), (2, 3),
+    (7, 13), (9, 14), (10, 11), (0, 4), (1, 2), (3, 12), (5, 7), (6, 8), (9, 10), (13, 14), (0, 1),
+    (2, 4), (3, 9), (10, 12), (5, 6), (7, 8), (11, 13), (1, 2), (4, 11), (3, 5), (6, 10), (7, 9),
+    (8, 12), (13, 14), (1, 3), (2, 5), (6, 7), (9, 10), (8, 13), (12, 14), (2, 3), (4, 5), (8, 11),
+    (12, 13), (4, 6), (5, 7), (8, 9), (10, 11), (3, 4), (5, 6), (7, 8), (9, 10), (11, 12), (6, 7),
+    (8, 9), (15, 28), (16, 27), (17, 30), (18, 29), (19, 23), (20, 21), (22, 26), (24, 25),
+    (15, 20), (16, 22), (17, 24), (18, 19), (21, 28), (23, 29), (25, 30), (26, 27), (15, 16),
+    (17, 18), (19, 20), (21, 23), (22, 24), (25, 26), (27, 28), (29, 30), (15, 17), (16, 18),
+    (19, 25), (20, 26), (21, 22), (23, 24), (27, 29), (28, 30), (16, 17), (18, 27), (19, 21),
+    (20, 22), (23, 25), (24, 26), (28, 29), (16, 19), (17, 21), (20, 23), (22, 25), (24, 28),
+    (26, 29), (17, 19), (18, 21), (24, 27), (26, 28), (18, 20), (21, 23), (22, 24), (25, 27),
+    (18, 19), (20, 21), (22, 23), (24, 25), (26, 27), (21, 22), (23, 24), (0, 15), (1, 16), (2, 17),
+    (3, 18), (4, 19), (5, 20), (6, 21), (7, 22), (8, 23), (9, 24), (10, 25), (11, 26), (12, 27),
+    (13, 28), (14, 29), (8, 15), (9, 16), (10, 17), (11, 18), (12, 19), (13, 20), (14, 21),
+    (22, 30), (4, 8), (5, 9), (6, 10), (7, 11), (12, 15), (13, 16), (14, 17), (18, 22), (19, 23),
+    (20, 24), (21, 25), (26, 30), (2, 4), (3, 5), (6, 8), (7, 9), (10, 12), (11, 13), (14, 15),
+    (16, 18), (17, 19), (20, 22), (21, 23), (24, 26), (25, 27), (28, 30), (1, 2), (3, 4), (5, 6),
+    (7, 8), (9, 10), (11, 12), (13, 14), (15, 16), (17, 18), (19, 20), (21, 22), (23, 24), (25, 26),
+    (27, 28), (29, 30),
+];
+#[rustfmt::skip]
+const NETWORK_32: &[(u8, u8)] = &[
+    (0, 13), (1, 12), (2, 15), (3, 14), (4, 8), (5, 6), (7, 11), (9, 10), (0, 5), (1, 7), (2, 9),
+    (3, 4), (6, 13), (8, 14), (10, 15), (11, 12), (0, 1), (2, 3), (4, 5), (6, 8), (7, 9), (10, 11),
+    (12, 13), (14, 15), (0, 2), (1, 3), (4, 10), (5, 11), (6, 7), (8, 9), (12, 14), (13, 15),
+    (1, 2), (3, 12), (4, 6), (5, 7), (8, 10), (9, 11), (13, 14), (1, 4), (2, 6), (5, 8), (7, 10),
+    (9, 13), (11, 14), (2, 4), (3, 6), (9, 12), (11, 13), (3, 5), (6, 8), (7, 9), (10, 12), (3, 4),
+    (5, 6), (7, 8), (9, 10), (11, 12), (6, 7), (8, 9), (16, 29), (17, 28), (18, 31), (19, 30),
+    (20, 24), (21, 22), (23, 27), (25, 26), (16, 21), (17, 23), (18, 25), (19, 20), (22, 29),
+    (24, 30), (26, 31), (27, 28), (16, 17), (18, 19), (20, 21), (22, 24), (23, 25), (26, 27),
+    (28, 29), (30, 31), (16, 18), (17, 19), (20, 26), (21, 27), (22, 23), (24, 25), (28, 30),
+    (29, 31), (17, 18), (19, 28), (20, 22), (21, 23), (24, 26), (25, 27), (29, 30), (17, 20),
+    (18, 22), (21, 24), (23, 26), (25, 29), (27, 30), (18, 20), (19, 22), (25, 28), (27, 29),
+    (19, 21), (22, 24), (23, 25), (26, 28), (19, 20), (21, 22), (23, 24), (25, 26), (27, 28),
+    (22, 23), (24, 25), (0, 16), (1, 17), (2, 18), (3, 19), (4, 20), (5, 21), (6, 22), (7, 23),
+    (8, 24), (9, 25), (10, 26), (11, 27), (12, 28), (13, 29), (14, 30), (15, 31), (8, 16), (9, 17),
+    (10, 18), (11, 19), (12, 20), (13, 21), (14, 22), (15, 23), (4, 8), (5, 9), (6, 10), (7, 11),
+    (12, 16), (13, 17), (14, 18), (15, 19), (20, 24), (21, 25), (22, 26), (23, 27), (2, 4), (3, 5),
+    (6, 8), (7, 9), (10, 12), (11, 13), (14, 16), (15, 17), (18, 20), (19, 21), (22, 24), (23, 25),
+    (26, 28), (27, 29), (1, 2), (3, 4), (5, 6), (7, 8), (9, 10), (11, 12), (13, 14), (15, 16),
+    (17, 18), (19, 20), (21, 22), (23, 24), (25, 26), (27, 28), (29, 30),
+];
@@ -0,0 +1,561 @@
+//! Sorts a slice of keys and a slice of values in lockstep, comparing only the keys.
+//!
+//! This mirrors the structure of the regular ipnsort quicksort, but every element move in the key
+//! slice is repeated at the same position in the value slice. Keeping the keys in a dense slice
+//! means pivot selection and partitioning only ever touch key memory for the comparisons.
+
+use core::mem::{self, ManuallyDrop};
+use core::ptr;
+
+use crate::networks;
+use crate::polyfill::{intrinsics, SizedTypeProperties};
+use crate::smallsort::has_efficient_in_place_swap;
+
+/// Up to this length the lockstep small-sort is used.
+const SMALL_SORT_THRESHOLD: usize = 20;
+
+/// Sorts `keys` and applies the same permutation to `values`.
+///
+/// Panics if `keys.len() != values.len()`, without modifying either slice.
+pub(crate) fn sort_pairs<K, V, F>(keys: &mut [K], values: &mut [V], is_less: &mut F)
+where
+    F: FnMut(&K, &K) -> bool,
+{
+    let len = keys.len();
+    if len != values.len() {
+        panic!(
+            "keys length {len} does not match values length {}",
+            values.len()
+        );
+    }
+
+    // Arrays of zero-sized types are always all-equal, and thus sorted.
+    if K::IS_ZST || len < 2 {
+        return;
+    }
+
+    if len <= SMALL_SORT_THRESHOLD {
+        small_sort(keys, values, is_less);
+        return;
+    }
+
+    let (run_len, was_reversed) = crate::find_existing_run(keys, is_less);
+
+    // SAFETY: find_existing_run promises to return a valid run_len.
+    unsafe { intrinsics::assume(run_len <= len) };
+
+    if run_len == len {
+        if was_reversed {
+            keys.reverse();
+            values.reverse();
+        }
+
+        return;
+    }
+
+    // See `ipnsort` for the choice of limit.
+    let limit = 2 * (len | 1).ilog2();
+    quicksort(keys, values, None, limit, is_less);
+}
+
+/// Sorts `keys` and `values` recursively, see [`crate::quicksort::quicksort`].
+fn quicksort<'a, K, V, F>(
+    mut keys: &'a mut [K],
+    mut values: &'a mut [V],
+    mut ancestor_pivot: Option<&'a K>,
+    mut limit: u32,
+    is_less: &mut F,
+) where
+    F: FnMut(&K, &K) -> bool,
+{
+    loop {
+        if keys.len() <= SMALL_SORT_THRESHOLD {
+            small_sort(keys, values, is_less);
+            return;
+        }
+
+        // If too many bad pivot choices were made, simply fall back to heapsort in order to
+        // guarantee `O(N x log(N))` worst-case.
+        if limit == 0 {
+            heapsort(keys, values, is_less);
+            return;
+        }
+
+        limit -= 1;
+
+        let pivot_pos = crate::pivot::choose_pivot(keys, is_less);
+
+        // See `quicksort` for an explanation of the ancestor pivot handling.
+        if let Some(p) = ancestor_pivot {
+            // SAFETY: We assume choose_pivot yields an in-bounds position.
+            if !is_less(p, unsafe { keys.get_unchecked(pivot_pos) }) {
+                let num_lt = partition(keys, values, pivot_pos, &mut |a, b| !is_less(b, a));
+
+                keys = &mut keys[(num_lt + 1)..];
+                values = &mut values[(num_lt + 1)..];
+                ancestor_pivot = None;
+                continue;
+            }
+        }
+
+        let num_lt = partition(keys, values, pivot_pos, is_less);
+        // SAFETY: partition ensures that `num_lt` will be in-bounds.
+        unsafe { intrinsics::assume(num_lt < keys.len()) };
+
+        let (left_keys, right_keys) = keys.split_at_mut(num_lt);
+        let (pivot, right_keys) = right_keys.split_at_mut(1);
+        let pivot = &pivot[0];
+
+        let (left_values, right_values) = values.split_at_mut(num_lt);
+        let right_values = &mut right_values[1..];
+
+        quicksort(left_keys, left_values, ancestor_pivot, limit, is_less);
+
+        keys = right_keys;
+        values = right_values;
+        ancestor_pivot = Some(pivot);
+    }
+}
+
+/// Lockstep version of [`crate::quicksort::partition`].
+fn partition<K, V, F>(keys: &mut [K], values: &mut [V], pivot: usize, is_less: &mut F) -> usize
+where
+    F: FnMut(&K, &K) -> bool,
+{
+    let len = keys.len();
+
+    // Allows for panic-free code-gen by proving this property to the compiler.
+    if len == 0 {
+        return 0;
+    }
+
+    // The partition implementations rely on this for memory safety.
+    if pivot >= len || values.len() != len {
+        intrinsics::abort();
+    }
+
+    keys.swap(0, pivot);
+    values.swap(0, pivot);
+
+    let (pivot, keys_without_pivot) = keys.split_at_mut(1);
+    let pivot = &mut pivot[0];
+
+    let num_lt = (const { inst_partition::<K, V, F>() })(
+        keys_without_pivot,
+        &mut values[1..],
+        pivot,
+        is_less,
+    );
+
+    keys.swap(0, num_lt);
+    values.swap(0, num_lt);
+
+    num_lt
+}
+
+const fn inst_partition<K, V, F: FnMut(&K, &K) -> bool>(
+) -> fn(&mut [K], &mut [V], &K, &mut F) -> usize {
+    const MAX_BRANCHLESS_PARTITION_SIZE: usize = 96;
+    // The branchless partition unconditionally moves every key and value, so the cost depends on
+    // the combined size.
+    if mem::size_of::<K>() + mem::size_of::<V>() <= MAX_BRANCHLESS_PARTITION_SIZE {
+        partition_lomuto_branchless_cyclic::<K, V, F>
+    } else {
+        partition_hoare_branchy_cyclic::<K, V, F>
+    }
+}
+
+/// Lockstep version of [`crate::quicksort::partition_hoare_branchy_cyclic`].
+fn partition_hoare_branchy_cyclic<K, V, F>(
+    keys: &mut [K],
+    values: &mut [V],
+    pivot: &K,
+    is_less: &mut F,
+) -> usize
+where
+    F: FnMut(&K, &K) -> bool,
+{
+    let len = keys.len();
+
+    if len == 0 {
+        return 0;
+    }
+
+    let mut gap_opt: Option<GapGuard<K, V>> = None;
+
+    // SAFETY: `partition` guarantees `values.len() == keys.len()`, so every index that is valid
+    // for `keys_base` is also valid for `values_base`. The index logic is the same as in the
+    // single slice version: `left < right <= len` holds whenever an element is accessed, and the
+    // exit check makes sure `left` and `right` never alias. The drop-guard `gap` ensures that
+    // should `is_less` panic we always overwrite the duplicate in both slices.
+    unsafe {
+        let keys_base = keys.as_mut_ptr();
+        let values_base = values.as_mut_ptr();
+
+        let mut left = 0;
+        let mut right = len;
+
+        loop {
+            // Find the first element greater than the pivot.
+            while left < right && is_less(&*keys_base.add(left), pivot) {
+                left += 1;
+            }
+
+            // Find the last element equal to the pivot.
+            loop {
+                right -= 1;
+                if left >= right || is_less(&*keys_base.add(right), pivot) {
+                    break;
+                }
+            }
+
+            if left >= right {
+                break;
+            }
+
+            // Swap the found pair of out-of-order elements via cyclic permutation.
+            let is_first_swap_pair = gap_opt.is_none();
+
+            if is_first_swap_pair {
+                gap_opt = Some(GapGuard {
+                    key_pos: keys_base.add(right),
+                    value_pos: values_base.add(right),
+                    key: ManuallyDrop::new(ptr::read(keys_base.add(left))),
+                    value: ManuallyDrop::new(ptr::read(values_base.add(left))),
+                });
+            }
+
+            let gap = gap_opt.as_mut().unwrap_unchecked();
+
+            if !is_first_swap_pair {
+                ptr::copy_nonoverlapping(keys_base.add(left), gap.key_pos, 1);
+                ptr::copy_nonoverlapping(values_base.add(left), gap.value_pos, 1);
+            }
+            gap.key_pos = keys_base.add(right);
+            gap.value_pos = values_base.add(right);
+            ptr::copy_nonoverlapping(keys_base.add(right), keys_base.add(left), 1);
+            ptr::copy_nonoverlapping(values_base.add(right), values_base.add(left), 1);
+
+            left += 1;
+        }
+
+        left
+
+        // `gap_opt` goes out of scope and overwrites the last wrong-side element on the right side
+        // with the first wrong-side element of the left side.
+    }
+}
+
+struct PartitionState<K, V> {
+    // The current element that is being looked at, scans left to right through both slices.
+    right_key: *mut K,
+    right_value: *mut V,
+    // Counts the number of elements that compared less-than.
+    num_lt: usize,
+    // Gap guard that tracks the temporary duplicates in the input.
+    gap: GapGuardRaw<K, V>,
+}
+
+/// Lockstep version of [`crate::quicksort::partition_lomuto_branchless_cyclic`].
+fn partition_lomuto_branchless_cyclic<K, V, F>(
+    keys: &mut [K],
+    values: &mut [V],
+    pivot: &K,
+    is_less: &mut F,
+) -> usize
+where
+    F: FnMut(&K, &K) -> bool,
+{
+    let len = keys.len();
+    let keys_base = keys.as_mut_ptr();
+    let values_base = values.as_mut_ptr();
+
+    if len == 0 {
+        return 0;
+    }
+
+    // SAFETY: `partition` guarantees `values.len() == keys.len()`, so every offset that is valid
+    // for `keys_base` is also valid for `values_base`. See the single slice version for the
+    // reasoning about the gap and the final iteration that uses the saved gap values as `right`.
+    unsafe {
+        let mut loop_body = |state: &mut PartitionState<K, V>| {
+            let right_is_lt = is_less(&*state.right_key, pivot);
+            let left_key = keys_base.add(state.num_lt);
+            let left_value = values_base.add(state.num_lt);
+
+            ptr::copy(left_key, state.gap.key_pos, 1);
+            ptr::copy(left_value, state.gap.value_pos, 1);
+            ptr::copy_nonoverlapping(state.right_key, left_key, 1);
+            ptr::copy_nonoverlapping(state.right_value, left_value, 1);
+
+            state.gap.key_pos = state.right_key;
+            state.gap.value_pos = state.right_value;
+            state.num_lt += right_is_lt as usize;
+
+            state.right_key = state.right_key.add(1);
+            state.right_value = state.right_value.add(1);
+        };
+
+        let mut gap_key = ManuallyDrop::new(ptr::read(keys_base));
+        let mut gap_value = ManuallyDrop::new(ptr::read(values_base));
+
+        let mut state = PartitionState {
+            right_key: keys_base.add(1),
+            right_value: values_base.add(1),
+            num_lt: 0,
+
+            gap: GapGuardRaw {
+                key_pos: keys_base,
+                value_pos: values_base,
+                key: &mut *gap_key,
+                value: &mut *gap_value,
+            },
+        };
+
+        let end = keys_base.add(len);
+        loop {
+            let is_done = state.right_key == end;
+            if is_done {
+                state.right_key = state.gap.key;
+                state.right_value = state.gap.value;
+            }
+
+            loop_body(&mut state);
+
+            if is_done {
+                mem::forget(state.gap);
+                break;
+            }
+        }
+
+        state.num_lt
+    }
+}
+
+/// Sorts `keys` and `values` using heapsort, which guarantees *O*(*n* \* log(*n*)) worst-case.
+#[inline(never)]
+fn heapsort<K, V, F>(keys: &mut [K], values: &mut [V], is_less: &mut F)
+where
+    F: FnMut(&K, &K) -> bool,
+{
+    let len = keys.len();
+    if values.len() != len {
+        intrinsics::abort();
+    }
+
+    for i in (0..len + len / 2).rev() {
+        let sift_idx = if i >= len {
+            i - len
+        } else {
+            keys.swap(0, i);
+            values.swap(0, i);
+            0
+        };
+
+        let end = i.min(len);
+
+        // SAFETY: `sift_idx` is either 0 or in `0..(len / 2)`, see `heapsort::heapsort`.
+        unsafe {
+            sift_down(&mut keys[..end], &mut values[..end], sift_idx, is_less);
+        }
+    }
+}
+
+// This binary heap respects the invariant `parent >= child`.
+//
+// SAFETY: The caller has to guarantee that `node <= keys.len()` and
+// `keys.len() == values.len()`.
+#[inline(always)]
+unsafe fn sift_down<K, V, F>(keys: &mut [K], values: &mut [V], mut node: usize, is_less: &mut F)
+where
+    F: FnMut(&K, &K) -> bool,
+{
+    let len = keys.len();
+
+    let keys_base = keys.as_mut_ptr();
+    let values_base = values.as_mut_ptr();
+
+    loop {
+        // Children of `node`.
+        let mut child = 2 * node + 1;
+        if child >= len {
+            break;
+        }
+
+        // SAFETY: The invariants and checks guarantee that both node and child are in-bounds.
+        unsafe {
+            // Choose the greater child.
+            if child + 1 < len {
+                child += is_less(&*keys_base.add(child), &*keys_base.add(child + 1)) as usize;
+            }
+
+            // Stop if the invariant holds at `node`.
+            if !is_less(&*keys_base.add(node), &*keys_base.add(child)) {
+                break;
+            }
+
+            ptr::swap_nonoverlapping(keys_base.add(node), keys_base.add(child), 1);
+            ptr::swap_nonoverlapping(values_base.add(node), values_base.add(child), 1);
+        }
+
+        node = child;
+    }
+}
+
+/// Sorts `keys` and `values` using strategies optimized for small sizes. Uses an optimal sorting
+/// network to presort a prefix if the keys are cheap to swap, followed by insertion sort.
+fn small_sort<K, V, F>(keys: &mut [K], values: &mut [V], is_less: &mut F)
+where
+    F: FnMut(&K, &K) -> bool,
+{
+    let len = keys.len();
+    if len < 2 {
+        return;
+    }
+
+    if values.len() != len {
+        intrinsics::abort();
+    }
+
+    let keys_base = keys.as_mut_ptr();
+    let values_base = values.as_mut_ptr();
+
+    // SAFETY: The networks only generate positions smaller than their size, which we checked to
+    // be in-bounds for both slices.
+    let presorted_len = if const { has_efficient_in_place_swap::<K>() } && len >= 13 {
+        networks::for_each_comparator::<_, 13>(&mut |a, b| unsafe {
+            swap_if_less(keys_base, values_base, a, b, is_less)
+        });
+        13
+    } else if const { has_efficient_in_place_swap::<K>() } && len >= 9 {
+        networks::for_each_comparator::<_, 9>(&mut |a, b| unsafe {
+            swap_if_less(keys_base, values_base, a, b, is_less)
+        });
+        9
+    } else {
+        1
+    };
+
+    // SAFETY: `presorted_len` is in `1..=len` and both slices have `len` elements.
+    unsafe {
+        for tail in presorted_len..len {
+            insert_tail(keys_base, values_base, tail, is_less);
+        }
+    }
+}
+
+/// Swap the elements at position `a_pos` and `b_pos` in both slices if the key at position `b_pos`
+/// is less than the one at position `a_pos`.
+///
+/// SAFETY: The caller must guarantee that `a_pos` and `b_pos` are distinct and in-bounds for both
+/// `keys_base` and `values_base`.
+unsafe fn swap_if_less<K, V, F>(
+    keys_base: *mut K,
+    values_base: *mut V,
+    a_pos: usize,
+    b_pos: usize,
+    is_less: &mut F,
+) where
+    F: FnMut(&K, &K) -> bool,
+{
+    // PANIC SAFETY: the only call to `is_less` happens before any element is moved.
+    unsafe {
+        let should_swap = is_less(&*keys_base.add(b_pos), &*keys_base.add(a_pos));
+
+        swap_if(keys_base, a_pos, b_pos, should_swap);
+        swap_if(values_base, a_pos, b_pos, should_swap);
+    }
+}
+
+/// Branchless conditional swap, see [`crate::smallsort::swap_if_less`].
+#[inline(always)]
+unsafe fn swap_if<T>(v_base: *mut T, a_pos: usize, b_pos: usize, should_swap: bool) {
+    // SAFETY: The caller must guarantee that `a_pos` and `b_pos` are in-bounds.
+    unsafe {
+        let v_a = v_base.add(a_pos);
+        let v_b = v_base.add(b_pos);
+
+        let left_swap = if should_swap { v_b } else { v_a };
+        let right_swap = if should_swap { v_a } else { v_b };
+
+        let right_swap_tmp = ManuallyDrop::new(ptr::read(right_swap));
+        ptr::copy(left_swap, v_a, 1);
+        ptr::copy_nonoverlapping(&*right_swap_tmp, v_b, 1);
+    }
+}
+
+/// Sorts range `[0, tail]` of both slices assuming `[0, tail)` is already sorted.
+///
+/// SAFETY: The caller must guarantee that `0 < tail` and that `tail` is in-bounds for both
+/// `keys_base` and `values_base`.
+unsafe fn insert_tail<K, V, F>(keys_base: *mut K, values_base: *mut V, tail: usize, is_less: &mut F)
+where
+    F: FnMut(&K, &K) -> bool,
+{
+    // SAFETY: see individual comments and `smallsort::insert_tail`.
+    unsafe {
+        let mut sift = tail - 1;
+        if !is_less(&*keys_base.add(tail), &*keys_base.add(sift)) {
+            return;
+        }
+
+        // SAFETY: after these reads `tail` is only ever used as the initial gap position. Should a
+        // panic occur, or we have found the correct insertion position, `gap` ensures the
+        // elements are moved back into both slices.
+        let mut gap = GapGuard {
+            key_pos: keys_base.add(tail),
+            value_pos: values_base.add(tail),
+            key: ManuallyDrop::new(ptr::read(keys_base.add(tail))),
+            value: ManuallyDrop::new(ptr::read(values_base.add(tail))),
+        };
+
+        loop {
+            ptr::copy_nonoverlapping(keys_base.add(sift), gap.key_pos, 1);
+            ptr::copy_nonoverlapping(values_base.add(sift), gap.value_pos, 1);
+            gap.key_pos = keys_base.add(sift);
+            gap.value_pos = values_base.add(sift);
+
+            if sift == 0 {
+                break;
+            }
+
+            sift -= 1;
+            if !is_less(&gap.key, &*keys_base.add(sift)) {
+                break;
+            }
+        }
+    }
+}
+
+struct GapGuard<K, V> {
+    key_pos: *mut K,
+    value_pos: *mut V,
+    key: ManuallyDrop<K>,
+    value: ManuallyDrop<V>,
+}
+
+impl<K, V> Drop for GapGuard<K, V> {
+    fn drop(&mut self) {
+        unsafe {
+            ptr::copy_nonoverlapping(&*self.key, self.key_pos, 1);
+            ptr::copy_nonoverlapping(&*self.value, self.value_pos, 1);
+        }
+    }
+}
+
+/// See [`crate::quicksort::GapGuardRaw`].
+struct GapGuardRaw<K, V> {
+    key_pos: *mut K,
+    value_pos: *mut V,
+    key: *mut K,
+    value: *mut V,
+}
+
+impl<K, V> Drop for GapGuardRaw<K, V> {
+    fn drop(&mut self) {
+        unsafe {
+            ptr::copy_nonoverlapping(self.key, self.key_pos, 1);
+            ptr::copy_nonoverlapping(self.value, self.value_pos, 1);
+        }
+    }
+}
@@ -0,0 +1,156 @@
+//! Parallel version of [`crate::quicksort::quicksort`], that forks the left side of each partition
+//! onto a scoped thread, as long as the sub-slice is large enough and the thread budget allows it.
+//!
+//! The partitioning is identical to the sequential sort, so the result and the number of
+//! comparisons are the same, only the order in which sub-slices are sorted differs.
+
+use std::panic;
+use std::thread::{self, Scope, ScopedJoinHandle};
+
+use crate::heapsort;
+use crate::pivot::choose_pivot;
+use crate::polyfill::{intrinsics, SizedTypeProperties};
+use crate::quicksort::{partition, quicksort};
+use crate::DefaultConfig;
+
+/// Slices shorter than this are sorted sequentially. Spawning a thread costs in the order of ten
+/// microseconds, which only pays off if the thread has enough work to do.
+const PAR_SORT_MIN_LEN: usize = 1 << 15;
+
+/// Sorts `v` using all available cores, see [`crate::par_sort_by`].
+pub(crate) fn par_unstable_sort<T, F>(v: &mut [T], is_less: &F)
+where
+    T: Send,
+    F: Fn(&T, &T) -> bool + Sync,
+{
+    let len = v.len();
+    let max_threads = thread::available_parallelism().map_or(1, usize::from);
+
+    if T::IS_ZST || len < PAR_SORT_MIN_LEN || max_threads == 1 {
+        crate::unstable_sort(v, is_less);
+        return;
+    }
+
+    let (run_len, was_reversed) = crate::find_existing_run(v, &mut |a, b| is_less(a, b));
+    trace_event!(ExistingRun {
+        len,
+        run_len,
+        descending: was_reversed
+    });
+
+    // SAFETY: find_existing_run promises to return a valid run_len.
+    unsafe { intrinsics::assume(run_len <= len) };
+
+    if run_len == len {
+        if was_reversed {
+            v.reverse();
+        }
+
+        return;
+    }
+
+    // Same limit as the sequential sort, see `crate::ipnsort`.
+    let limit = 2 * (len | 1).ilog2();
+    thread::scope(|scope| par_quicksort(scope, v, None, limit, max_threads, is_less));
+}
+
+/// Sorts `v` recursively, using at most `threads` threads including the current one.
+///
+/// `ancestor_pivot` and `limit` have the same meaning as for [`quicksort`]. The ancestor pivot is
+/// passed as mutable reference, which is `Send` for `T: Send`, each pivot is only ever handed to
+/// a single sub-slice.
+fn par_quicksort<'scope, T, F>(
+    scope: &'scope Scope<'scope, '_>,
+    mut v: &'scope mut [T],
+    mut ancestor_pivot: Option<&'scope mut T>,
+    mut limit: u32,
+    mut threads: usize,
+    is_less: &'scope F,
+) where
+    T: Send,
+    F: Fn(&T, &T) -> bool + Sync,
+{
+    let mut handles: Vec<ScopedJoinHandle<'scope, ()>> = Vec::new();
+
+    loop {
+        if threads <= 1 || v.len() < PAR_SORT_MIN_LEN {
+            quicksort::<DefaultConfig, _, _>(
+                v,
+                ancestor_pivot.as_deref(),
+                limit,
+                None,
+                &mut |a, b| is_less(a, b),
+            );
+            break;
+        }
+
+        if limit == 0 {
+            trace_event!(HeapsortFallback { len: v.len() });
+            heapsort::heapsort(v, &mut |a, b| is_less(a, b));
+            break;
+        }
+
+        limit -= 1;
+
+        let pivot_pos = choose_pivot(v, &mut |a, b| is_less(a, b));
+        trace_event!(PivotChosen {
+            len: v.len(),
+            pivot_pos
+        });
+
+        // See `quicksort` for an explanation of the equal partition.
+        if let Some(p) = ancestor_pivot.as_deref() {
+            // SAFETY: We assume choose_pivot yields an in-bounds position.
+            if !is_less(p, unsafe { v.get_unchecked(pivot_pos) }) {
+                let num_lt = partition(v, pivot_pos, &mut |a, b| !is_less(b, a));
+                trace_event!(EqualPartition {
+                    len: v.len(),
+                    num_le: num_lt + 1
+                });
+
+                v = &mut v[(num_lt + 1)..];
+                ancestor_pivot = None;
+                continue;
+            }
+        }
+
+        let num_lt = partition(v, pivot_pos, &mut |a, b| is_less(a, b));
+        trace_event!(Partition {
+            len: v.len(),
+            num_lt
+        });
+        // SAFETY: partition ensures that `num_lt` will be in-bounds.
+        unsafe { intrinsics::assume(num_lt < v.len()) };
+
+        let (left, right) = v.split_at_mut(num_lt);
+        let (pivot, right) = right.split_at_mut(1);
+
+        // Hand half the budget to the left side, and continue with the right side.
+        let left_threads = threads / 2;
+        threads -= left_threads;
+
+        let left_ancestor_pivot = ancestor_pivot.take();
+        handles.push(scope.spawn(move || {
+            par_quicksort(
+                scope,
+                left,
+                left_ancestor_pivot,
+                limit,
+                left_threads,
+                is_less,
+            );
+        }));
+
+        v = right;
+        ancestor_pivot = Some(&mut pivot[0]);
+    }
+
+    // Joining explicitly propagates the original panic payload, instead of the generic one that
+    // `thread::scope` would raise. Should the current thread panic, `thread::scope` still joins all
+    // threads before unwinding further.
+    for handle in handles {
+        if let Err(payload) = handle.join() {
+            panic::resume_unwind(payload);
+        }
+    }
+}
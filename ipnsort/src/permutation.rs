@@ -0,0 +1,83 @@
+use core::mem::ManuallyDrop;
+use core::ptr;
+
+use crate::polyfill::SizedTypeProperties;
+
+/// Marks an entry in `perm` as not yet placed. Valid indices are always smaller than
+/// `isize::MAX` for non zero-sized types, so the highest bit is free to use.
+const UNPLACED_BIT: usize = !(usize::MAX >> 1);
+
+/// Rearranges `v` such that afterwards `v[i]` holds the element that was previously at
+/// `v[perm[i]]`.
+///
+/// Every element is moved exactly once, by following each cycle of the permutation starting at
+/// its leader. `perm` is used to keep track of the already placed elements and is restored before
+/// this function returns.
+///
+/// Panics if `v.len() != perm.len()` or if `perm` is not a permutation of `0..v.len()`. `v` is
+/// left unmodified in that case.
+pub(crate) fn apply_permutation<T>(v: &mut [T], perm: &mut [usize]) {
+    let len = v.len();
+
+    if len != perm.len() {
+        panic!(
+            "permutation length {} does not match slice length {len}",
+            perm.len()
+        );
+    }
+
+    if let Some(idx) = perm.iter().position(|&k| k >= len) {
+        panic!(
+            "permutation index {} out of range for slice of length {len}",
+            perm[idx]
+        );
+    }
+
+    // Arrays of zero-sized types are always all-equal, so any permutation is the identity.
+    if T::IS_ZST {
+        return;
+    }
+
+    // Mark every target as unplaced, this also detects duplicates which would otherwise lead to
+    // duplicated and leaked elements.
+    for i in 0..len {
+        let k = perm[i] & !UNPLACED_BIT;
+        if perm[k] & UNPLACED_BIT != 0 {
+            for k in perm.iter_mut() {
+                *k &= !UNPLACED_BIT;
+            }
+            panic!("permutation contains duplicate index {k}");
+        }
+        perm[k] |= UNPLACED_BIT;
+    }
+
+    let v_base = v.as_mut_ptr();
+
+    // SAFETY: All indices in `perm` were checked to be in-bounds and unique, so every cycle
+    // visits each position exactly once and returns to its leader. The leader value is held in
+    // `tmp` while the cycle is shifted, and written into the last position of the cycle. No user
+    // code runs in this loop, so no panic can observe the intermediate duplicate.
+    unsafe {
+        for leader in 0..len {
+            if *perm.get_unchecked(leader) & UNPLACED_BIT == 0 {
+                continue;
+            }
+
+            let tmp = ManuallyDrop::new(ptr::read(v_base.add(leader)));
+            let mut pos = leader;
+
+            loop {
+                let src = *perm.get_unchecked(pos) & !UNPLACED_BIT;
+                *perm.get_unchecked_mut(pos) = src;
+
+                if src == leader {
+                    ptr::copy_nonoverlapping(&*tmp, v_base.add(pos), 1);
+                    break;
+                }
+
+                ptr::copy_nonoverlapping(v_base.add(src), v_base.add(pos), 1);
+                pos = src;
+            }
+        }
+    }
+}
@@ -1,4 +1,4 @@
-use core::intrinsics;
+use crate::polyfill::intrinsics;
 
 // Recursively select a pseudomedian if above this threshold.
 const PSEUDO_MEDIAN_REC_THRESHOLD: usize = 64;
@@ -36,6 +36,60 @@ pub fn choose_pivot<T, F: FnMut(&T, &T) -> bool>(v: &[T], is_less: &mut F) -> us
     }
 }
 
+/// Selects a pivot from `v` like [`choose_pivot`], but samples the three regions at random
+/// positions drawn from `rng`, so the sampled elements can't be predicted from the algorithm.
+pub fn choose_pivot_randomized<T, F: FnMut(&T, &T) -> bool>(
+    v: &[T],
+    rng: &mut PivotRng,
+    is_less: &mut F,
+) -> usize {
+    let len = v.len();
+    if len < 8 {
+        intrinsics::abort();
+    }
+
+    // SAFETY: Every region starts at most `len - len_div_8` elements into `v`, so a, b, c point to
+    // initialized regions of len_div_8 elements. Regions may overlap, which only means the same
+    // element may be sampled more than once.
+    unsafe {
+        let v_base = v.as_ptr();
+        let len_div_8 = len / 8;
+        let max_start = len - len_div_8;
+
+        let a = v_base.add(rng.below(max_start + 1));
+        let b = v_base.add(rng.below(max_start + 1));
+        let c = v_base.add(rng.below(max_start + 1));
+
+        if len < PSEUDO_MEDIAN_REC_THRESHOLD {
+            median3(&*a, &*b, &*c, is_less).offset_from_unsigned(v_base)
+        } else {
+            median3_rec(a, b, c, len_div_8, is_less).offset_from_unsigned(v_base)
+        }
+    }
+}
+
+/// Random state for [`choose_pivot_randomized`], using the SplitMix64 generator.
+pub(crate) struct PivotRng(u64);
+
+impl PivotRng {
+    pub(crate) fn new(seed: u64) -> Self {
+        Self(seed)
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /// Returns a value in `[0, n)`, assumes `n > 0`.
+    fn below(&mut self, n: usize) -> usize {
+        ((self.next_u64() as u128 * n as u128) >> 64) as usize
+    }
+}
+
 /// Calculates an approximate median of 3 elements from sections a, b, c, or
 /// recursively from an approximation of each, if they're large enough. By
 /// dividing the size of each section by 8 when recursing we have logarithmic
@@ -0,0 +1,69 @@
+//! Nightly-only building blocks, or their stable replacements if the `stable` feature is enabled.
+
+#[cfg(not(feature = "stable"))]
+pub(crate) use core::intrinsics;
+
+#[cfg(not(feature = "stable"))]
+pub(crate) use core::mem::SizedTypeProperties;
+
+#[cfg(feature = "stable")]
+pub(crate) mod intrinsics {
+    /// See `core::intrinsics::assume`.
+    ///
+    /// # Safety
+    ///
+    /// `b` must be true.
+    #[inline(always)]
+    pub(crate) unsafe fn assume(b: bool) {
+        // SAFETY: See function safety.
+        unsafe { core::hint::assert_unchecked(b) };
+    }
+
+    /// See `core::intrinsics::likely`, there is no stable way to express the hint.
+    #[inline(always)]
+    pub(crate) fn likely(b: bool) -> bool {
+        b
+    }
+
+    /// See `core::intrinsics::abort`.
+    #[inline(always)]
+    pub(crate) fn abort() -> ! {
+        std::process::abort()
+    }
+}
+
+/// See `core::mem::SizedTypeProperties`.
+#[cfg(feature = "stable")]
+pub(crate) trait SizedTypeProperties: Sized {
+    const IS_ZST: bool = core::mem::size_of::<Self>() == 0;
+}
+
+#[cfg(feature = "stable")]
+impl<T> SizedTypeProperties for T {}
+
+/// The `is_less` function of [`crate::sort`]. Without the `stable` feature it has a nameable type,
+/// which allows specializing the partition for it.
+#[cfg(not(feature = "stable"))]
+#[inline(always)]
+pub(crate) fn ord_lt<T: Ord>() -> impl FnMut(&T, &T) -> bool + Copy {
+    crate::quicksort::simd::OrdLt
+}
+
+#[cfg(feature = "stable")]
+#[inline(always)]
+pub(crate) fn ord_lt<T: Ord>() -> impl FnMut(&T, &T) -> bool + Copy {
+    |a: &T, b: &T| a.lt(b)
+}
+
+/// The `is_less` function of [`crate::sort_floats`], see [`ord_lt`].
+#[cfg(not(feature = "stable"))]
+#[inline(always)]
+pub(crate) fn total_lt<T: crate::Float>() -> impl FnMut(&T, &T) -> bool + Copy {
+    crate::quicksort::simd::TotalLt
+}
+
+#[cfg(feature = "stable")]
+#[inline(always)]
+pub(crate) fn total_lt<T: crate::Float>() -> impl FnMut(&T, &T) -> bool + Copy {
+    |a: &T, b: &T| a.total_cmp(b) == core::cmp::Ordering::Less
+}
@@ -1,9 +1,15 @@
-use core::intrinsics;
-use core::mem::{self, ManuallyDrop};
+use core::mem::{self, ManuallyDrop, MaybeUninit};
 use core::ptr;
 
 use crate::heapsort;
+use crate::pivot::{self, PivotRng};
+use crate::polyfill::intrinsics;
 use crate::smallsort::UnstableSmallSortTypeImpl;
+use crate::{DefaultConfig, SortConfig};
+
+// Only compiled without the `stable` feature, see `crate::freeze`.
+#[cfg(not(feature = "stable"))]
+pub(crate) mod simd;
 
 /// Sorts `v` recursively.
 ///
@@ -11,25 +17,38 @@ use crate::smallsort::UnstableSmallSortTypeImpl;
 ///
 /// `limit` is the number of allowed imbalanced partitions before switching to `heapsort`. If zero,
 /// this function will immediately switch to heapsort.
-pub(crate) fn quicksort<'a, T, F>(
+///
+/// If `rng` is specified, the pivots are sampled at random positions, see
+/// [`crate::sort_hardened`].
+///
+/// The recursion depth is at most `limit`, unless `C::BOUNDED_STACK` selects
+/// [`quicksort_bounded_stack`].
+pub(crate) fn quicksort<'a, C, T, F>(
     mut v: &'a mut [T],
     mut ancestor_pivot: Option<&'a T>,
     mut limit: u32,
+    mut rng: Option<&mut PivotRng>,
     is_less: &mut F,
 ) where
+    C: SortConfig,
     F: FnMut(&T, &T) -> bool,
 {
-    loop {
-        // println!("len: {}", v.len());
+    if C::BOUNDED_STACK {
+        quicksort_bounded_stack::<C, T, F>(v, ancestor_pivot, limit, rng, is_less);
+        return;
+    }
 
-        if v.len() <= T::small_sort_threshold() {
-            T::small_sort(v, is_less);
+    loop {
+        if v.len() <= T::small_sort_threshold::<C>() {
+            trace_event!(SmallSort { len: v.len() });
+            T::small_sort::<C, _>(v, is_less);
             return;
         }
 
         // If too many bad pivot choices were made, simply fall back to heapsort in order to
         // guarantee `O(N x log(N))` worst-case.
         if limit == 0 {
+            trace_event!(HeapsortFallback { len: v.len() });
             heapsort::heapsort(v, is_less);
             return;
         }
@@ -37,7 +56,14 @@ pub(crate) fn quicksort<'a, T, F>(
         limit -= 1;
 
         // Choose a pivot and try guessing whether the slice is already sorted.
-        let pivot_pos = crate::pivot::choose_pivot(v, is_less);
+        let pivot_pos = match rng.as_deref_mut() {
+            Some(rng) => pivot::choose_pivot_randomized(v, rng, is_less),
+            None => pivot::choose_pivot(v, is_less),
+        };
+        trace_event!(PivotChosen {
+            len: v.len(),
+            pivot_pos
+        });
 
         // If the chosen pivot is equal to the predecessor, then it's the smallest element in the
         // slice. Partition the slice into elements equal to and elements greater than the pivot.
@@ -46,6 +72,10 @@ pub(crate) fn quicksort<'a, T, F>(
             // SAFETY: We assume choose_pivot yields an in-bounds position.
             if !is_less(p, unsafe { v.get_unchecked(pivot_pos) }) {
                 let num_lt = partition(v, pivot_pos, &mut |a, b| !is_less(b, a));
+                trace_event!(EqualPartition {
+                    len: v.len(),
+                    num_le: num_lt + 1
+                });
 
                 // Continue sorting elements greater than the pivot. We know that `num_lt` contains
                 // the pivot. So we can continue after `num_lt`.
@@ -57,6 +87,10 @@ pub(crate) fn quicksort<'a, T, F>(
 
         // Partition the slice.
         let num_lt = partition(v, pivot_pos, is_less);
+        trace_event!(Partition {
+            len: v.len(),
+            num_lt
+        });
         // SAFETY: partition ensures that `num_lt` will be in-bounds.
         unsafe { intrinsics::assume(num_lt < v.len()) };
 
@@ -67,7 +101,7 @@ pub(crate) fn quicksort<'a, T, F>(
 
         // Recurse into the left side. We have a fixed recursion limit, testing shows no real
         // benefit for recursing into the shorter side.
-        quicksort(left, ancestor_pivot, limit, is_less);
+        quicksort::<C, _, _>(left, ancestor_pivot, limit, rng.as_deref_mut(), is_less);
 
         // Continue with the right side.
         v = right;
@@ -75,6 +109,317 @@ pub(crate) fn quicksort<'a, T, F>(
     }
 }
 
+/// A sub-slice of the input that remains to be sorted by [`quicksort_bounded_stack`], with the
+/// `ancestor_pivot` and `limit` it would have been recursed into with.
+struct PendingSlice<T> {
+    start: usize,
+    len: usize,
+    ancestor_pivot: *const T,
+    limit: u32,
+}
+
+/// Sorts `v` like [`quicksort`] without recursion, producing the same output.
+///
+/// After partitioning, the larger side is pushed onto a fixed-size stack and the loop continues
+/// with the smaller side, which is at most half as long as the partitioned slice. Everything that
+/// is pushed while sorting the smaller side is shorter than that as well, so there are at most
+/// `log2(v.len())` pending sub-slices at any time.
+///
+/// Each sub-slice is sorted with the same `ancestor_pivot` and `limit` as in [`quicksort`], so the
+/// result does not depend on the order in which they are processed. Only the order of the calls to
+/// `is_less` and `rng` differs.
+fn quicksort_bounded_stack<C, T, F>(
+    v: &mut [T],
+    ancestor_pivot: Option<&T>,
+    limit: u32,
+    mut rng: Option<&mut PivotRng>,
+    is_less: &mut F,
+) where
+    C: SortConfig,
+    F: FnMut(&T, &T) -> bool,
+{
+    const MAX_PENDING: usize = usize::BITS as usize;
+
+    let mut stack = [const { MaybeUninit::<PendingSlice<T>>::uninit() }; MAX_PENDING];
+    let mut stack_len = 0;
+
+    let v_base = v.as_mut_ptr();
+    let mut current = PendingSlice {
+        start: 0,
+        len: v.len(),
+        ancestor_pivot: ancestor_pivot.map_or(ptr::null(), ptr::from_ref),
+        limit,
+    };
+
+    loop {
+        // SAFETY: All pending sub-slices are disjoint and in-bounds of `v`, and every ancestor
+        // pivot is either null or points to an element of `v` outside of all pending sub-slices,
+        // or to the `ancestor_pivot` passed in. Elements outside of pending sub-slices are never
+        // modified, so the references created here don't alias mutable ones.
+        let (v, ancestor_pivot) = unsafe {
+            (
+                &mut *ptr::slice_from_raw_parts_mut(v_base.add(current.start), current.len),
+                current.ancestor_pivot.as_ref(),
+            )
+        };
+
+        let done = if v.len() <= T::small_sort_threshold::<C>() {
+            trace_event!(SmallSort { len: v.len() });
+            T::small_sort::<C, _>(v, is_less);
+            true
+        } else if current.limit == 0 {
+            trace_event!(HeapsortFallback { len: v.len() });
+            heapsort::heapsort(v, is_less);
+            true
+        } else {
+            false
+        };
+
+        if done {
+            if stack_len == 0 {
+                return;
+            }
+
+            stack_len -= 1;
+            // SAFETY: The first `stack_len` entries are initialized.
+            current = unsafe { stack.get_unchecked(stack_len).assume_init_read() };
+            continue;
+        }
+
+        // See `quicksort` for an explanation of the following steps.
+        current.limit -= 1;
+
+        let pivot_pos = match rng.as_deref_mut() {
+            Some(rng) => pivot::choose_pivot_randomized(v, rng, is_less),
+            None => pivot::choose_pivot(v, is_less),
+        };
+        trace_event!(PivotChosen {
+            len: v.len(),
+            pivot_pos
+        });
+
+        if let Some(p) = ancestor_pivot {
+            // SAFETY: We assume choose_pivot yields an in-bounds position.
+            if !is_less(p, unsafe { v.get_unchecked(pivot_pos) }) {
+                let num_lt = partition(v, pivot_pos, &mut |a, b| !is_less(b, a));
+                trace_event!(EqualPartition {
+                    len: v.len(),
+                    num_le: num_lt + 1
+                });
+
+                current.start += num_lt + 1;
+                current.len -= num_lt + 1;
+                current.ancestor_pivot = ptr::null();
+                continue;
+            }
+        }
+
+        let num_lt = partition(v, pivot_pos, is_less);
+        trace_event!(Partition {
+            len: v.len(),
+            num_lt
+        });
+        // SAFETY: partition ensures that `num_lt` will be in-bounds.
+        unsafe { intrinsics::assume(num_lt < v.len()) };
+
+        let left = PendingSlice {
+            start: current.start,
+            len: num_lt,
+            ancestor_pivot: current.ancestor_pivot,
+            limit: current.limit,
+        };
+        let right = PendingSlice {
+            start: current.start + num_lt + 1,
+            len: current.len - num_lt - 1,
+            // SAFETY: `num_lt` is in-bounds, the pivot is not part of either side.
+            ancestor_pivot: unsafe { v_base.add(current.start + num_lt) },
+            limit: current.limit,
+        };
+
+        let (larger, smaller) = if left.len >= right.len {
+            (left, right)
+        } else {
+            (right, left)
+        };
+
+        // The stack can't overflow, see the function documentation. `stack_len < usize::BITS`
+        // because the length of each pending sub-slice is at most half of the one below it.
+        if stack_len >= MAX_PENDING {
+            intrinsics::abort();
+        }
+        // SAFETY: `stack_len` was checked to be in-bounds.
+        unsafe { stack.get_unchecked_mut(stack_len).write(larger) };
+        stack_len += 1;
+
+        current = smaller;
+    }
+}
+
+/// Sorts the `k` smallest elements of `v` into `v[..k]`, leaving the remaining elements in
+/// `v[k..]` in unspecified order.
+///
+/// Only partitions that overlap `[0, k)` are recursed into, so the expected cost is
+/// `O(N + k x log(k))`. `ancestor_pivot` and `limit` have the same meaning as for [`quicksort`].
+/// If `limit` reaches zero the `k` smallest elements are selected in linear time and sorted with
+/// `heapsort`.
+pub(crate) fn partial_quicksort<'a, T, F>(
+    mut v: &'a mut [T],
+    mut k: usize,
+    mut ancestor_pivot: Option<&'a T>,
+    mut limit: u32,
+    is_less: &mut F,
+) where
+    F: FnMut(&T, &T) -> bool,
+{
+    loop {
+        if k == 0 {
+            return;
+        }
+
+        if v.len() <= T::small_sort_threshold::<DefaultConfig>() {
+            T::small_sort::<DefaultConfig, _>(v, is_less);
+            return;
+        }
+
+        // If too many bad pivot choices were made, select the `k` smallest elements and heapsort
+        // only those, in order to guarantee `O(N + k x log(k))` worst-case.
+        if limit == 0 {
+            if k < v.len() {
+                crate::select::partition_at_index(v, k - 1, &mut *is_less);
+            }
+            heapsort::heapsort(&mut v[..k], is_less);
+            return;
+        }
+
+        limit -= 1;
+
+        let pivot_pos = pivot::choose_pivot(v, is_less);
+
+        // See `quicksort` for an explanation of the ancestor pivot handling.
+        if let Some(p) = ancestor_pivot {
+            // SAFETY: We assume choose_pivot yields an in-bounds position.
+            if !is_less(p, unsafe { v.get_unchecked(pivot_pos) }) {
+                let num_lt = partition(v, pivot_pos, &mut |a, b| !is_less(b, a));
+
+                // All elements up to and including the pivot are equal and thus already sorted.
+                let mid = num_lt + 1;
+                if mid >= k {
+                    return;
+                }
+
+                v = &mut v[mid..];
+                k -= mid;
+                ancestor_pivot = None;
+                continue;
+            }
+        }
+
+        let num_lt = partition(v, pivot_pos, is_less);
+        // SAFETY: partition ensures that `num_lt` will be in-bounds.
+        unsafe { intrinsics::assume(num_lt < v.len()) };
+
+        let (left, right) = v.split_at_mut(num_lt);
+        let (pivot, right) = right.split_at_mut(1);
+        let pivot = &pivot[0];
+
+        if num_lt >= k {
+            // Neither the pivot nor the right side overlap `[0, k)`.
+            v = left;
+            continue;
+        }
+
+        // The left side is fully inside `[0, k)` and has to be sorted completely.
+        quicksort::<DefaultConfig, _, _>(left, ancestor_pivot, limit, None, is_less);
+
+        k -= num_lt + 1;
+        v = right;
+        ancestor_pivot = Some(pivot);
+    }
+}
+
+/// Sorts `v[start..end]` and appends one element of each group of equal elements to the unique
+/// elements in `v[..num_unique]`, returning the new number of unique elements. All elements in
+/// `v[start..end]` must be at least as large as the last unique element, and `v[num_unique..start]`
+/// may only contain duplicates, which get replaced by unique elements in unspecified order.
+///
+/// Follows [`quicksort`], visiting the sub-slices in order, so every unique element is moved into
+/// place once its sub-slice is sorted, and never again. The last unique element takes the role of
+/// `ancestor_pivot`. Elements found by the equal partition are discarded without looking at them
+/// again, as they are all equal to it.
+pub(crate) fn dedup_quicksort<T, F>(
+    v: &mut [T],
+    mut start: usize,
+    end: usize,
+    mut num_unique: usize,
+    mut limit: u32,
+    is_less: &mut F,
+) -> usize
+where
+    F: FnMut(&T, &T) -> bool,
+{
+    loop {
+        let (unique, rest) = v.split_at_mut(start);
+        let sub = &mut rest[..(end - start)];
+
+        if sub.len() <= T::small_sort_threshold::<DefaultConfig>() {
+            T::small_sort::<DefaultConfig, _>(sub, is_less);
+            return append_unique(v, start, end, num_unique, is_less);
+        }
+
+        if limit == 0 {
+            heapsort::heapsort(sub, is_less);
+            return append_unique(v, start, end, num_unique, is_less);
+        }
+
+        limit -= 1;
+
+        let pivot_pos = pivot::choose_pivot(sub, is_less);
+
+        // See `quicksort` for an explanation of the ancestor pivot handling.
+        if let Some(p) = num_unique.checked_sub(1).map(|i| &unique[i]) {
+            // SAFETY: We assume choose_pivot yields an in-bounds position.
+            if !is_less(p, unsafe { sub.get_unchecked(pivot_pos) }) {
+                let num_lt = partition(sub, pivot_pos, &mut |a, b| !is_less(b, a));
+                start += num_lt + 1;
+                continue;
+            }
+        }
+
+        let num_lt = partition(sub, pivot_pos, is_less);
+        // SAFETY: partition ensures that `num_lt` will be in-bounds.
+        unsafe { intrinsics::assume(num_lt < sub.len()) };
+
+        let pivot_pos = start + num_lt;
+        num_unique = dedup_quicksort(v, start, pivot_pos, num_unique, limit, is_less);
+
+        v.swap(num_unique, pivot_pos);
+        num_unique += 1;
+        start = pivot_pos + 1;
+    }
+}
+
+/// Appends the unique elements of the sorted `v[start..end]` to `v[..num_unique]`, see
+/// [`dedup_quicksort`].
+pub(crate) fn append_unique<T, F>(
+    v: &mut [T],
+    start: usize,
+    end: usize,
+    mut num_unique: usize,
+    is_less: &mut F,
+) -> usize
+where
+    F: FnMut(&T, &T) -> bool,
+{
+    for i in start..end {
+        if num_unique == 0 || is_less(&v[num_unique - 1], &v[i]) {
+            v.swap(num_unique, i);
+            num_unique += 1;
+        }
+    }
+
+    num_unique
+}
+
 // TODO move to main docs.
 // Instead of swapping one pair at the time, it is more efficient to perform a cyclic
 // permutation. This is not strictly equivalent to swapping, but produces a similar
@@ -110,7 +455,7 @@ pub(crate) fn quicksort<'a, T, F>(
 /// unspecified. All original elements will remain in `v` and any possible modifications via
 /// interior mutability will be observable. Same is true if `is_less` panics or `v.len()`
 /// exceeds `scratch.len()`.
-fn partition<T, F>(v: &mut [T], pivot: usize, is_less: &mut F) -> usize
+pub(crate) fn partition<T, F>(v: &mut [T], pivot: usize, is_less: &mut F) -> usize
 where
     F: FnMut(&T, &T) -> bool,
 {
@@ -148,6 +493,12 @@ where
 }
 
 const fn inst_partition<T, F: FnMut(&T, &T) -> bool>() -> fn(&mut [T], &T, &mut F) -> usize {
+    #[cfg(not(feature = "stable"))]
+    if let Some(partition) = <F as simd::SimdPartition<T>>::PARTITION {
+        // Primitive types sorted by their natural order, compared a whole vector at a time.
+        return partition;
+    }
+
     const MAX_BRANCHLESS_PARTITION_SIZE: usize = 96;
     if mem::size_of::<T>() <= MAX_BRANCHLESS_PARTITION_SIZE {
         // Specialize for types that are relatively cheap to copy, where branchless optimizations
@@ -251,7 +602,11 @@ struct PartitionState<T> {
     gap: GapGuardRaw<T>,
 }
 
-fn partition_lomuto_branchless_cyclic<T, F>(v: &mut [T], pivot: &T, is_less: &mut F) -> usize
+pub(crate) fn partition_lomuto_branchless_cyclic<T, F>(
+    v: &mut [T],
+    pivot: &T,
+    is_less: &mut F,
+) -> usize
 where
     F: FnMut(&T, &T) -> bool,
 {
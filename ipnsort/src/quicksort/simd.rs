@@ -0,0 +1,414 @@
+//! Partition kernel based on `std::simd` for primitive types sorted by their natural order, see
+//! [`crate::sort`] and [`crate::sort_floats`]. Not available with the `stable` feature.
+//!
+//! Instead of comparing and moving one element at a time, a whole vector is compared against the
+//! pivot, yielding a bitmask. A lookup table maps the bitmask to a permutation that places the
+//! elements that are less than the pivot at the front of the vector, emulating the compress-store
+//! instructions only available on some architectures. The permuted vector is then stored at both
+//! ends of the partitioned region, where the respective parts are kept.
+//!
+//! `std::simd` has no efficient permutation with runtime indices, so the permutation uses the
+//! intrinsics of the target. With AVX2, e.g. with `-C target-cpu=x86-64-v3`, it permutes 256-bit
+//! vectors. Otherwise it permutes the bytes of 128-bit vectors, with SSSE3 on x86-64, NEON on
+//! AArch64 and simd128 on WebAssembly, for 32-bit types only. Emulating the permutation with scalar
+//! loads and stores is slower than the branchless Lomuto partition, which is used on all other
+//! targets and for 64-bit types without AVX2.
+
+use core::cmp::Ordering;
+
+/// The `is_less` function of [`crate::sort`], as nameable type so that the partition can be
+/// specialized for it.
+#[derive(Copy, Clone)]
+pub(crate) struct OrdLt;
+
+impl<T: Ord> FnOnce<(&T, &T)> for OrdLt {
+    type Output = bool;
+
+    extern "rust-call" fn call_once(mut self, args: (&T, &T)) -> bool {
+        self.call_mut(args)
+    }
+}
+
+impl<T: Ord> FnMut<(&T, &T)> for OrdLt {
+    #[inline(always)]
+    extern "rust-call" fn call_mut(&mut self, (a, b): (&T, &T)) -> bool {
+        a.lt(b)
+    }
+}
+
+/// The `is_less` function of [`crate::sort_floats`], see [`OrdLt`].
+#[derive(Copy, Clone)]
+pub(crate) struct TotalLt;
+
+impl<T: crate::Float> FnOnce<(&T, &T)> for TotalLt {
+    type Output = bool;
+
+    extern "rust-call" fn call_once(mut self, args: (&T, &T)) -> bool {
+        self.call_mut(args)
+    }
+}
+
+impl<T: crate::Float> FnMut<(&T, &T)> for TotalLt {
+    #[inline(always)]
+    extern "rust-call" fn call_mut(&mut self, (a, b): (&T, &T)) -> bool {
+        a.total_cmp(b) == Ordering::Less
+    }
+}
+
+/// Signature of [`crate::quicksort::partition`] functions.
+pub(crate) type PartitionFn<T, F> = fn(&mut [T], &T, &mut F) -> usize;
+
+pub(crate) trait SimdPartition<T>: Sized {
+    /// The vectorized partition, if available for `T` and `Self` as `is_less`.
+    const PARTITION: Option<PartitionFn<T, Self>>;
+}
+
+impl<T, F> SimdPartition<T> for F {
+    default const PARTITION: Option<PartitionFn<T, F>> = None;
+}
+
+#[cfg(any(
+    all(target_arch = "x86_64", target_feature = "ssse3"),
+    all(
+        target_arch = "aarch64",
+        target_feature = "neon",
+        target_endian = "little"
+    ),
+    all(target_arch = "wasm32", target_feature = "simd128"),
+))]
+mod kernel {
+    use core::cmp::Ordering;
+    use core::{array, mem};
+    use std::simd::cmp::SimdPartialOrd;
+    use std::simd::num::{SimdFloat, SimdInt, SimdUint};
+    use std::simd::{Simd, SimdElement};
+
+    use super::{OrdLt, PartitionFn, SimdPartition, TotalLt};
+
+    #[cfg(all(target_arch = "x86_64", target_feature = "avx2"))]
+    use permute_avx2::{permute, Index, VECTOR_INDICES};
+    #[cfg(not(all(target_arch = "x86_64", target_feature = "avx2")))]
+    use permute_bytes::{permute, Index, VECTOR_INDICES};
+
+    /// Permutes 32-bit units of 256-bit vectors.
+    #[cfg(all(target_arch = "x86_64", target_feature = "avx2"))]
+    mod permute_avx2 {
+        use core::arch::x86_64::{__m256i, _mm256_loadu_si256, _mm256_permutevar8x32_epi32};
+        use core::mem;
+        use std::simd::{Simd, SimdElement};
+
+        pub(super) type Index = u32;
+
+        pub(super) const VECTOR_INDICES: usize = 8;
+
+        /// Moves unit `perm[i]` of `x` to unit `i`.
+        #[inline(always)]
+        pub(super) fn permute<T: SimdElement, const N: usize>(
+            x: Simd<T, N>,
+            perm: &[Index; VECTOR_INDICES],
+        ) -> Simd<T, N> {
+            const { assert!(mem::size_of::<Simd<T, N>>() == mem::size_of::<__m256i>()) };
+
+            // SAFETY: AVX2 is available as checked by the `cfg`, and both vector types have the
+            // same size and no invalid bit patterns.
+            unsafe {
+                let perm = _mm256_loadu_si256(perm.as_ptr().cast());
+                let x = mem::transmute_copy::<_, __m256i>(&x);
+                mem::transmute_copy(&_mm256_permutevar8x32_epi32(x, perm))
+            }
+        }
+    }
+
+    /// Permutes bytes of 128-bit vectors, with the byte shuffle instruction of the target.
+    /// `Simd::swizzle_dyn` would be the portable choice, but it selects the instruction when the
+    /// standard library is compiled, which is for the baseline target without them.
+    #[cfg(not(all(target_arch = "x86_64", target_feature = "avx2")))]
+    mod permute_bytes {
+        use core::mem;
+        use std::simd::{Simd, SimdElement};
+
+        #[cfg(target_arch = "aarch64")]
+        use core::arch::aarch64::{uint8x16_t as Bytes, vqtbl1q_u8 as shuffle};
+        #[cfg(target_arch = "wasm32")]
+        use core::arch::wasm32::{u8x16_swizzle as shuffle, v128 as Bytes};
+        #[cfg(target_arch = "x86_64")]
+        use core::arch::x86_64::{__m128i as Bytes, _mm_shuffle_epi8 as shuffle};
+
+        pub(super) type Index = u8;
+
+        pub(super) const VECTOR_INDICES: usize = 16;
+
+        /// Moves byte `perm[i]` of `x` to byte `i`.
+        #[inline(always)]
+        pub(super) fn permute<T: SimdElement, const N: usize>(
+            x: Simd<T, N>,
+            perm: &[Index; VECTOR_INDICES],
+        ) -> Simd<T, N> {
+            const { assert!(mem::size_of::<Simd<T, N>>() == mem::size_of::<Bytes>()) };
+
+            // SAFETY: The shuffle instruction is available as checked by the `cfg` of the kernel,
+            // and all vector types have the same size and no invalid bit patterns. The indices
+            // are less than 16, so every instruction selects the same bytes.
+            unsafe {
+                let x = mem::transmute_copy::<_, Bytes>(&x);
+                let perm = mem::transmute_copy::<_, Bytes>(perm);
+                mem::transmute_copy(&shuffle(x, perm))
+            }
+        }
+    }
+
+    /// Size of the vectors in bytes.
+    const VECTOR_BYTES: usize = VECTOR_INDICES * mem::size_of::<Index>();
+
+    macro_rules! impl_simd_partition {
+        ($cmp:ty, $($t:ty),*) => {
+            $(
+                impl SimdPartition<$t> for $cmp {
+                    const PARTITION: Option<PartitionFn<$t, $cmp>> = Some(|v, pivot, _is_less| {
+                        partition_simd::<$t, { VECTOR_BYTES / mem::size_of::<$t>() }>(v, pivot)
+                    });
+                }
+            )*
+        };
+    }
+
+    impl_simd_partition!(OrdLt, i32, u32);
+    impl_simd_partition!(TotalLt, f32);
+
+    // Two lanes of a 128-bit vector don't make up for the permutation, which makes the kernel
+    // slower than the scalar partition for 64-bit types.
+    #[cfg(all(target_arch = "x86_64", target_feature = "avx2"))]
+    impl_simd_partition!(OrdLt, i64, u64);
+    #[cfg(all(target_arch = "x86_64", target_feature = "avx2"))]
+    impl_simd_partition!(TotalLt, f64);
+
+    /// Lane types that can be compared as vector, with the same order as their `is_less` function.
+    trait SimdLt: SimdElement + Copy {
+        /// Lane permutations in units of `Index`, see [`compress_lut`].
+        const COMPRESS_LUT: &'static [[Index; VECTOR_INDICES]];
+
+        fn lt(a: Self, b: Self) -> bool;
+
+        /// Bit `i` is set if `a[i] < b[i]`.
+        fn lt_bitmask<const N: usize>(a: Simd<Self, N>, b: Simd<Self, N>) -> u64;
+    }
+
+    macro_rules! impl_simd_lt_int {
+        ($lut:ident, $($t:ty),*) => {
+            $(
+                impl SimdLt for $t {
+                    const COMPRESS_LUT: &'static [[Index; VECTOR_INDICES]] = &$lut;
+
+                    #[inline(always)]
+                    fn lt(a: Self, b: Self) -> bool {
+                        a < b
+                    }
+
+                    #[inline(always)]
+                    fn lt_bitmask<const N: usize>(a: Simd<Self, N>, b: Simd<Self, N>) -> u64 {
+                        a.simd_lt(b).to_bitmask()
+                    }
+                }
+            )*
+        };
+    }
+
+    impl_simd_lt_int!(COMPRESS_LUT_32, i32, u32);
+    #[cfg(all(target_arch = "x86_64", target_feature = "avx2"))]
+    impl_simd_lt_int!(COMPRESS_LUT_64, i64, u64);
+
+    macro_rules! impl_simd_lt_float {
+        ($lut:ident, $t:ty, $i:ty, $u:ty) => {
+            impl SimdLt for $t {
+                const COMPRESS_LUT: &'static [[Index; VECTOR_INDICES]] = &$lut;
+
+                #[inline(always)]
+                fn lt(a: Self, b: Self) -> bool {
+                    a.total_cmp(&b) == Ordering::Less
+                }
+
+                #[inline(always)]
+                fn lt_bitmask<const N: usize>(a: Simd<Self, N>, b: Simd<Self, N>) -> u64 {
+                    // Same transformation as `total_cmp`, flipping all bits except the sign bit of
+                    // negative numbers yields signed integers that have the IEEE 754 total order.
+                    let key = |x: Simd<$t, N>| {
+                        let bits = x.to_bits().cast::<$i>();
+                        let sign = bits >> Simd::splat(<$i>::BITS as $i - 1);
+                        bits ^ (sign.cast::<$u>() >> Simd::splat(1)).cast::<$i>()
+                    };
+
+                    key(a).simd_lt(key(b)).to_bitmask()
+                }
+            }
+        };
+    }
+
+    impl_simd_lt_float!(COMPRESS_LUT_32, f32, i32, u32);
+    #[cfg(all(target_arch = "x86_64", target_feature = "avx2"))]
+    impl_simd_lt_float!(COMPRESS_LUT_64, f64, i64, u64);
+
+    const LANES_32: usize = VECTOR_BYTES / 4;
+    #[cfg(all(target_arch = "x86_64", target_feature = "avx2"))]
+    const LANES_64: usize = VECTOR_BYTES / 8;
+
+    static COMPRESS_LUT_32: [[Index; VECTOR_INDICES]; 1 << LANES_32] =
+        compress_lut::<{ VECTOR_INDICES / LANES_32 }, { 1 << LANES_32 }>();
+    #[cfg(all(target_arch = "x86_64", target_feature = "avx2"))]
+    static COMPRESS_LUT_64: [[Index; VECTOR_INDICES]; 1 << LANES_64] =
+        compress_lut::<{ VECTOR_INDICES / LANES_64 }, { 1 << LANES_64 }>();
+
+    /// Entry `mask` moves the lanes that are set in `mask` to the front, followed by the lanes
+    /// that are not, both in ascending order. Lanes are `WIDTH` units of `Index` wide, the
+    /// indices are in these units as expected by [`permute`].
+    const fn compress_lut<const WIDTH: usize, const MASKS: usize>(
+    ) -> [[Index; VECTOR_INDICES]; MASKS] {
+        let lanes = VECTOR_INDICES / WIDTH;
+        let mut lut = [[0; VECTOR_INDICES]; MASKS];
+
+        let mut mask = 0;
+        while mask < MASKS {
+            let mut pos = 0;
+
+            let mut pass = 0;
+            while pass < 2 {
+                let mut lane = 0;
+                while lane < lanes {
+                    let is_set = mask & (1 << lane) != 0;
+                    if is_set == (pass == 0) {
+                        let mut part = 0;
+                        while part < WIDTH {
+                            lut[mask][pos * WIDTH + part] = (lane * WIDTH + part) as Index;
+                            part += 1;
+                        }
+                        pos += 1;
+                    }
+                    lane += 1;
+                }
+                pass += 1;
+            }
+
+            mask += 1;
+        }
+
+        lut
+    }
+
+    /// Number of vectors that are read from one side at once. Which side is read from is
+    /// unpredictable for random inputs, deciding it once per block amortizes the mispredictions.
+    const UNROLL: usize = 4;
+
+    /// See [`crate::quicksort::partition`], `v` does not contain the pivot.
+    fn partition_simd<T, const N: usize>(v: &mut [T], pivot: &T) -> usize
+    where
+        T: SimdLt,
+    {
+        const { assert!(N * mem::size_of::<T>() == VECTOR_BYTES) };
+
+        let len = v.len();
+        let block_len = UNROLL * N;
+
+        if len < 2 * block_len {
+            return crate::quicksort::partition_lomuto_branchless_cyclic(v, pivot, &mut |a, b| {
+                T::lt(*a, *b)
+            });
+        }
+
+        let pivot = *pivot;
+        let pivot_vec = Simd::<T, N>::splat(pivot);
+        let v_base = v.as_mut_ptr();
+
+        // SAFETY: `len >= 2 * block_len`, so the first and last `block_len` elements are in-bounds
+        // and disjoint. All elements in `[read_l, read_r)` have not been read yet, elements less
+        // than the pivot are written to `[0, write_l)` and the others to `[write_r, len)`. The gaps
+        // `[write_l, read_l)` and `[read_r, write_r)` add up to `2 * block_len` before each read,
+        // because every step reads and writes the same number of elements. Reading from the side
+        // with the smaller gap leaves at least as many elements on both sides as are read, so the
+        // full vector stores of `store_partitioned` never overwrite unread elements. Once all
+        // elements are read, the gap takes up the vectors that were read at the start. All
+        // elements are `Copy` without drop glue.
+        unsafe {
+            let load = |i: usize| {
+                Simd::<T, N>::from_array(v_base.add(i).cast::<[T; N]>().read_unaligned())
+            };
+
+            let saved_l: [Simd<T, N>; UNROLL] = array::from_fn(|i| load(i * N));
+            let saved_r: [Simd<T, N>; UNROLL] = array::from_fn(|i| load(len - block_len + i * N));
+
+            let mut read_l = block_len;
+            let mut read_r = len - block_len;
+            let mut write_l = 0;
+            let mut write_r = len;
+
+            let store = |i: usize, x: Simd<T, N>| {
+                v_base.add(i).cast::<[T; N]>().write_unaligned(x.to_array());
+            };
+
+            let store_partitioned = |x: Simd<T, N>, write_l: &mut usize, write_r: &mut usize| {
+                let mask = T::lt_bitmask(x, pivot_vec) as usize;
+                let num_lt = mask.count_ones() as usize;
+
+                let compressed = permute(x, T::COMPRESS_LUT.get_unchecked(mask));
+                store(*write_l, compressed);
+                store(*write_r - N, compressed);
+
+                *write_l += num_lt;
+                *write_r -= N - num_lt;
+            };
+
+            while read_r - read_l >= block_len {
+                let pos = if read_l - write_l <= write_r - read_r {
+                    read_l += block_len;
+                    read_l - block_len
+                } else {
+                    read_r -= block_len;
+                    read_r
+                };
+
+                // Read the whole block before writing, the stores may overlap it.
+                let block: [Simd<T, N>; UNROLL] = array::from_fn(|i| load(pos + i * N));
+                for x in block {
+                    store_partitioned(x, &mut write_l, &mut write_r);
+                }
+            }
+
+            // Same as the loop above, one vector at a time.
+            while read_r - read_l >= N {
+                let pos = if read_l - write_l <= write_r - read_r {
+                    read_l += N;
+                    read_l - N
+                } else {
+                    read_r -= N;
+                    read_r
+                };
+
+                store_partitioned(load(pos), &mut write_l, &mut write_r);
+            }
+
+            // Same as the loop above, one element at a time.
+            while read_l < read_r {
+                let x = if read_l - write_l <= write_r - read_r {
+                    read_l += 1;
+                    v_base.add(read_l - 1).read()
+                } else {
+                    read_r -= 1;
+                    v_base.add(read_r).read()
+                };
+
+                if T::lt(x, pivot) {
+                    v_base.add(write_l).write(x);
+                    write_l += 1;
+                } else {
+                    write_r -= 1;
+                    v_base.add(write_r).write(x);
+                }
+            }
+
+            for (l, r) in saved_l.into_iter().zip(saved_r) {
+                store_partitioned(l, &mut write_l, &mut write_r);
+                store_partitioned(r, &mut write_l, &mut write_r);
+            }
+
+            write_l
+        }
+    }
+}
@@ -0,0 +1,181 @@
+//! Radix sort fast path for [`crate::sort`] on primitive integers, selected with specialization.
+//! Not available with the `stable` feature.
+//!
+//! For random inputs an MSD radix sort needs only a few passes over the data, each of them
+//! without data dependent branches on the comparison result. For inputs with patterns, e.g.
+//! mostly sorted ones, the comparison based sort is faster and stays in charge.
+
+use core::mem;
+
+use crate::polyfill::{self, intrinsics};
+
+/// Inputs and buckets shorter than this are sorted by comparison. A radix pass has a fixed cost
+/// per bucket and is latency bound, it only pays off if the resulting buckets are small enough to
+/// be sorted by comparison within the cache.
+const RADIX_SORT_MIN_LEN: usize = 4096;
+
+/// Number of bits sorted per radix pass.
+const DIGIT_BITS: u32 = 8;
+
+const NUM_BUCKETS: usize = 1 << DIGIT_BITS;
+
+/// Number of elements ahead of the current bucket position that are prefetched.
+const PREFETCH_DISTANCE: usize = 16;
+
+/// Number of evenly spaced elements inspected by [`looks_random`].
+const SAMPLE_LEN: usize = 64;
+
+pub(crate) trait RadixSort: Sized {
+    /// Sorts `v` with a radix sort and returns `true` if that is expected to be faster than the
+    /// comparison based sort. Otherwise returns `false` without modifying `v`.
+    fn radix_sort_if_random(v: &mut [Self]) -> bool;
+}
+
+impl<T> RadixSort for T {
+    #[inline(always)]
+    default fn radix_sort_if_random(_v: &mut [T]) -> bool {
+        false
+    }
+}
+
+impl<T: RadixKey> RadixSort for T {
+    #[inline(always)]
+    fn radix_sort_if_random(v: &mut [T]) -> bool {
+        if v.len() < RADIX_SORT_MIN_LEN || !looks_random(v) {
+            return false;
+        }
+
+        trace_event!(RadixSort { len: v.len() });
+        radix_sort(v);
+        true
+    }
+}
+
+/// Primitive integers that can be sorted by the bits of an order preserving unsigned key.
+trait RadixKey: Copy + Ord {
+    /// Maps `self` to an unsigned integer, such that `a < b` if and only if
+    /// `a.radix_key() < b.radix_key()`.
+    fn radix_key(self) -> u64;
+}
+
+macro_rules! impl_radix_key_unsigned {
+    ($($t:ty),*) => {
+        $(
+            impl RadixKey for $t {
+                #[inline(always)]
+                fn radix_key(self) -> u64 {
+                    self as u64
+                }
+            }
+        )*
+    };
+}
+
+macro_rules! impl_radix_key_signed {
+    ($($t:ty => $u:ty),*) => {
+        $(
+            impl RadixKey for $t {
+                // Flipping the sign bit maps the smallest value to zero and keeps the order.
+                #[inline(always)]
+                fn radix_key(self) -> u64 {
+                    ((self as $u) ^ (1 << (<$u>::BITS - 1))) as u64
+                }
+            }
+        )*
+    };
+}
+
+impl_radix_key_unsigned!(u8, u16, u32, u64, usize);
+impl_radix_key_signed!(i8 => u8, i16 => u16, i32 => u32, i64 => u64, isize => usize);
+
+/// Checks a sample of evenly spaced elements for ascending and descending neighbors. Sorted,
+/// reverse sorted and mostly sorted inputs yield almost exclusively one of the two, while random
+/// inputs yield about as many of each.
+fn looks_random<T: RadixKey>(v: &[T]) -> bool {
+    let stride = v.len() / SAMPLE_LEN;
+
+    let mut ascending = 0;
+    let mut descending = 0;
+    for i in 1..SAMPLE_LEN {
+        let prev = v[(i - 1) * stride];
+        let curr = v[i * stride];
+        ascending += (prev < curr) as usize;
+        descending += (curr < prev) as usize;
+    }
+
+    const MIN_COUNT: usize = SAMPLE_LEN / 8;
+    ascending >= MIN_COUNT && descending >= MIN_COUNT
+}
+
+/// In-place MSD radix sort, also known as American flag sort.
+///
+/// Each pass sorts by the [`DIGIT_BITS`] bits starting at the most significant bit in which the
+/// elements differ, which skips bits that are the same for all elements, e.g. the upper bits of
+/// small values. Every pass consumes at least [`DIGIT_BITS`] bits, which bounds the recursion
+/// depth by `64 / DIGIT_BITS`.
+fn radix_sort<T: RadixKey>(v: &mut [T]) {
+    let first = v[0].radix_key();
+    let diff = v.iter().fold(0, |acc, x| acc | (x.radix_key() ^ first));
+    if diff == 0 {
+        // All elements are equal.
+        return;
+    }
+
+    let msb = u64::BITS - 1 - diff.leading_zeros();
+    let shift = msb.saturating_sub(DIGIT_BITS - 1);
+    let digit = |x: T| ((x.radix_key() >> shift) as usize) % NUM_BUCKETS;
+
+    let mut heads = [0; NUM_BUCKETS];
+    for x in v.iter() {
+        heads[digit(*x)] += 1;
+    }
+
+    let mut ends = [0; NUM_BUCKETS];
+    let mut sum = 0;
+    for (head, end) in heads.iter_mut().zip(ends.iter_mut()) {
+        let count = *head;
+        *head = sum;
+        sum += count;
+        *end = sum;
+    }
+
+    // Move every element into its bucket, following the cycle of displaced elements. `heads[b]`
+    // is the first position in bucket `b` that does not hold an element of `b` yet.
+    for bucket in 0..NUM_BUCKETS {
+        while heads[bucket] < ends[bucket] {
+            let mut x = v[heads[bucket]];
+            let mut d = digit(x);
+            while d != bucket {
+                mem::swap(&mut x, &mut v[heads[d]]);
+                heads[d] += 1;
+                // The next element of a bucket depends on the digit of the previous one, which
+                // serializes the cache misses. With up to `NUM_BUCKETS` positions being written
+                // concurrently the hardware prefetcher can't help, so prefetch explicitly.
+                intrinsics::prefetch_write_data::<T, 3>(
+                    v.as_ptr().wrapping_add(heads[d] + PREFETCH_DISTANCE),
+                );
+                d = digit(x);
+            }
+
+            v[heads[bucket]] = x;
+            heads[bucket] += 1;
+        }
+    }
+
+    if shift == 0 {
+        // The last bits were sorted, all elements within a bucket are equal.
+        return;
+    }
+
+    let mut start = 0;
+    for end in ends {
+        let bucket = &mut v[start..end];
+        start = end;
+
+        if bucket.len() < RADIX_SORT_MIN_LEN {
+            crate::unstable_sort(bucket, polyfill::ord_lt());
+        } else {
+            radix_sort(bucket);
+        }
+    }
+}
@@ -1,108 +1,88 @@
-use core::intrinsics;
-use core::mem::{self, ManuallyDrop, MaybeUninit};
+use core::mem::{self, ManuallyDrop};
 use core::ptr;
-use core::slice;
+#[cfg(not(feature = "stable"))]
+use core::{mem::MaybeUninit, slice};
 
+use crate::networks;
+use crate::polyfill::intrinsics;
+#[cfg(not(feature = "stable"))]
 use crate::Freeze;
+use crate::SortConfig;
+
+// Only compiled without the `stable` feature, see `crate::freeze`.
+#[cfg(not(feature = "stable"))]
+mod specialize;
 
 /// Using a trait allows us to specialize on `Freeze` which in turn allows us to make safe
-/// abstractions.
+/// abstractions. With the `stable` feature there is no specialization and the dispatch can only
+/// depend on the size of the type.
 pub(crate) trait UnstableSmallSortTypeImpl: Sized {
     /// For which input length <= return value of this function, is it valid to call `small_sort`.
-    fn small_sort_threshold() -> usize;
+    fn small_sort_threshold<C: SortConfig>() -> usize;
 
     /// Sorts `v` using strategies optimized for small sizes.
-    fn small_sort<F: FnMut(&Self, &Self) -> bool>(v: &mut [Self], is_less: &mut F);
+    fn small_sort<C: SortConfig, F: FnMut(&Self, &Self) -> bool>(v: &mut [Self], is_less: &mut F);
 }
 
+#[cfg(feature = "stable")]
 impl<T> UnstableSmallSortTypeImpl for T {
     #[inline(always)]
-    default fn small_sort_threshold() -> usize {
-        SMALL_SORT_FALLBACK_THRESHOLD
-    }
-
-    #[inline(always)]
-    default fn small_sort<F>(v: &mut [T], is_less: &mut F)
-    where
-        F: FnMut(&T, &T) -> bool,
-    {
-        small_sort_fallback(v, is_less);
-    }
-}
-
-impl<T: Freeze> UnstableSmallSortTypeImpl for T {
-    #[inline(always)]
-    fn small_sort_threshold() -> usize {
-        match const { choose_unstable_small_sort::<T>() } {
-            UnstalbeSmallSort::Fallback => SMALL_SORT_FALLBACK_THRESHOLD,
-            UnstalbeSmallSort::General => SMALL_SORT_GENERAL_THRESHOLD,
-            UnstalbeSmallSort::Network => SMALL_SORT_NETWORK_THRESHOLD,
+    fn small_sort_threshold<C: SortConfig>() -> usize {
+        if const { has_efficient_in_place_swap::<T>() } {
+            SMALL_SORT_IN_PLACE_NETWORK_THRESHOLD
+        } else {
+            SMALL_SORT_FALLBACK_THRESHOLD
         }
     }
 
     #[inline(always)]
-    fn small_sort<F>(v: &mut [T], is_less: &mut F)
+    fn small_sort<C: SortConfig, F>(v: &mut [T], is_less: &mut F)
     where
         F: FnMut(&T, &T) -> bool,
     {
-        // This construct is used to limit the LLVM IR generated, which saves large amounts of
-        // compile-time by only instantiating the code that is needed. Idea by Frank Steffahn.
-        (const { inst_unstable_small_sort::<T, F>() })(v, is_less);
+        if const { has_efficient_in_place_swap::<T>() } {
+            small_sort_in_place_network(v, is_less);
+        } else {
+            small_sort_fallback(v, is_less);
+        }
     }
 }
 
 /// Optimal number of comparisons, and good perf.
 const SMALL_SORT_FALLBACK_THRESHOLD: usize = 16;
 
-/// SAFETY: If you change this value, you have to adjust [`small_sort_general`] !
-const SMALL_SORT_GENERAL_THRESHOLD: usize = 32;
+#[cfg(not(feature = "stable"))]
+/// The length of a stack array can't depend on the [`SortConfig`], so the scratch buffers come in
+/// a few fixed sizes, and the configured thresholds are rounded up to the next one. The thresholds
+/// are checked by `crate::config::check_config` to be at most 128.
+const fn scratch_len_class(threshold: usize) -> usize {
+    if threshold <= 32 {
+        32
+    } else if threshold <= 64 {
+        64
+    } else {
+        128
+    }
+}
 
+#[cfg(not(feature = "stable"))]
 /// [`small_sort_general`] uses [`sort8_stable`] as primitive and does a kind of ping-pong merge,
 /// where the output of the first two [`sort8_stable`] calls is stored at the end of the scratch
 /// buffer. This simplifies panic handling and avoids additional copies. This affects the required
 /// scratch buffer size.
-///
-/// SAFETY: If you change this value, you have to adjust [`small_sort_general`] !
-const SMALL_SORT_GENERAL_SCRATCH_LEN: usize = SMALL_SORT_GENERAL_THRESHOLD + 16;
-
-/// SAFETY: If you change this value, you have to adjust [`small_sort_network`] !
-const SMALL_SORT_NETWORK_THRESHOLD: usize = 32;
-const SMALL_SORT_NETWORK_SCRATCH_LEN: usize = SMALL_SORT_NETWORK_THRESHOLD;
-
-/// Using a stack array, could cause a stack overflow if the type `T` is very large. To be
-/// conservative we limit the usage of small-sorts that require a stack array to types that fit
-/// within this limit.
-const MAX_STACK_ARRAY_SIZE: usize = 4096;
-
-enum UnstalbeSmallSort {
-    Fallback,
-    General,
-    Network,
+const fn small_sort_general_scratch_len(threshold: usize) -> usize {
+    scratch_len_class(threshold) + 16
 }
 
-const fn choose_unstable_small_sort<T: Freeze>() -> UnstalbeSmallSort {
-    if T::IS_COPY
-        && has_efficient_in_place_swap::<T>()
-        && (mem::size_of::<T>() * SMALL_SORT_NETWORK_SCRATCH_LEN) <= MAX_STACK_ARRAY_SIZE
-    {
-        // Heuristic for int like types.
-        return UnstalbeSmallSort::Network;
-    }
-
-    if (mem::size_of::<T>() * SMALL_SORT_GENERAL_SCRATCH_LEN) <= MAX_STACK_ARRAY_SIZE {
-        return UnstalbeSmallSort::General;
-    }
-
-    UnstalbeSmallSort::Fallback
+#[cfg(not(feature = "stable"))]
+const fn small_sort_network_scratch_len(threshold: usize) -> usize {
+    scratch_len_class(threshold)
 }
 
-const fn inst_unstable_small_sort<T: Freeze, F: FnMut(&T, &T) -> bool>() -> fn(&mut [T], &mut F) {
-    match const { choose_unstable_small_sort::<T>() } {
-        UnstalbeSmallSort::Fallback => small_sort_fallback::<T, F>,
-        UnstalbeSmallSort::General => small_sort_general::<T, F>,
-        UnstalbeSmallSort::Network => small_sort_network::<T, F>,
-    }
-}
+/// Without `Freeze` the network small-sort can't merge on scratch copies, so it is limited to what
+/// the sorting-network prefix plus insertion sort handle well.
+#[cfg(feature = "stable")]
+const SMALL_SORT_IN_PLACE_NETWORK_THRESHOLD: usize = 20;
 
 fn small_sort_fallback<T, F: FnMut(&T, &T) -> bool>(v: &mut [T], is_less: &mut F) {
     if v.len() >= 2 {
@@ -110,19 +90,21 @@ fn small_sort_fallback<T, F: FnMut(&T, &T) -> bool>(v: &mut [T], is_less: &mut F
     }
 }
 
-fn small_sort_general<T: Freeze, F: FnMut(&T, &T) -> bool>(v: &mut [T], is_less: &mut F) {
-    let mut stack_array = MaybeUninit::<[T; SMALL_SORT_GENERAL_SCRATCH_LEN]>::uninit();
+#[cfg(not(feature = "stable"))]
+fn small_sort_general<T: Freeze, F: FnMut(&T, &T) -> bool, const SCRATCH_LEN: usize>(
+    v: &mut [T],
+    is_less: &mut F,
+) {
+    let mut stack_array = MaybeUninit::<[T; SCRATCH_LEN]>::uninit();
 
     let scratch = unsafe {
-        slice::from_raw_parts_mut(
-            stack_array.as_mut_ptr() as *mut MaybeUninit<T>,
-            SMALL_SORT_GENERAL_SCRATCH_LEN,
-        )
+        slice::from_raw_parts_mut(stack_array.as_mut_ptr() as *mut MaybeUninit<T>, SCRATCH_LEN)
     };
 
     small_sort_general_with_scratch(v, scratch, is_less);
 }
 
+#[cfg(not(feature = "stable"))]
 fn small_sort_general_with_scratch<T: Freeze, F: FnMut(&T, &T) -> bool>(
     v: &mut [T],
     scratch: &mut [MaybeUninit<T>],
@@ -139,6 +121,7 @@ fn small_sort_general_with_scratch<T: Freeze, F: FnMut(&T, &T) -> bool>(
 
     let v_base = v.as_mut_ptr();
     let len_div_2 = len / 2;
+    let mut is_consistent = true;
 
     // SAFETY: See individual comments.
     unsafe {
@@ -146,8 +129,8 @@ fn small_sort_general_with_scratch<T: Freeze, F: FnMut(&T, &T) -> bool>(
 
         let presorted_len = if const { mem::size_of::<T>() <= 16 } && len >= 16 {
             // SAFETY: scratch_base is valid and has enough space.
-            sort8_stable(v_base, scratch_base, scratch_base.add(len), is_less);
-            sort8_stable(
+            is_consistent &= sort8_stable(v_base, scratch_base, scratch_base.add(len), is_less);
+            is_consistent &= sort8_stable(
                 v_base.add(len_div_2),
                 scratch_base.add(len_div_2),
                 scratch_base.add(len + 8),
@@ -197,13 +180,17 @@ fn small_sort_general_with_scratch<T: Freeze, F: FnMut(&T, &T) -> bool>(
         // If a panic occurs we ensure the original array is restored to a valid
         // permutation of the input through drop_guard. This technique is similar
         // to ping-pong merging.
-        bidirectional_merge(
+        is_consistent &= bidirectional_merge(
             &*ptr::slice_from_raw_parts(drop_guard.src, drop_guard.len),
             drop_guard.dst,
             is_less,
         );
         mem::forget(drop_guard);
     }
+
+    if !is_consistent {
+        crate::checked::ord_violation(v);
+    }
 }
 
 struct CopyOnDrop<T> {
@@ -222,7 +209,8 @@ impl<T> Drop for CopyOnDrop<T> {
     }
 }
 
-fn small_sort_network<T, F>(v: &mut [T], is_less: &mut F)
+#[cfg(not(feature = "stable"))]
+fn small_sort_network<T, F, const SCRATCH_LEN: usize>(v: &mut [T], is_less: &mut F)
 where
     T: Freeze,
     F: FnMut(&T, &T) -> bool,
@@ -234,11 +222,11 @@ where
         return;
     }
 
-    if len > SMALL_SORT_NETWORK_SCRATCH_LEN {
+    if len > SCRATCH_LEN {
         intrinsics::abort();
     }
 
-    let mut stack_array = MaybeUninit::<[T; SMALL_SORT_NETWORK_SCRATCH_LEN]>::uninit();
+    let mut stack_array = MaybeUninit::<[T; SCRATCH_LEN]>::uninit();
 
     let len_div_2 = len / 2;
     let no_merge = len < 18;
@@ -251,10 +239,10 @@ where
     // Avoid compiler unrolling, we *really* don't want that to happen here for binary-size reasons.
     loop {
         let presorted_len = if region.len() >= 13 {
-            sort13_optimal(region, is_less);
+            sort_prefix::<_, _, 13>(region, is_less);
             13
         } else if region.len() >= 9 {
-            sort9_optimal(region, is_less);
+            sort_prefix::<_, _, 9>(region, is_less);
             9
         } else {
             1
@@ -278,17 +266,45 @@ where
     // SAFETY: We checked that T is Freeze and thus observation safe.
     // Should is_less panic v was not modified in parity_merge and retains it's original input.
     // scratch and v must not alias and scratch has v.len() space.
-    unsafe {
+    let is_consistent = unsafe {
         let scratch_base = stack_array.as_mut_ptr() as *mut T;
-        bidirectional_merge(
-            &mut *ptr::slice_from_raw_parts_mut(v_base, len),
+        let is_consistent = bidirectional_merge(
+            &*ptr::slice_from_raw_parts(v_base, len),
             scratch_base,
             is_less,
         );
         ptr::copy_nonoverlapping(scratch_base, v_base, len);
+        is_consistent
+    };
+
+    if !is_consistent {
+        crate::checked::ord_violation(v);
     }
 }
 
+/// Sorts `v` with a sorting-network prefix followed by insertion sort. In contrast to
+/// `small_sort_network` there is no merge and no scratch copy, every comparison is done on the
+/// elements in `v`, which makes it safe for types with interior mutability.
+#[cfg(feature = "stable")]
+fn small_sort_in_place_network<T, F: FnMut(&T, &T) -> bool>(v: &mut [T], is_less: &mut F) {
+    let len = v.len();
+    if len < 2 {
+        return;
+    }
+
+    let presorted_len = if len >= 13 {
+        sort_prefix::<_, _, 13>(v, is_less);
+        13
+    } else if len >= 9 {
+        sort_prefix::<_, _, 9>(v, is_less);
+        9
+    } else {
+        1
+    };
+
+    insertion_sort_shift_left(v, presorted_len, is_less);
+}
+
 /// Swap two values in the slice pointed to by `v_base` at the position `a_pos` and `b_pos` if the
 /// value at position `b_pos` is less than the one at position `a_pos`.
 pub unsafe fn swap_if_less<T, F>(v_base: *mut T, a_pos: usize, b_pos: usize, is_less: &mut F)
@@ -326,114 +342,16 @@ where
 
 // Never inline this function to avoid code bloat. It still optimizes nicely and has practically no
 // performance impact.
-fn sort9_optimal<T, F>(v: &mut [T], is_less: &mut F)
+fn sort_prefix<T, F, const N: usize>(v: &mut [T], is_less: &mut F)
 where
     F: FnMut(&T, &T) -> bool,
 {
-    // SAFETY: caller must ensure v.len() >= 9.
-    if v.len() < 9 {
+    // SAFETY: caller must ensure v.len() >= N.
+    let Some(prefix) = v.first_chunk_mut::<N>() else {
         intrinsics::abort();
-    }
-
-    let v_base = v.as_mut_ptr();
-
-    // Optimal sorting network see:
-    // https://bertdobbelaere.github.io/sorting_networks.html.
-
-    // SAFETY: We checked the len.
-    unsafe {
-        swap_if_less(v_base, 0, 3, is_less);
-        swap_if_less(v_base, 1, 7, is_less);
-        swap_if_less(v_base, 2, 5, is_less);
-        swap_if_less(v_base, 4, 8, is_less);
-        swap_if_less(v_base, 0, 7, is_less);
-        swap_if_less(v_base, 2, 4, is_less);
-        swap_if_less(v_base, 3, 8, is_less);
-        swap_if_less(v_base, 5, 6, is_less);
-        swap_if_less(v_base, 0, 2, is_less);
-        swap_if_less(v_base, 1, 3, is_less);
-        swap_if_less(v_base, 4, 5, is_less);
-        swap_if_less(v_base, 7, 8, is_less);
-        swap_if_less(v_base, 1, 4, is_less);
-        swap_if_less(v_base, 3, 6, is_less);
-        swap_if_less(v_base, 5, 7, is_less);
-        swap_if_less(v_base, 0, 1, is_less);
-        swap_if_less(v_base, 2, 4, is_less);
-        swap_if_less(v_base, 3, 5, is_less);
-        swap_if_less(v_base, 6, 8, is_less);
-        swap_if_less(v_base, 2, 3, is_less);
-        swap_if_less(v_base, 4, 5, is_less);
-        swap_if_less(v_base, 6, 7, is_less);
-        swap_if_less(v_base, 1, 2, is_less);
-        swap_if_less(v_base, 3, 4, is_less);
-        swap_if_less(v_base, 5, 6, is_less);
-    }
-}
-
-// Never inline this function to avoid code bloat. It still optimizes nicely and has practically no
-// performance impact.
-fn sort13_optimal<T, F>(v: &mut [T], is_less: &mut F)
-where
-    F: FnMut(&T, &T) -> bool,
-{
-    // SAFETY: caller must ensure v.len() >= 13.
-    if v.len() < 13 {
-        intrinsics::abort();
-    }
-
-    let v_base = v.as_mut_ptr();
-
-    // Optimal sorting network see:
-    // https://bertdobbelaere.github.io/sorting_networks.html.
+    };
 
-    // SAFETY: We checked the len.
-    unsafe {
-        swap_if_less(v_base, 0, 12, is_less);
-        swap_if_less(v_base, 1, 10, is_less);
-        swap_if_less(v_base, 2, 9, is_less);
-        swap_if_less(v_base, 3, 7, is_less);
-        swap_if_less(v_base, 5, 11, is_less);
-        swap_if_less(v_base, 6, 8, is_less);
-        swap_if_less(v_base, 1, 6, is_less);
-        swap_if_less(v_base, 2, 3, is_less);
-        swap_if_less(v_base, 4, 11, is_less);
-        swap_if_less(v_base, 7, 9, is_less);
-        swap_if_less(v_base, 8, 10, is_less);
-        swap_if_less(v_base, 0, 4, is_less);
-        swap_if_less(v_base, 1, 2, is_less);
-        swap_if_less(v_base, 3, 6, is_less);
-        swap_if_less(v_base, 7, 8, is_less);
-        swap_if_less(v_base, 9, 10, is_less);
-        swap_if_less(v_base, 11, 12, is_less);
-        swap_if_less(v_base, 4, 6, is_less);
-        swap_if_less(v_base, 5, 9, is_less);
-        swap_if_less(v_base, 8, 11, is_less);
-        swap_if_less(v_base, 10, 12, is_less);
-        swap_if_less(v_base, 0, 5, is_less);
-        swap_if_less(v_base, 3, 8, is_less);
-        swap_if_less(v_base, 4, 7, is_less);
-        swap_if_less(v_base, 6, 11, is_less);
-        swap_if_less(v_base, 9, 10, is_less);
-        swap_if_less(v_base, 0, 1, is_less);
-        swap_if_less(v_base, 2, 5, is_less);
-        swap_if_less(v_base, 6, 9, is_less);
-        swap_if_less(v_base, 7, 8, is_less);
-        swap_if_less(v_base, 10, 11, is_less);
-        swap_if_less(v_base, 1, 3, is_less);
-        swap_if_less(v_base, 2, 4, is_less);
-        swap_if_less(v_base, 5, 6, is_less);
-        swap_if_less(v_base, 9, 10, is_less);
-        swap_if_less(v_base, 1, 2, is_less);
-        swap_if_less(v_base, 3, 4, is_less);
-        swap_if_less(v_base, 5, 7, is_less);
-        swap_if_less(v_base, 6, 8, is_less);
-        swap_if_less(v_base, 2, 3, is_less);
-        swap_if_less(v_base, 4, 5, is_less);
-        swap_if_less(v_base, 6, 7, is_less);
-        swap_if_less(v_base, 8, 9, is_less);
-        swap_if_less(v_base, 3, 4, is_less);
-        swap_if_less(v_base, 5, 6, is_less);
-    }
+    networks::sort_array(prefix, is_less);
 }
 
 /// Sorts range [begin, tail] assuming [begin, tail) is already sorted.
@@ -512,6 +430,7 @@ pub fn insertion_sort_shift_left<T, F: FnMut(&T, &T) -> bool>(
     }
 }
 
+#[cfg(not(feature = "stable"))]
 /// SAFETY: The caller MUST guarantee that `v_base` is valid for 4 reads and
 /// `dst` is valid for 4 writes. The result will be stored in `dst[0..4]`.
 pub unsafe fn sort4_stable<T, F: FnMut(&T, &T) -> bool>(
@@ -571,15 +490,18 @@ pub unsafe fn sort4_stable<T, F: FnMut(&T, &T) -> bool>(
     }
 }
 
+#[cfg(not(feature = "stable"))]
 /// SAFETY: The caller MUST guarantee that `v_base` is valid for 8 reads and
 /// writes, `scratch_base` and `dst` MUST be valid for 8 writes. The result will
-/// be stored in `dst[0..8]`.
+/// be stored in `dst[0..8]`. Returns `false` if `is_less` was found to be inconsistent, see
+/// [`bidirectional_merge`].
+#[must_use]
 unsafe fn sort8_stable<T: Freeze, F: FnMut(&T, &T) -> bool>(
     v_base: *mut T,
     dst: *mut T,
     scratch_base: *mut T,
     is_less: &mut F,
-) {
+) -> bool {
     // SAFETY: these pointers are all in-bounds by the precondition of our function.
     unsafe {
         sort4_stable(v_base, scratch_base, is_less);
@@ -588,11 +510,10 @@ unsafe fn sort8_stable<T: Freeze, F: FnMut(&T, &T) -> bool>(
 
     // SAFETY: scratch_base[0..8] is now initialized, allowing us to merge back
     // into dst.
-    unsafe {
-        bidirectional_merge(&*ptr::slice_from_raw_parts(scratch_base, 8), dst, is_less);
-    }
+    unsafe { bidirectional_merge(&*ptr::slice_from_raw_parts(scratch_base, 8), dst, is_less) }
 }
 
+#[cfg(not(feature = "stable"))]
 #[inline(always)]
 unsafe fn merge_up<T, F: FnMut(&T, &T) -> bool>(
     mut left_src: *const T,
@@ -626,6 +547,7 @@ unsafe fn merge_up<T, F: FnMut(&T, &T) -> bool>(
     (left_src, right_src, dst)
 }
 
+#[cfg(not(feature = "stable"))]
 #[inline(always)]
 unsafe fn merge_down<T, F: FnMut(&T, &T) -> bool>(
     mut left_src: *const T,
@@ -659,6 +581,7 @@ unsafe fn merge_down<T, F: FnMut(&T, &T) -> bool>(
     (left_src, right_src, dst)
 }
 
+#[cfg(not(feature = "stable"))]
 /// Merge v assuming v[..len / 2] and v[len / 2..] are sorted.
 ///
 /// Original idea for bi-directional merging by Igor van den Hoven (quadsort),
@@ -671,11 +594,15 @@ unsafe fn merge_down<T, F: FnMut(&T, &T) -> bool>(
 ///
 /// Note that T must be Freeze, the comparison function is evaluated on outdated
 /// temporary 'copies' that may not end up in the final array.
+///
+/// Returns `false` if the comparison function was found to not implement a total order, in which
+/// case `dst` holds a copy of `v`.
+#[must_use]
 unsafe fn bidirectional_merge<T: Freeze, F: FnMut(&T, &T) -> bool>(
     v: &[T],
     dst: *mut T,
     is_less: &mut F,
-) {
+) -> bool {
     // It helps to visualize the merge:
     //
     // Initial:
@@ -716,6 +643,7 @@ unsafe fn bidirectional_merge<T: Freeze, F: FnMut(&T, &T) -> bool>(
     // will always be in bounds if the caller guarantees that `dst` is valid for
     // `v.len()` writes.
     unsafe {
+        let dst_base = dst;
         let mut left = src;
         let mut right = src.add(len_div_2);
         let mut dst = dst;
@@ -733,7 +661,7 @@ unsafe fn bidirectional_merge<T: Freeze, F: FnMut(&T, &T) -> bool>(
         let right_end = right_rev.wrapping_add(1);
 
         // Odd length, so one element is left unconsumed in the input.
-        if len % 2 != 0 {
+        if !len.is_multiple_of(2) {
             let left_nonempty = left < left_end;
             let last_src = if left_nonempty { left } else { right };
             ptr::copy_nonoverlapping(last_src, dst, 1);
@@ -743,20 +671,19 @@ unsafe fn bidirectional_merge<T: Freeze, F: FnMut(&T, &T) -> bool>(
 
         // We now should have consumed the full input exactly once. This can
         // only fail if the comparison operator fails to be Ord, in which case
-        // we will panic and never access the inconsistent state in dst.
+        // we overwrite the inconsistent state in dst with the input, and let
+        // the caller report the violation.
         if left != left_end || right != right_end {
-            panic_on_ord_violation();
+            ptr::copy_nonoverlapping(src, dst_base, len);
+            return false;
         }
     }
-}
 
-#[inline(never)]
-fn panic_on_ord_violation() -> ! {
-    panic!("Ord violation");
+    true
 }
 
 #[must_use]
-const fn has_efficient_in_place_swap<T>() -> bool {
+pub(crate) const fn has_efficient_in_place_swap<T>() -> bool {
     const MEM_SIZE_U64: usize = mem::size_of::<u64>();
 
     mem::size_of::<T>() <= MEM_SIZE_U64
@@ -769,15 +696,3 @@ fn type_info() {
     assert!(!has_efficient_in_place_swap::<u128>());
     assert!(!has_efficient_in_place_swap::<String>());
 }
-
-trait IsCopy {
-    const IS_COPY: bool;
-}
-
-impl<T> IsCopy for T {
-    default const IS_COPY: bool = false;
-}
-
-impl<T: Copy> IsCopy for T {
-    const IS_COPY: bool = true;
-}
@@ -0,0 +1,102 @@
+//! Small-sort dispatch based on specialization, which allows picking the faster small-sorts for
+//! types that are `Freeze`. Not available with the `stable` feature.
+
+use core::mem;
+
+use super::{
+    has_efficient_in_place_swap, scratch_len_class, small_sort_fallback, small_sort_general,
+    small_sort_general_scratch_len, small_sort_network, small_sort_network_scratch_len,
+    UnstableSmallSortTypeImpl, SMALL_SORT_FALLBACK_THRESHOLD,
+};
+use crate::{Freeze, SortConfig};
+
+impl<T> UnstableSmallSortTypeImpl for T {
+    #[inline(always)]
+    default fn small_sort_threshold<C: SortConfig>() -> usize {
+        SMALL_SORT_FALLBACK_THRESHOLD
+    }
+
+    #[inline(always)]
+    default fn small_sort<C: SortConfig, F>(v: &mut [T], is_less: &mut F)
+    where
+        F: FnMut(&T, &T) -> bool,
+    {
+        small_sort_fallback(v, is_less);
+    }
+}
+
+impl<T: Freeze> UnstableSmallSortTypeImpl for T {
+    #[inline(always)]
+    fn small_sort_threshold<C: SortConfig>() -> usize {
+        match const { choose_unstable_small_sort::<C, T>() } {
+            UnstalbeSmallSort::Fallback => SMALL_SORT_FALLBACK_THRESHOLD,
+            UnstalbeSmallSort::General => C::SMALL_SORT_GENERAL_THRESHOLD,
+            UnstalbeSmallSort::Network => C::SMALL_SORT_NETWORK_THRESHOLD,
+        }
+    }
+
+    #[inline(always)]
+    fn small_sort<C: SortConfig, F>(v: &mut [T], is_less: &mut F)
+    where
+        F: FnMut(&T, &T) -> bool,
+    {
+        // This construct is used to limit the LLVM IR generated, which saves large amounts of
+        // compile-time by only instantiating the code that is needed. Idea by Frank Steffahn.
+        (const { inst_unstable_small_sort::<C, T, F>() })(v, is_less);
+    }
+}
+
+enum UnstalbeSmallSort {
+    Fallback,
+    General,
+    Network,
+}
+
+const fn choose_unstable_small_sort<C: SortConfig, T: Freeze>() -> UnstalbeSmallSort {
+    let network_scratch_len = small_sort_network_scratch_len(C::SMALL_SORT_NETWORK_THRESHOLD);
+    if T::IS_COPY
+        && has_efficient_in_place_swap::<T>()
+        && (mem::size_of::<T>() * network_scratch_len) <= C::MAX_STACK_ARRAY_SIZE
+    {
+        // Heuristic for int like types.
+        return UnstalbeSmallSort::Network;
+    }
+
+    let general_scratch_len = small_sort_general_scratch_len(C::SMALL_SORT_GENERAL_THRESHOLD);
+    if (mem::size_of::<T>() * general_scratch_len) <= C::MAX_STACK_ARRAY_SIZE {
+        return UnstalbeSmallSort::General;
+    }
+
+    UnstalbeSmallSort::Fallback
+}
+
+const fn inst_unstable_small_sort<C: SortConfig, T: Freeze, F: FnMut(&T, &T) -> bool>(
+) -> fn(&mut [T], &mut F) {
+    // The scratch lengths have to match `small_sort_general_scratch_len` and
+    // `small_sort_network_scratch_len`.
+    match const { choose_unstable_small_sort::<C, T>() } {
+        UnstalbeSmallSort::Fallback => small_sort_fallback::<T, F>,
+        UnstalbeSmallSort::General => match scratch_len_class(C::SMALL_SORT_GENERAL_THRESHOLD) {
+            32 => small_sort_general::<T, F, { 32 + 16 }>,
+            64 => small_sort_general::<T, F, { 64 + 16 }>,
+            _ => small_sort_general::<T, F, { 128 + 16 }>,
+        },
+        UnstalbeSmallSort::Network => match scratch_len_class(C::SMALL_SORT_NETWORK_THRESHOLD) {
+            32 => small_sort_network::<T, F, 32>,
+            64 => small_sort_network::<T, F, 64>,
+            _ => small_sort_network::<T, F, 128>,
+        },
+    }
+}
+
+trait IsCopy {
+    const IS_COPY: bool;
+}
+
+impl<T> IsCopy for T {
+    default const IS_COPY: bool = false;
+}
+
+impl<T: Copy> IsCopy for T {
+    const IS_COPY: bool = true;
+}
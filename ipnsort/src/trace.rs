@@ -0,0 +1,102 @@
+//! Typed events emitted by the sort implementation, to analyze decisions like pivot quality and
+//! fallback frequency. Only available with the `trace` feature, without it no events are
+//! generated and there is no runtime cost.
+//!
+//! ```
+//! use ipnsort::trace::{self, Event, Observer};
+//!
+//! #[derive(Default)]
+//! struct Partitions(Vec<(usize, usize)>);
+//!
+//! impl Observer for Partitions {
+//!     fn event(&mut self, event: Event) {
+//!         if let Event::Partition { len, num_lt } = event {
+//!             self.0.push((len, num_lt));
+//!         }
+//!     }
+//! }
+//!
+//! let mut v: Vec<u64> = (0..1_000).map(|i| (i * 7919) % 1_000).collect();
+//! let mut observer = Partitions::default();
+//! trace::with_observer(&mut observer, || ipnsort::sort(&mut v));
+//! assert!(!observer.0.is_empty());
+//! ```
+
+use std::cell::Cell;
+use std::ptr::NonNull;
+
+/// An event in the sort implementation. All lengths refer to the (sub-)slice that is processed at
+/// that point.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Event {
+    /// The input starts with a run of `run_len` ascending or strictly descending elements. If
+    /// `run_len == len` the input is sorted after reversing it if necessary.
+    ExistingRun {
+        len: usize,
+        run_len: usize,
+        descending: bool,
+    },
+    /// The element at `pivot_pos` was chosen as pivot.
+    PivotChosen { len: usize, pivot_pos: usize },
+    /// The slice was partitioned, with `num_lt` elements less than the pivot placed before it, and
+    /// `len - num_lt - 1` elements after it.
+    Partition { len: usize, num_lt: usize },
+    /// The pivot was equal to the pivot of an ancestor partition, so the slice was partitioned into
+    /// the `num_le` elements that are less or equal, which are not sorted further, and the rest.
+    EqualPartition { len: usize, num_le: usize },
+    /// Too many imbalanced partitions were encountered, the slice is sorted with heapsort.
+    HeapsortFallback { len: usize },
+    /// The slice is sorted with a small-sort.
+    SmallSort { len: usize },
+    /// The slice of primitive integers is sorted with a radix sort. Sub-slices that are sorted by
+    /// comparison afterwards emit their own events.
+    RadixSort { len: usize },
+}
+
+/// Receives the events emitted while [`with_observer`] is active.
+pub trait Observer {
+    fn event(&mut self, event: Event);
+}
+
+thread_local! {
+    static OBSERVER: Cell<Option<NonNull<dyn Observer + 'static>>> = const { Cell::new(None) };
+}
+
+/// Calls `f` with `observer` receiving all events that are emitted on the current thread. Sorts
+/// called by the observer itself are not observed. Events of [`crate::par_sort`] that happen on
+/// the spawned threads are not observed.
+pub fn with_observer<'a, R>(observer: &'a mut (dyn Observer + 'a), f: impl FnOnce() -> R) -> R {
+    // SAFETY: The lifetime is erased to store the pointer in the thread local. `_guard` restores
+    // the previous observer before this function returns or unwinds, so the pointer is never used
+    // after `'a` ends.
+    let observer: NonNull<dyn Observer + 'static> =
+        unsafe { std::mem::transmute(NonNull::from(observer)) };
+
+    let _guard = RestoreOnDrop(OBSERVER.replace(Some(observer)));
+
+    f()
+}
+
+struct RestoreOnDrop(Option<NonNull<dyn Observer + 'static>>);
+
+impl Drop for RestoreOnDrop {
+    fn drop(&mut self) {
+        OBSERVER.set(self.0);
+    }
+}
+
+/// Reports the event produced by `make_event` to the active observer, if any. Use the
+/// `trace_event!` macro instead, which compiles to nothing without the `trace` feature.
+#[inline]
+pub(crate) fn emit(make_event: impl FnOnce() -> Event) {
+    // The observer is taken out while it runs, which makes it unreachable for nested sorts and
+    // guarantees that there is only ever one mutable reference to it.
+    if let Some(mut observer) = OBSERVER.take() {
+        let _guard = RestoreOnDrop(Some(observer));
+
+        // SAFETY: The pointer was created from a mutable reference that is valid for the duration
+        // of `with_observer`, and it is only set while `with_observer` runs.
+        unsafe { observer.as_mut().event(make_event()) };
+    }
+}
@@ -0,0 +1,98 @@
+//! Fixtures shared by the tests of the individual APIs.
+
+use std::cmp::Ordering;
+use std::fmt::Debug;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::atomic::{AtomicUsize, Ordering as AtomicOrdering};
+use std::sync::OnceLock;
+use std::thread;
+
+use sort_test_tools::patterns;
+
+/// The comparison function the checks below pass to the API under test. It is `Sync`, so that the
+/// same checks work for the parallel sort.
+pub type Compare<'a, T> = &'a (dyn Fn(&T, &T) -> Ordering + Sync);
+
+/// The input patterns every API is tested with. Modules that need more extend them.
+pub fn test_patterns() -> Vec<fn(usize) -> Vec<i32>> {
+    vec![
+        patterns::random,
+        |len| patterns::random_uniform(len, 0..2),
+        |len| patterns::random_uniform(len, 0..16),
+        |len| patterns::random_sorted(len, 95.0),
+        patterns::ascending,
+        patterns::descending,
+        patterns::all_equal,
+        patterns::pipe_organ,
+    ]
+}
+
+/// Returns the next ordering of a fixed random sequence, and advances `rand_idx`.
+pub fn random_ordering(rand_idx: &AtomicUsize) -> Ordering {
+    static RANDOM_ORDERINGS: OnceLock<Vec<i32>> = OnceLock::new();
+
+    let random_orderings = RANDOM_ORDERINGS.get_or_init(|| patterns::random_uniform(10_000, 0..3));
+    let idx = rand_idx.fetch_add(1, AtomicOrdering::Relaxed) % random_orderings.len();
+    [Ordering::Less, Ordering::Equal, Ordering::Greater][random_orderings[idx] as usize]
+}
+
+/// Calls `sort_by` on copies of `v_orig`, with a comparison function that panics after none, half
+/// and all but one of the comparisons it needs, and checks that every element is retained.
+pub fn panic_retain_orig_set<T, R>(v_orig: &[T], mut sort_by: impl FnMut(&mut [T], Compare<T>) -> R)
+where
+    T: Ord + Clone + Debug,
+{
+    let required_comps = AtomicUsize::new(0);
+    sort_by(&mut v_orig.to_vec(), &|a, b| {
+        required_comps.fetch_add(1, AtomicOrdering::Relaxed);
+        a.cmp(b)
+    });
+    let required_comps = required_comps.into_inner();
+
+    let mut expected = v_orig.to_vec();
+    expected.sort();
+
+    for panic_threshold in [0, required_comps / 2, required_comps.saturating_sub(1)] {
+        let mut v = v_orig.to_vec();
+        let comp_counter = AtomicUsize::new(0);
+        let res = panic::catch_unwind(AssertUnwindSafe(|| {
+            sort_by(&mut v, &|a, b| {
+                if comp_counter.fetch_add(1, AtomicOrdering::Relaxed) == panic_threshold {
+                    panic!();
+                }
+                a.cmp(b)
+            });
+        }));
+
+        if required_comps > 0 {
+            assert!(res.is_err());
+        }
+
+        v.sort();
+        assert_eq!(v, expected);
+    }
+}
+
+/// Calls `sort_by` on a copy of `v_orig`, with a comparison function that returns random
+/// orderings, and checks that every element is retained. Returns the result of `sort_by`, or the
+/// panic it raised.
+pub fn violate_ord_retain_orig_set<T, R>(
+    v_orig: &[T],
+    sort_by: impl FnOnce(&mut [T], Compare<T>) -> R,
+) -> thread::Result<R>
+where
+    T: Ord + Clone + Debug,
+{
+    let mut v = v_orig.to_vec();
+    let rand_idx = AtomicUsize::new(0);
+    let res = panic::catch_unwind(AssertUnwindSafe(|| {
+        sort_by(&mut v, &|_, _| random_ordering(&rand_idx))
+    }));
+
+    v.sort();
+    let mut expected = v_orig.to_vec();
+    expected.sort();
+    assert_eq!(v, expected);
+
+    res
+}
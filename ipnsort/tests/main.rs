@@ -1,5 +1,11 @@
+mod common;
+
+use sort_test_tools::counting_alloc::CountingAlloc;
 use sort_test_tools::{instantiate_sort_tests, Sort};
 
+#[global_allocator]
+static ALLOC: CountingAlloc = CountingAlloc;
+
 struct SortImpl {}
 
 impl Sort for SortImpl {
@@ -20,6 +26,1876 @@ impl Sort for SortImpl {
     {
         ipnsort::sort_by(arr, compare);
     }
+
+    fn sort_by_key<T, K, F>(arr: &mut [T], f: F)
+    where
+        K: Ord,
+        F: FnMut(&T) -> K,
+    {
+        ipnsort::sort_by_key(arr, f);
+    }
+
+    fn sort_by_cached_key<T, K, F>(arr: &mut [T], f: F)
+    where
+        K: Ord,
+        F: FnMut(&T) -> K,
+    {
+        ipnsort::sort_by_cached_key(arr, f);
+    }
+
+    fn allocates<T>() -> bool {
+        std::mem::size_of::<T>()
+            >= <ipnsort::DefaultConfig as ipnsort::SortConfig>::INDIRECT_SORT_MIN_SIZE
+    }
 }
 
 instantiate_sort_tests!(SortImpl);
+
+// --- sort_with_config ---
+
+/// Runs the sort test suite against `ipnsort::sort_with_config` with the given configuration.
+macro_rules! instantiate_config_sort_tests {
+    ($mod_name:ident, $config:ident { $($name:ident: $ty:ty = $val:expr),* $(,)? }) => {
+        mod $mod_name {
+            use sort_test_tools::{instantiate_sort_tests, Sort};
+
+            struct $config;
+
+            impl ipnsort::SortConfig for $config {
+                $(const $name: $ty = $val;)*
+            }
+
+            struct SortImpl {}
+
+            impl Sort for SortImpl {
+                fn name() -> String {
+                    concat!("rust_ipnsort_", stringify!($mod_name), "_unstable").into()
+                }
+
+                fn sort<T>(arr: &mut [T])
+                where
+                    T: Ord,
+                {
+                    ipnsort::sort_with_config::<$config, T>(arr);
+                }
+
+                fn sort_by<T, F>(arr: &mut [T], compare: F)
+                where
+                    F: FnMut(&T, &T) -> std::cmp::Ordering,
+                {
+                    ipnsort::sort_by_with_config::<$config, T, F>(arr, compare);
+                }
+
+                fn allocates<T>() -> bool {
+                    std::mem::size_of::<T>()
+                        >= <$config as ipnsort::SortConfig>::INDIRECT_SORT_MIN_SIZE
+                }
+            }
+
+            instantiate_sort_tests!(SortImpl);
+        }
+    };
+}
+
+// Largest scratch buffers, and no insertion sort for small inputs.
+instantiate_config_sort_tests!(
+    config_large,
+    LargeConfig {
+        MAX_LEN_ALWAYS_INSERTION_SORT: usize = 0,
+        SMALL_SORT_GENERAL_THRESHOLD: usize = 128,
+        SMALL_SORT_NETWORK_THRESHOLD: usize = 128,
+        MAX_STACK_ARRAY_SIZE: usize = 1 << 16,
+        IMBALANCE_LIMIT_FACTOR: u32 = 4,
+    }
+);
+
+// Medium scratch buffers, with a stack array limit that excludes larger types.
+instantiate_config_sort_tests!(
+    config_medium,
+    MediumConfig {
+        MAX_LEN_ALWAYS_INSERTION_SORT: usize = 40,
+        SMALL_SORT_GENERAL_THRESHOLD: usize = 48,
+        SMALL_SORT_NETWORK_THRESHOLD: usize = 64,
+        MAX_STACK_ARRAY_SIZE: usize = 1024,
+    }
+);
+
+// Smallest thresholds, and heapsort for everything that is not handled by a small-sort.
+instantiate_config_sort_tests!(
+    config_small,
+    SmallConfig {
+        SMALL_SORT_GENERAL_THRESHOLD: usize = 8,
+        SMALL_SORT_NETWORK_THRESHOLD: usize = 8,
+        IMBALANCE_LIMIT_FACTOR: u32 = 0,
+    }
+);
+
+// Every type is sorted indirectly.
+instantiate_config_sort_tests!(
+    config_indirect,
+    IndirectConfig {
+        INDIRECT_SORT_MIN_SIZE: usize = 0,
+    }
+);
+
+// Sorts without recursion.
+instantiate_config_sort_tests!(
+    config_bounded_stack,
+    BoundedStackConfig {
+        BOUNDED_STACK: bool = true,
+    }
+);
+
+mod bounded_stack {
+    use sort_test_tools::patterns;
+
+    struct Recursive;
+
+    impl ipnsort::SortConfig for Recursive {
+        const BOUNDED_STACK: bool = false;
+    }
+
+    struct BoundedStack;
+
+    impl ipnsort::SortConfig for BoundedStack {
+        const BOUNDED_STACK: bool = true;
+    }
+
+    // Few allowed imbalanced partitions, to also reach the heapsort fallback.
+    struct RecursiveLowLimit;
+
+    impl ipnsort::SortConfig for RecursiveLowLimit {
+        const BOUNDED_STACK: bool = false;
+        const IMBALANCE_LIMIT_FACTOR: u32 = 1;
+    }
+
+    struct BoundedStackLowLimit;
+
+    impl ipnsort::SortConfig for BoundedStackLowLimit {
+        const BOUNDED_STACK: bool = true;
+        const IMBALANCE_LIMIT_FACTOR: u32 = 1;
+    }
+
+    fn check_identical<A: ipnsort::SortConfig, B: ipnsort::SortConfig>() {
+        let test_patterns: [fn(usize) -> Vec<i32>; 7] = [
+            patterns::random,
+            |len| patterns::random_uniform(len, 0..4),
+            |len| patterns::random_zipf(len, 1.0),
+            |len| patterns::random_sorted(len, 95.0),
+            |len| patterns::saw_mixed(len, (len as f64).log2().round() as usize),
+            patterns::descending,
+            patterns::pipe_organ,
+        ];
+
+        for pattern_fn in test_patterns {
+            for len in [1, 20, 21, 33, 100, 1_000, 10_000, 100_000] {
+                // Equal keys are told apart by their original index, to compare the order of equal
+                // elements as well.
+                let mut a: Vec<(i32, usize)> = pattern_fn(len).into_iter().zip(0..).collect();
+                let mut b = a.clone();
+
+                ipnsort::sort_by_with_config::<A, _, _>(&mut a, |x, y| x.0.cmp(&y.0));
+                ipnsort::sort_by_with_config::<B, _, _>(&mut b, |x, y| x.0.cmp(&y.0));
+
+                assert!(a.windows(2).all(|w| w[0].0 <= w[1].0));
+                assert_eq!(a, b, "len: {len}");
+            }
+        }
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn identical_output() {
+        check_identical::<Recursive, BoundedStack>();
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn identical_output_low_limit() {
+        check_identical::<RecursiveLowLimit, BoundedStackLowLimit>();
+    }
+}
+
+// --- sort_hardened ---
+
+mod hardened {
+    use sort_test_tools::{adversary, patterns, Sort};
+
+    struct SortImpl {}
+
+    impl Sort for SortImpl {
+        fn name() -> String {
+            "rust_ipnsort_hardened_unstable".into()
+        }
+
+        fn sort<T>(arr: &mut [T])
+        where
+            T: Ord,
+        {
+            ipnsort::sort_hardened(arr);
+        }
+
+        fn sort_by<T, F>(arr: &mut [T], compare: F)
+        where
+            F: FnMut(&T, &T) -> std::cmp::Ordering,
+        {
+            ipnsort::sort_hardened_by(arr, compare);
+        }
+    }
+
+    // The shared sort tests expect the same comparisons for the same input, which the random
+    // pivots deliberately don't provide.
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn correct_patterns() {
+        let patterns: [fn(usize) -> Vec<i32>; 5] = [
+            patterns::random,
+            |len| patterns::random_uniform(len, 0..10),
+            |len| patterns::random_sorted(len, 95.0),
+            patterns::descending,
+            patterns::pipe_organ,
+        ];
+
+        for pattern_fn in patterns {
+            for len in [0, 1, 2, 20, 21, 50, 1_000, 10_000, 100_000] {
+                let mut v = pattern_fn(len);
+                let mut expected = v.clone();
+                expected.sort();
+
+                ipnsort::sort_hardened(&mut v);
+                assert_eq!(v, expected);
+
+                let mut v: Vec<String> = pattern_fn(len).iter().map(|x| x.to_string()).collect();
+                let mut expected = v.clone();
+                expected.sort_by(|a, b| b.cmp(a));
+
+                ipnsort::sort_hardened_by(&mut v, |a, b| b.cmp(a));
+                assert_eq!(v, expected);
+            }
+        }
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn adversary() {
+        // The randomized pivot positions can't help here. The adversary decides the value of an
+        // element only when it is first compared, and makes whatever element the sort picks as
+        // pivot one of the smallest, so every sort is driven into the heapsort fallback. Up to
+        // 100_000 elements this needs 3.58 * n * log2(n) comparisons, the same as the
+        // deterministic sort. What the randomization defeats is a fixed input decided ahead of
+        // time, see `replay_adversary_input`, which does meet the 1.5 bound.
+        sort_test_tools::tests::adversary_comparisons::<SortImpl>(3.75);
+    }
+
+    fn count_comparisons<S: Sort>(input: &[i32]) -> usize {
+        let mut v = input.to_vec();
+        let mut comparisons = 0;
+        S::sort_by(&mut v, |a, b| {
+            comparisons += 1;
+            a.cmp(b)
+        });
+
+        assert!(v.windows(2).all(|w| w[0] <= w[1]));
+        comparisons
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn replay_adversary_input() {
+        let len = 100_000;
+        let n_log2_n = len as f64 * (len as f64).log2();
+
+        // Against the deterministic sort the input decided by the adversary is just as bad as the
+        // adversary itself.
+        let input = adversary::antiqsort::<super::SortImpl>(len).into_input();
+
+        let deterministic = count_comparisons::<super::SortImpl>(&input) as f64 / n_log2_n;
+        let hardened = count_comparisons::<SortImpl>(&input) as f64 / n_log2_n;
+
+        assert!(
+            deterministic > 3.0,
+            "deterministic: {deterministic:.2} * n * log2(n)"
+        );
+        assert!(hardened < 1.5, "hardened: {hardened:.2} * n * log2(n)");
+    }
+}
+
+// --- sort_by_min_cmp ---
+
+mod min_cmp {
+    use sort_test_tools::{instantiate_sort_tests, patterns, Sort};
+
+    struct SortImpl {}
+
+    impl Sort for SortImpl {
+        fn name() -> String {
+            "rust_ipnsort_min_cmp_unstable".into()
+        }
+
+        fn sort<T>(arr: &mut [T])
+        where
+            T: Ord,
+        {
+            ipnsort::sort_by_min_cmp(arr, T::cmp);
+        }
+
+        fn sort_by<T, F>(arr: &mut [T], compare: F)
+        where
+            F: FnMut(&T, &T) -> std::cmp::Ordering,
+        {
+            ipnsort::sort_by_min_cmp(arr, compare);
+        }
+    }
+
+    instantiate_sort_tests!(SortImpl);
+
+    fn log2_factorial(len: usize) -> f64 {
+        (1..=len).map(|i| (i as f64).log2()).sum()
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn comparisons_random() {
+        for len in [8, 20, 100, 1_000, 10_000, 100_000] {
+            // `patterns::random` returns the same input for the same length, split a longer one
+            // to average over different inputs.
+            let runs = 1_000_000 / len;
+            let mut inputs = patterns::random(runs * len);
+            let mut comparisons = 0;
+            for v in inputs.chunks_exact_mut(len) {
+                SortImpl::sort_by(v, |a, b| {
+                    comparisons += 1;
+                    a.cmp(b)
+                });
+            }
+
+            let ratio = comparisons as f64 / runs as f64 / log2_factorial(len);
+            assert!(ratio < 1.1, "len: {len} comparisons: {ratio:.3} * log2(n!)");
+        }
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn adversary() {
+        // The adversary drives the sort into the bottom-up heapsort fallback, which needs fewer
+        // comparisons than the one of `sort`.
+        sort_test_tools::tests::adversary_comparisons::<SortImpl>(3.0);
+    }
+}
+
+// --- sort_array ---
+
+mod sort_array {
+    use std::panic::{self, AssertUnwindSafe};
+
+    use sort_test_tools::{patterns, sorting_network};
+
+    /// Calls `$check::<N>()` for every `N` that is sorted with a sorting network.
+    macro_rules! for_each_network_len {
+        ($check:ident) => {
+            for_each_network_len!($check; 0 1 2 3 4 5 6 7 8 9 10 11 12 13 14 15 16 17 18 19 20 21 22 23 24 25 26 27 28 29 30 31 32);
+        };
+        ($check:ident; $($len:literal)*) => {
+            $($check::<$len>();)*
+        };
+    }
+
+    /// The number of comparators of the network for each length.
+    const NETWORK_SIZES: [usize; 33] = [
+        0, 0, 1, 3, 5, 9, 12, 16, 19, 25, 29, 35, 39, 45, 51, 56, 60, 71, 78, 85, 91, 100, 108,
+        115, 120, 133, 140, 150, 156, 165, 172, 180, 185,
+    ];
+
+    fn check_patterns<const N: usize>() {
+        let patterns: [fn(usize) -> Vec<i32>; 5] = [
+            patterns::random,
+            |len| patterns::random_uniform(len, 0..4),
+            patterns::ascending,
+            patterns::descending,
+            patterns::pipe_organ,
+        ];
+
+        for pattern_fn in patterns {
+            let mut arr: [i32; N] = pattern_fn(N).try_into().unwrap();
+            let mut expected = arr;
+            expected.sort();
+
+            ipnsort::sort_array(&mut arr);
+            assert_eq!(arr, expected);
+
+            let mut arr: [String; N] = pattern_fn(N)
+                .iter()
+                .map(|val| val.to_string())
+                .collect::<Vec<_>>()
+                .try_into()
+                .unwrap();
+            let mut expected = arr.clone();
+            expected.sort_by(|a, b| b.cmp(a));
+
+            ipnsort::sort_array_by(&mut arr, |a, b| b.cmp(a));
+            assert_eq!(arr, expected);
+        }
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn correct_patterns() {
+        for_each_network_len!(check_patterns);
+
+        // Larger arrays are sorted with `sort`.
+        check_patterns::<33>();
+        check_patterns::<1_000>();
+    }
+
+    fn check_zero_one_inputs<const N: usize>() {
+        for bits in 0..(1u32 << N) {
+            let mut arr: [bool; N] = core::array::from_fn(|i| (bits >> i) & 1 == 1);
+            ipnsort::sort_array(&mut arr);
+
+            let num_ones = bits.count_ones() as usize;
+            assert!(arr[..N - num_ones].iter().all(|x| !x));
+            assert!(arr[N - num_ones..].iter().all(|x| *x));
+        }
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn zero_one_inputs_exhaustive() {
+        // Sorting 2^N arrays is too slow for larger N in debug builds, see
+        // `networks_sort_zero_one_inputs_exhaustive` for those.
+        for_each_network_len!(check_zero_one_inputs; 0 1 2 3 4 5 6 7 8 9 10 11 12 13 14 15 16);
+    }
+
+    fn check_network<const N: usize>() {
+        let comparators = sorting_network::record_comparators(N, |v, compare| {
+            let arr: &mut [u32; N] = v.try_into().unwrap();
+            ipnsort::sort_array_by(arr, compare);
+        })
+        .expect("sort_array must compare the same positions for every input");
+
+        assert_eq!(comparators.len(), NETWORK_SIZES[N], "len: {N}");
+
+        // Larger networks take a couple seconds each, even though the check is optimized.
+        #[cfg(not(feature = "large_test_sizes"))]
+        if N > 24 {
+            return;
+        }
+
+        assert!(
+            sorting_network::sorts_all_zero_one_inputs(&comparators, N),
+            "len: {N}"
+        );
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn networks_sort_zero_one_inputs_exhaustive() {
+        for_each_network_len!(check_network);
+    }
+
+    #[test]
+    fn panic_safe() {
+        let input: Vec<String> = patterns::random(20)
+            .iter()
+            .map(|val| val.to_string())
+            .collect();
+
+        for panic_after in 0..NETWORK_SIZES[20] {
+            let mut arr: [String; 20] = input.clone().try_into().unwrap();
+            let mut comparisons = 0;
+
+            let res = panic::catch_unwind(AssertUnwindSafe(|| {
+                ipnsort::sort_array_by(&mut arr, |a, b| {
+                    if comparisons == panic_after {
+                        panic!();
+                    }
+                    comparisons += 1;
+                    a.cmp(b)
+                });
+            }));
+            assert!(res.is_err());
+
+            let mut remaining = arr.to_vec();
+            remaining.sort();
+            let mut expected = input.clone();
+            expected.sort();
+            assert_eq!(remaining, expected);
+        }
+    }
+}
+
+// --- select_nth_unstable ---
+
+mod select {
+    use std::cell::Cell;
+
+    use sort_test_tools::patterns;
+
+    use crate::common;
+
+    #[cfg(not(miri))]
+    const TEST_LENGTHS: &[usize] = &[1, 2, 3, 7, 16, 17, 20, 33, 50, 100, 500, 1_000, 10_000];
+
+    #[cfg(miri)]
+    const TEST_LENGTHS: &[usize] = &[1, 2, 3, 7, 16, 17, 20, 33, 50];
+
+    fn test_patterns() -> Vec<fn(usize) -> Vec<i32>> {
+        let mut test_patterns = common::test_patterns();
+        test_patterns.push(|len| patterns::random_zipf(len, 1.0));
+        test_patterns
+    }
+
+    fn check_select(v_orig: &[i32], index: usize) {
+        let mut expected = v_orig.to_vec();
+        expected.sort();
+
+        let mut v = v_orig.to_vec();
+        let (left, nth, right) = ipnsort::select_nth_unstable(&mut v, index);
+        let nth = *nth;
+
+        assert_eq!(nth, expected[index]);
+        assert!(left.iter().all(|x| *x <= nth));
+        assert!(right.iter().all(|x| *x >= nth));
+
+        v.sort();
+        assert_eq!(v, expected);
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn correct_all_indices_small() {
+        for pattern_fn in test_patterns() {
+            for len in 1..=40 {
+                let v = pattern_fn(len);
+                for index in 0..len {
+                    check_select(&v, index);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn correct_patterns() {
+        for pattern_fn in test_patterns() {
+            for len in TEST_LENGTHS {
+                let v = pattern_fn(*len);
+                for index in [0, len / 4, len / 2, len - 1 - len / 3, len - 1] {
+                    check_select(&v, index);
+                }
+            }
+        }
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn by_and_by_key() {
+        let v_orig = patterns::random(1_000);
+
+        let mut v = v_orig.clone();
+        let (_, nth, _) = ipnsort::select_nth_unstable_by(&mut v, 10, |a, b| b.cmp(a));
+        let mut expected = v_orig.clone();
+        expected.sort_by(|a, b| b.cmp(a));
+        assert_eq!(*nth, expected[10]);
+
+        let mut v = v_orig.clone();
+        let (_, nth, _) = ipnsort::select_nth_unstable_by_key(&mut v, 500, |x| x.wrapping_abs());
+        let mut expected = v_orig;
+        expected.sort_by_key(|x| x.wrapping_abs());
+        assert_eq!(nth.wrapping_abs(), expected[500].wrapping_abs());
+    }
+
+    #[test]
+    fn zst() {
+        let mut v = [(); 100];
+        let (left, _, right) = ipnsort::select_nth_unstable(&mut v, 40);
+        assert_eq!(left.len(), 40);
+        assert_eq!(right.len(), 59);
+    }
+
+    #[test]
+    #[should_panic]
+    fn index_out_of_bounds() {
+        let mut v = [3, 1, 2];
+        ipnsort::select_nth_unstable(&mut v, 3);
+    }
+
+    #[test]
+    #[should_panic]
+    fn empty() {
+        let mut v: [i32; 0] = [];
+        ipnsort::select_nth_unstable(&mut v, 0);
+    }
+
+    #[test]
+    fn panic_retain_orig_set() {
+        for len in TEST_LENGTHS {
+            common::panic_retain_orig_set(&patterns::random(*len), |v, compare| {
+                ipnsort::select_nth_unstable_by(v, len / 2, compare);
+            });
+        }
+    }
+
+    #[test]
+    fn violate_ord_retain_orig_set() {
+        for len in TEST_LENGTHS {
+            let v_orig = patterns::random(*len);
+            for index in [0, len / 2, len - 1] {
+                let _ = common::violate_ord_retain_orig_set(&v_orig, |v, compare| {
+                    ipnsort::select_nth_unstable_by(v, index, compare);
+                });
+            }
+        }
+    }
+
+    #[test]
+    fn observable_is_less() {
+        #[derive(Clone)]
+        struct CompCount {
+            val: i32,
+            comp_count: Cell<u32>,
+        }
+
+        for len in TEST_LENGTHS {
+            let mut v = patterns::random(*len)
+                .into_iter()
+                .map(|val| CompCount {
+                    val,
+                    comp_count: Cell::new(0),
+                })
+                .collect::<Vec<_>>();
+
+            let mut comp_count_global = 0;
+            ipnsort::select_nth_unstable_by(&mut v, len / 2, |a, b| {
+                a.comp_count.set(a.comp_count.get() + 1);
+                b.comp_count.set(b.comp_count.get() + 1);
+                comp_count_global += 1;
+                a.val.cmp(&b.val)
+            });
+
+            let total_inner: u64 = v.iter().map(|c| c.comp_count.get() as u64).sum();
+            assert_eq!(total_inner, comp_count_global * 2);
+        }
+    }
+}
+
+// --- partial_sort ---
+
+mod partial_sort {
+    use sort_test_tools::patterns;
+
+    use crate::common;
+
+    #[cfg(not(miri))]
+    const TEST_LENGTHS: &[usize] = &[1, 2, 3, 7, 16, 17, 20, 33, 50, 100, 500, 1_000, 10_000];
+
+    #[cfg(miri)]
+    const TEST_LENGTHS: &[usize] = &[1, 2, 3, 7, 16, 17, 20, 33, 50];
+
+    fn test_patterns() -> Vec<fn(usize) -> Vec<i32>> {
+        let mut test_patterns = common::test_patterns();
+        test_patterns.push(|len| patterns::random_zipf(len, 1.0));
+        test_patterns
+    }
+
+    fn check_partial_sort(v_orig: &[i32], k: usize) {
+        let mut expected = v_orig.to_vec();
+        expected.sort();
+
+        let mut v = v_orig.to_vec();
+        ipnsort::partial_sort(&mut v, k);
+
+        let k = k.min(v.len());
+        assert_eq!(v[..k], expected[..k]);
+
+        v.sort();
+        assert_eq!(v, expected);
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn correct_all_k_small() {
+        for pattern_fn in test_patterns() {
+            for len in 1..=40 {
+                let v = pattern_fn(len);
+                for k in 0..=(len + 1) {
+                    check_partial_sort(&v, k);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn correct_patterns() {
+        for pattern_fn in test_patterns() {
+            for len in TEST_LENGTHS {
+                let v = pattern_fn(*len);
+                for k in [1, 10, len / 3, len / 2, len.saturating_sub(1), *len] {
+                    check_partial_sort(&v, k);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn empty() {
+        let mut v: [i32; 0] = [];
+        ipnsort::partial_sort(&mut v, 0);
+        ipnsort::partial_sort(&mut v, 5);
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn by() {
+        let v_orig = patterns::random(1_000);
+
+        let mut v = v_orig.clone();
+        ipnsort::partial_sort_by(&mut v, 100, |a, b| b.cmp(a));
+
+        let mut expected = v_orig;
+        expected.sort_by(|a, b| b.cmp(a));
+        assert_eq!(v[..100], expected[..100]);
+    }
+
+    #[test]
+    fn panic_retain_orig_set() {
+        for len in TEST_LENGTHS {
+            common::panic_retain_orig_set(&patterns::random(*len), |v, compare| {
+                ipnsort::partial_sort_by(v, len / 2, compare);
+            });
+        }
+    }
+
+    #[test]
+    fn violate_ord_retain_orig_set() {
+        for len in TEST_LENGTHS {
+            let v_orig = patterns::random(*len);
+            for k in [1, len / 2, *len] {
+                let _ = common::violate_ord_retain_orig_set(&v_orig, |v, compare| {
+                    ipnsort::partial_sort_by(v, k, compare);
+                });
+            }
+        }
+    }
+}
+
+// --- sort_dedup ---
+
+mod sort_dedup {
+    use sort_test_tools::patterns;
+
+    use crate::common;
+
+    #[cfg(not(miri))]
+    const TEST_LENGTHS: &[usize] = &[
+        0, 1, 2, 3, 7, 16, 17, 20, 33, 50, 100, 500, 1_000, 10_000, 100_000,
+    ];
+
+    #[cfg(miri)]
+    const TEST_LENGTHS: &[usize] = &[0, 1, 2, 3, 7, 16, 17, 20, 33, 50];
+
+    fn test_patterns() -> Vec<fn(usize) -> Vec<i32>> {
+        let mut test_patterns = common::test_patterns();
+        test_patterns.push(|len| patterns::random_uniform(len, 0..(len as i32 / 20 + 1)));
+        test_patterns.push(|len| patterns::random_uniform(len, 0..(len as i32 / 2 + 1)));
+        test_patterns
+    }
+
+    fn check_sort_dedup(v_orig: &[i32]) {
+        let mut expected = v_orig.to_vec();
+        expected.sort();
+        expected.dedup();
+
+        let mut v = v_orig.to_vec();
+        let num_unique = ipnsort::sort_dedup(&mut v);
+        assert_eq!(v[..num_unique], expected);
+
+        // The duplicates are retained.
+        v.sort();
+        let mut v_orig_sorted = v_orig.to_vec();
+        v_orig_sorted.sort();
+        assert_eq!(v, v_orig_sorted);
+    }
+
+    #[test]
+    fn correct_all_small() {
+        for pattern_fn in test_patterns() {
+            for len in 1..=40 {
+                check_sort_dedup(&pattern_fn(len));
+            }
+        }
+    }
+
+    #[test]
+    fn correct_patterns() {
+        for pattern_fn in test_patterns() {
+            for len in TEST_LENGTHS {
+                check_sort_dedup(&pattern_fn(*len));
+            }
+        }
+
+        for len in &TEST_LENGTHS[1..] {
+            check_sort_dedup(&patterns::random_zipf(*len, 1.0));
+        }
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn by() {
+        let v_orig = patterns::random_uniform(1_000, 0..100);
+
+        let mut v = v_orig.clone();
+        let num_unique = ipnsort::sort_dedup_by(&mut v, |a, b| (b / 10).cmp(&(a / 10)));
+
+        let mut expected = v_orig;
+        expected.sort_by(|a, b| b.cmp(a));
+        expected.dedup_by_key(|x| *x / 10);
+        let keys = |v: &[i32]| v.iter().map(|x| x / 10).collect::<Vec<_>>();
+        assert_eq!(keys(&v[..num_unique]), keys(&expected));
+    }
+
+    #[test]
+    fn zst() {
+        assert_eq!(ipnsort::sort_dedup(&mut [(); 0]), 0);
+        assert_eq!(ipnsort::sort_dedup(&mut [(); 100]), 1);
+    }
+
+    #[test]
+    fn panic_retain_orig_set() {
+        for len in TEST_LENGTHS {
+            let v_orig = patterns::random_uniform(*len, 0..(*len as i32 / 4 + 1));
+            common::panic_retain_orig_set(&v_orig, |v, compare| ipnsort::sort_dedup_by(v, compare));
+        }
+    }
+
+    #[test]
+    fn violate_ord_retain_orig_set() {
+        for len in TEST_LENGTHS {
+            let res = common::violate_ord_retain_orig_set(&patterns::random(*len), |v, compare| {
+                ipnsort::sort_dedup_by(v, compare)
+            });
+
+            if let Ok(num_unique) = res {
+                assert!(num_unique <= *len);
+            }
+        }
+    }
+}
+
+// --- sort_appended ---
+
+mod sort_appended {
+    use std::mem::MaybeUninit;
+
+    use sort_test_tools::patterns;
+
+    use crate::common::{self, test_patterns};
+
+    #[cfg(not(miri))]
+    const TEST_LENGTHS: &[usize] = &[0, 1, 2, 3, 7, 16, 17, 20, 33, 50, 100, 500, 1_000, 10_000];
+
+    #[cfg(miri)]
+    const TEST_LENGTHS: &[usize] = &[0, 1, 2, 3, 7, 16, 17, 20, 33, 50];
+
+    fn prefix_lens(len: usize) -> Vec<usize> {
+        vec![
+            0,
+            1.min(len),
+            len / 2,
+            len * 95 / 100,
+            len.saturating_sub(1),
+            len,
+        ]
+    }
+
+    /// Sorts `v[..sorted_prefix_len]` and checks `sort_appended_with_scratch` with different
+    /// scratch sizes.
+    fn check_sort_appended(v_orig: &[i32], sorted_prefix_len: usize) {
+        let mut v_orig = v_orig.to_vec();
+        v_orig[..sorted_prefix_len].sort();
+
+        let mut expected = v_orig.clone();
+        expected.sort();
+
+        let mut v = v_orig.clone();
+        ipnsort::sort_appended(&mut v, sorted_prefix_len);
+        assert_eq!(v, expected);
+
+        let appended_len = v_orig.len() - sorted_prefix_len;
+        for scratch_len in [1, appended_len / 2, appended_len, v_orig.len()] {
+            let mut v = v_orig.clone();
+            let mut scratch = vec![MaybeUninit::uninit(); scratch_len];
+            ipnsort::sort_appended_with_scratch(&mut v, sorted_prefix_len, &mut scratch);
+            assert_eq!(v, expected);
+        }
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn correct_all_prefix_lens_small() {
+        for pattern_fn in test_patterns() {
+            for len in 0..=40 {
+                let v = pattern_fn(len);
+                for sorted_prefix_len in 0..=len {
+                    check_sort_appended(&v, sorted_prefix_len);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn correct_patterns() {
+        for pattern_fn in test_patterns() {
+            for len in TEST_LENGTHS {
+                let v = pattern_fn(*len);
+                for sorted_prefix_len in prefix_lens(*len) {
+                    check_sort_appended(&v, sorted_prefix_len);
+                }
+            }
+        }
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn by() {
+        let mut v_orig = patterns::random(1_000);
+        v_orig[..900].sort_by(|a, b| b.cmp(a));
+
+        let mut expected = v_orig.clone();
+        expected.sort_by(|a, b| b.cmp(a));
+
+        let mut v = v_orig.clone();
+        ipnsort::sort_appended_by(&mut v, 900, |a, b| b.cmp(a));
+        assert_eq!(v, expected);
+
+        let mut v = v_orig;
+        let mut scratch = [const { MaybeUninit::uninit() }; 100];
+        ipnsort::sort_appended_by_with_scratch(&mut v, 900, &mut scratch, |a, b| b.cmp(a));
+        assert_eq!(v, expected);
+    }
+
+    #[test]
+    fn string() {
+        for len in TEST_LENGTHS {
+            let v_orig: Vec<String> = patterns::random(*len)
+                .iter()
+                .map(|x| format!("{x:010}"))
+                .collect();
+
+            for sorted_prefix_len in prefix_lens(*len) {
+                let mut v = v_orig.clone();
+                v[..sorted_prefix_len].sort();
+
+                let mut expected = v.clone();
+                expected.sort();
+
+                let mut scratch: Vec<MaybeUninit<String>> = Vec::with_capacity(len / 4);
+                scratch.resize_with(len / 4, MaybeUninit::uninit);
+                ipnsort::sort_appended_with_scratch(&mut v, sorted_prefix_len, &mut scratch);
+                assert_eq!(v, expected);
+            }
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn prefix_out_of_bounds() {
+        ipnsort::sort_appended(&mut [3, 1, 2], 4);
+    }
+
+    #[test]
+    fn panic_retain_orig_set() {
+        for len in TEST_LENGTHS {
+            let mut v_orig = patterns::random(*len);
+            v_orig[..(len / 2)].sort();
+
+            for scratch_len in [0, len / 8, *len] {
+                let mut scratch = vec![MaybeUninit::uninit(); scratch_len];
+                common::panic_retain_orig_set(&v_orig, |v, compare| {
+                    ipnsort::sort_appended_by_with_scratch(v, len / 2, &mut scratch, compare);
+                });
+            }
+        }
+    }
+
+    #[test]
+    fn violate_ord_retain_orig_set() {
+        for len in TEST_LENGTHS {
+            let v_orig = patterns::random(*len);
+
+            for scratch_len in [0, len / 8, *len] {
+                let mut scratch = vec![MaybeUninit::uninit(); scratch_len];
+                let _ = common::violate_ord_retain_orig_set(&v_orig, |v, compare| {
+                    ipnsort::sort_appended_by_with_scratch(v, len / 2, &mut scratch, compare);
+                });
+            }
+        }
+    }
+}
+
+mod argsort {
+    use std::panic::{self, AssertUnwindSafe};
+    use std::rc::Rc;
+
+    use sort_test_tools::patterns;
+
+    use crate::common::test_patterns;
+
+    #[cfg(not(miri))]
+    const TEST_LENGTHS: &[usize] = &[0, 1, 2, 3, 7, 16, 17, 20, 33, 50, 100, 500, 1_000, 10_000];
+
+    #[cfg(miri)]
+    const TEST_LENGTHS: &[usize] = &[0, 1, 2, 3, 7, 16, 17, 20, 33, 50];
+
+    fn is_permutation(perm: &[usize]) -> bool {
+        let mut seen = vec![false; perm.len()];
+        perm.iter()
+            .all(|&i| i < seen.len() && !std::mem::replace(&mut seen[i], true))
+    }
+
+    #[test]
+    fn correct_patterns() {
+        for pattern_fn in test_patterns() {
+            for len in TEST_LENGTHS {
+                let v = pattern_fn(*len);
+                let v_orig = v.clone();
+
+                let perm = ipnsort::argsort(&v);
+                assert_eq!(v, v_orig);
+                assert!(is_permutation(&perm));
+
+                let mut expected = v.clone();
+                expected.sort();
+                let gathered: Vec<i32> = perm.iter().map(|&i| v[i]).collect();
+                assert_eq!(gathered, expected);
+            }
+        }
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn by() {
+        let v = patterns::random(1_000);
+        let perm = ipnsort::argsort_by(&v, |a, b| b.cmp(a));
+
+        let mut expected = v.clone();
+        expected.sort_by(|a, b| b.cmp(a));
+        let gathered: Vec<i32> = perm.iter().map(|&i| v[i]).collect();
+        assert_eq!(gathered, expected);
+    }
+
+    #[test]
+    fn apply_companion_slices() {
+        for pattern_fn in test_patterns() {
+            for len in TEST_LENGTHS {
+                let mut keys = pattern_fn(*len);
+                let mut values: Vec<String> = (0..*len).map(|i| i.to_string()).collect();
+                let keys_orig = keys.clone();
+
+                let mut perm = ipnsort::argsort(&keys);
+                let perm_orig = perm.clone();
+
+                ipnsort::apply_permutation(&mut keys, &mut perm);
+                assert_eq!(perm, perm_orig);
+                ipnsort::apply_permutation(&mut values, &mut perm);
+                assert_eq!(perm, perm_orig);
+
+                let mut expected = keys_orig.clone();
+                expected.sort();
+                assert_eq!(keys, expected);
+
+                for (key, value) in keys.iter().zip(values.iter()) {
+                    assert_eq!(keys_orig[value.parse::<usize>().unwrap()], *key);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn apply_random_permutation() {
+        for len in TEST_LENGTHS {
+            // Sorting the indices by random keys yields a random permutation, with a mix of cycle
+            // lengths.
+            let keys = patterns::random(*len);
+            let mut perm = ipnsort::argsort(&keys);
+
+            let v_orig: Vec<Rc<usize>> = (0..*len).map(Rc::new).collect();
+            let mut v = v_orig.clone();
+            ipnsort::apply_permutation(&mut v, &mut perm);
+
+            for (i, &src) in perm.iter().enumerate() {
+                assert!(Rc::ptr_eq(&v[i], &v_orig[src]));
+            }
+
+            drop(v);
+            assert!(v_orig.iter().all(|rc| Rc::strong_count(rc) == 1));
+        }
+    }
+
+    #[test]
+    fn apply_zst() {
+        let mut v = [(); 5];
+        let mut perm = vec![4, 3, 2, 1, 0];
+        ipnsort::apply_permutation(&mut v, &mut perm);
+        assert_eq!(perm, [4, 3, 2, 1, 0]);
+    }
+
+    #[test]
+    fn apply_invalid_permutation() {
+        let invalid_perms: [&[usize]; 4] =
+            [&[0, 1, 2], &[0, 1, 2, 3, 4], &[0, 1, 5, 3], &[2, 1, 2, 3]];
+
+        for invalid_perm in invalid_perms {
+            let v_orig = vec![10, 20, 30, 40];
+            let mut v = v_orig.clone();
+            let mut perm = invalid_perm.to_vec();
+
+            let res = panic::catch_unwind(AssertUnwindSafe(|| {
+                ipnsort::apply_permutation(&mut v, &mut perm);
+            }));
+
+            assert!(res.is_err());
+            assert_eq!(v, v_orig);
+            assert_eq!(perm, invalid_perm);
+        }
+    }
+}
+
+mod sort_pairs {
+    use std::panic::{self, AssertUnwindSafe};
+
+    use sort_test_tools::patterns;
+
+    use crate::common;
+
+    #[cfg(not(miri))]
+    const TEST_LENGTHS: &[usize] = &[
+        0, 1, 2, 3, 7, 16, 17, 20, 21, 33, 50, 100, 500, 1_000, 10_000,
+    ];
+
+    #[cfg(miri)]
+    const TEST_LENGTHS: &[usize] = &[0, 1, 2, 3, 7, 16, 17, 20, 21, 33, 50];
+
+    fn test_patterns() -> Vec<fn(usize) -> Vec<i32>> {
+        let mut test_patterns = common::test_patterns();
+        test_patterns.push(|len| patterns::saw_mixed_range(len, 20..70));
+        test_patterns
+    }
+
+    /// Sorts `keys` with index values produced by `make_value`, and checks that the keys are
+    /// sorted and every value still belongs to its original key.
+    fn check_sort_pairs<K: Ord + Clone + std::fmt::Debug, V>(
+        keys_orig: &[K],
+        make_value: impl Fn(usize) -> V,
+        value_idx: impl Fn(&V) -> usize,
+    ) {
+        let mut keys = keys_orig.to_vec();
+        let mut values: Vec<V> = (0..keys.len()).map(make_value).collect();
+
+        ipnsort::sort_pairs(&mut keys, &mut values);
+
+        let mut expected = keys_orig.to_vec();
+        expected.sort();
+        assert_eq!(keys, expected);
+
+        let mut seen = vec![false; keys.len()];
+        for (key, value) in keys.iter().zip(values.iter()) {
+            let idx = value_idx(value);
+            assert!(!std::mem::replace(&mut seen[idx], true));
+            assert_eq!(keys_orig[idx], *key);
+        }
+    }
+
+    #[test]
+    fn correct_patterns() {
+        for pattern_fn in test_patterns() {
+            for len in TEST_LENGTHS {
+                let keys: Vec<u64> = pattern_fn(*len).iter().map(|x| *x as u64).collect();
+                check_sort_pairs(&keys, |i| i, |v| *v);
+            }
+        }
+    }
+
+    #[test]
+    fn string_values() {
+        for pattern_fn in test_patterns() {
+            for len in TEST_LENGTHS {
+                let keys = pattern_fn(*len);
+                check_sort_pairs(&keys, |i| i.to_string(), |v| v.parse().unwrap());
+            }
+        }
+    }
+
+    #[test]
+    fn large_values() {
+        // Large enough to select the branchy Hoare partition.
+        for pattern_fn in test_patterns() {
+            for len in TEST_LENGTHS {
+                let keys = pattern_fn(*len);
+                check_sort_pairs(&keys, |i| [i; 16], |v| v[0]);
+            }
+        }
+    }
+
+    #[test]
+    fn string_keys() {
+        for pattern_fn in test_patterns() {
+            for len in TEST_LENGTHS {
+                let keys: Vec<String> = pattern_fn(*len)
+                    .iter()
+                    .map(|x| format!("{:010}", x))
+                    .collect();
+                check_sort_pairs(&keys, |i| i, |v| *v);
+            }
+        }
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn by() {
+        let keys_orig = patterns::random(1_000);
+        let mut keys = keys_orig.clone();
+        let mut values: Vec<usize> = (0..keys.len()).collect();
+
+        ipnsort::sort_pairs_by(&mut keys, &mut values, |a, b| b.cmp(a));
+
+        let mut expected = keys_orig.clone();
+        expected.sort_by(|a, b| b.cmp(a));
+        assert_eq!(keys, expected);
+        assert!(keys
+            .iter()
+            .zip(values.iter())
+            .all(|(key, idx)| keys_orig[*idx] == *key));
+    }
+
+    #[test]
+    fn zst() {
+        let mut keys = [(); 50];
+        let mut values: Vec<usize> = (0..50).collect();
+        ipnsort::sort_pairs(&mut keys, &mut values);
+        assert!(values.iter().enumerate().all(|(i, v)| i == *v));
+
+        let mut keys = patterns::random(50);
+        let mut values = [(); 50];
+        ipnsort::sort_pairs(&mut keys, &mut values);
+        assert!(keys.windows(2).all(|w| w[0] <= w[1]));
+    }
+
+    #[test]
+    fn len_mismatch() {
+        let mut keys = vec![3, 2, 1];
+        let mut values = vec![1, 2];
+
+        let res = panic::catch_unwind(AssertUnwindSafe(|| {
+            ipnsort::sort_pairs(&mut keys, &mut values);
+        }));
+
+        assert!(res.is_err());
+        assert_eq!(keys, [3, 2, 1]);
+        assert_eq!(values, [1, 2]);
+    }
+
+    /// Sorts the `(key, value)` pairs in `v` with `sort_pairs_by`, and writes the pairs back even
+    /// if `compare` panics, so that the shared checks can tell whether every pair is retained.
+    fn sort_pairs_by(v: &mut [(i32, String)], compare: common::Compare<(i32, String)>) {
+        let (mut keys, mut values): (Vec<i32>, Vec<String>) = v.iter().cloned().unzip();
+
+        // The values don't take part in the comparison.
+        let res = panic::catch_unwind(AssertUnwindSafe(|| {
+            ipnsort::sort_pairs_by(&mut keys, &mut values, |a, b| {
+                compare(&(*a, String::new()), &(*b, String::new()))
+            });
+        }));
+
+        for (pair, (key, value)) in v.iter_mut().zip(keys.into_iter().zip(values)) {
+            *pair = (key, value);
+        }
+
+        if let Err(err) = res {
+            panic::resume_unwind(err);
+        }
+    }
+
+    fn random_pairs(len: usize) -> Vec<(i32, String)> {
+        patterns::random(len)
+            .into_iter()
+            .zip((0..len).map(|i| i.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn panic_retain_orig_set() {
+        for len in TEST_LENGTHS {
+            common::panic_retain_orig_set(&random_pairs(*len), sort_pairs_by);
+        }
+    }
+
+    #[test]
+    fn violate_ord_retain_orig_set() {
+        for len in TEST_LENGTHS {
+            let _ = common::violate_ord_retain_orig_set(&random_pairs(*len), sort_pairs_by);
+        }
+    }
+}
+
+// --- par_sort ---
+
+mod par_sort {
+    use sort_test_tools::patterns;
+
+    use crate::common;
+
+    // Lengths above 32k use multiple threads, if the machine has more than one core.
+    #[cfg(not(miri))]
+    const TEST_LENGTHS: &[usize] = &[0, 1, 2, 3, 20, 21, 1_000, 40_000, 100_000, 300_000];
+
+    #[cfg(miri)]
+    const TEST_LENGTHS: &[usize] = &[0, 1, 2, 3, 20, 21, 50];
+
+    fn test_patterns() -> Vec<fn(usize) -> Vec<i32>> {
+        let mut test_patterns = common::test_patterns();
+        test_patterns.push(|len| patterns::saw_mixed_range(len, 20..70));
+        test_patterns
+    }
+
+    #[test]
+    fn correct_patterns() {
+        for pattern_fn in test_patterns() {
+            for len in TEST_LENGTHS {
+                let mut v = pattern_fn(*len);
+                let mut expected = v.clone();
+                expected.sort();
+
+                ipnsort::par_sort(&mut v);
+                assert_eq!(v, expected);
+            }
+        }
+    }
+
+    #[test]
+    fn string() {
+        for len in TEST_LENGTHS {
+            let mut v: Vec<String> = patterns::random(*len)
+                .iter()
+                .map(|x| format!("{:010}", x))
+                .collect();
+            let mut expected = v.clone();
+            expected.sort();
+
+            ipnsort::par_sort(&mut v);
+            assert_eq!(v, expected);
+        }
+    }
+
+    #[test]
+    fn same_as_sequential() {
+        // Equal keys are distinguishable by their original position. For types that are not sorted
+        // indirectly the partitioning is the same as for the sequential sort, so the order of
+        // equal elements must be the same too.
+        for len in TEST_LENGTHS {
+            let v_orig: Vec<(i32, usize)> = patterns::random_uniform(*len, 0..100)
+                .into_iter()
+                .enumerate()
+                .map(|(i, key)| (key, i))
+                .collect();
+
+            let mut v = v_orig.clone();
+            ipnsort::par_sort_by(&mut v, |a, b| a.0.cmp(&b.0));
+
+            let mut expected = v_orig;
+            ipnsort::sort_by(&mut expected, |a, b| a.0.cmp(&b.0));
+            assert_eq!(v, expected);
+        }
+    }
+
+    #[test]
+    fn by() {
+        for len in TEST_LENGTHS {
+            let mut v = patterns::random(*len);
+            let mut expected = v.clone();
+            expected.sort_by(|a, b| b.cmp(a));
+
+            ipnsort::par_sort_by(&mut v, |a, b| b.cmp(a));
+            assert_eq!(v, expected);
+        }
+    }
+
+    #[test]
+    fn zst() {
+        let mut v = vec![(); 100_000];
+        ipnsort::par_sort(&mut v);
+        assert_eq!(v.len(), 100_000);
+    }
+
+    #[test]
+    fn panic_retain_orig_set() {
+        for len in TEST_LENGTHS {
+            let v_orig: Vec<String> = patterns::random(*len)
+                .iter()
+                .map(|x| x.to_string())
+                .collect();
+
+            common::panic_retain_orig_set(&v_orig, |v, compare| ipnsort::par_sort_by(v, compare));
+        }
+    }
+
+    #[test]
+    fn violate_ord_retain_orig_set() {
+        for len in TEST_LENGTHS {
+            let _ = common::violate_ord_retain_orig_set(&patterns::random(*len), |v, compare| {
+                ipnsort::par_sort_by(v, compare)
+            });
+        }
+    }
+}
+
+// --- try_sort_by ---
+
+mod try_sort_by {
+    use std::cmp::Ordering;
+
+    use sort_test_tools::patterns;
+
+    use crate::common::{self, test_patterns};
+
+    #[cfg(not(miri))]
+    const TEST_LENGTHS: &[usize] = &[0, 1, 2, 3, 8, 17, 20, 21, 32, 50, 100, 1_000, 10_000];
+
+    #[cfg(miri)]
+    const TEST_LENGTHS: &[usize] = &[0, 1, 2, 3, 8, 17, 20, 21, 32, 50];
+
+    #[test]
+    fn correct_patterns() {
+        for pattern_fn in test_patterns() {
+            for len in TEST_LENGTHS {
+                let mut v = pattern_fn(*len);
+                let mut expected = v.clone();
+                expected.sort();
+
+                assert_eq!(ipnsort::try_sort_by(&mut v, |a, b| a.cmp(b)), Ok(()));
+                assert_eq!(v, expected);
+
+                let mut v: Vec<String> = pattern_fn(*len).iter().map(|x| x.to_string()).collect();
+                let mut expected = v.clone();
+                expected.sort();
+
+                assert_eq!(ipnsort::try_sort_by(&mut v, |a, b| a.cmp(b)), Ok(()));
+                assert_eq!(v, expected);
+            }
+        }
+    }
+
+    #[test]
+    fn adjacent_out_of_order() {
+        let mut v = patterns::random(100);
+        let result = ipnsort::try_sort_by(&mut v, |_, _| Ordering::Less);
+
+        let (left, right) = result.unwrap_err().indices();
+        assert!(left < right && right < v.len());
+    }
+
+    fn check_violate_ord<T: Ord + Clone + std::fmt::Debug>(v_orig: &[T]) {
+        let res = common::violate_ord_retain_orig_set(v_orig, |v, compare| {
+            ipnsort::try_sort_by(v, compare)
+        });
+
+        // The comparison function doesn't panic, and a violation must be reported inside the
+        // slice.
+        if let Err(violation) = res.unwrap() {
+            let (left, right) = violation.indices();
+            assert!(left < right && right < v_orig.len());
+        }
+    }
+
+    #[test]
+    fn violate_ord_retain_orig_set() {
+        // Both the network small-sort for `i32` and the general small-sort for `String` merge.
+        for len in TEST_LENGTHS {
+            let v_orig = patterns::random(*len);
+            check_violate_ord(&v_orig);
+
+            let v_orig: Vec<String> = v_orig.iter().map(|x| x.to_string()).collect();
+            check_violate_ord(&v_orig);
+        }
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn large_type() {
+        // Large enough to be sorted indirectly by `sort`.
+        #[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+        struct Large(i32, [u64; 128]);
+
+        for len in [2, 21, 100, 1_000] {
+            let v_orig: Vec<Large> = patterns::random(len)
+                .into_iter()
+                .map(|x| Large(x, [x as u64; 128]))
+                .collect();
+
+            let mut v = v_orig.clone();
+            let mut expected = v_orig.clone();
+            expected.sort();
+            assert_eq!(ipnsort::try_sort_by(&mut v, |a, b| a.cmp(b)), Ok(()));
+            assert_eq!(v, expected);
+
+            check_violate_ord(&v_orig);
+
+            // Claims every seventh pair is out of order.
+            let mut v = v_orig.clone();
+            let mut comparisons = 0;
+            let result = ipnsort::try_sort_by(&mut v, |a, b| {
+                comparisons += 1;
+                if comparisons % 7 == 0 {
+                    Ordering::Less
+                } else {
+                    a.cmp(b)
+                }
+            });
+
+            if let Err(violation) = result {
+                let (left, right) = violation.indices();
+                assert!(left < right && right < len);
+            }
+
+            v.sort();
+            assert_eq!(v, expected);
+        }
+    }
+
+    #[test]
+    #[cfg(not(feature = "stable"))]
+    fn merge_violation_region() {
+        use std::sync::atomic::AtomicUsize;
+
+        // A random comparator makes the small-sort merges fail, which is reported for the whole
+        // small-sort region instead of two adjacent elements.
+        let mut found_region = false;
+        for start in 0..100 {
+            let mut v = patterns::random(32);
+            let rand_idx = AtomicUsize::new(start);
+            let result = ipnsort::try_sort_by(&mut v, |_, _| common::random_ordering(&rand_idx));
+
+            if let Err(violation) = result {
+                let (left, right) = violation.indices();
+                assert!(left < right && right < v.len());
+                found_region |= right > left + 1;
+            }
+        }
+
+        assert!(found_region);
+    }
+
+    #[test]
+    fn error_display() {
+        let mut v = [2, 1];
+        let violation = ipnsort::try_sort_by(&mut v, |_, _| Ordering::Less).unwrap_err();
+
+        assert_eq!(violation.indices(), (0, 1));
+        assert_eq!(
+            violation.to_string(),
+            "comparison function does not implement a total order for the elements at indices \
+             0..=1"
+        );
+    }
+}
+
+// --- radix ---
+
+mod radix {
+    use sort_test_tools::patterns;
+
+    use crate::common;
+
+    // Around the minimum length for the radix sort, and long enough for multiple radix passes.
+    const TEST_LENGTHS: &[usize] = &[4_000, 4_095, 4_096, 4_097, 10_000, 100_000];
+
+    fn test_patterns() -> Vec<fn(usize) -> Vec<i32>> {
+        let mut test_patterns = common::test_patterns();
+        test_patterns.push(|len| patterns::random_uniform(len, 0..1_000));
+        test_patterns.push(|len| patterns::random_uniform(len, -50..50));
+        test_patterns
+    }
+
+    /// Spreads the values over the whole range of 64-bit integers.
+    fn widen(x: i32) -> i64 {
+        (x as i64).wrapping_mul(0x9E37_79B9_7F4A_7C15u64 as i64)
+    }
+
+    macro_rules! check_type {
+        ($t:ty, $convert:expr) => {
+            for pattern_fn in test_patterns() {
+                for len in TEST_LENGTHS {
+                    let mut v: Vec<$t> = pattern_fn(*len).into_iter().map($convert).collect();
+
+                    // Values that are easy to get wrong when mapping signed to unsigned keys.
+                    if let [first, second, third, ..] = v.as_mut_slice() {
+                        *first = <$t>::MAX;
+                        *second = <$t>::MIN;
+                        *third = 0;
+                    }
+
+                    let mut expected = v.clone();
+                    expected.sort_unstable();
+
+                    ipnsort::sort(&mut v);
+                    assert_eq!(v, expected, "type: {} len: {len}", stringify!($t));
+                }
+            }
+        };
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn unsigned() {
+        check_type!(u8, |x| x as u8);
+        check_type!(u16, |x| x as u16);
+        check_type!(u32, |x| x as u32);
+        check_type!(u64, |x| widen(x) as u64);
+        check_type!(usize, |x| widen(x) as usize);
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn signed() {
+        check_type!(i8, |x| x as i8);
+        check_type!(i16, |x| x as i16);
+        check_type!(i32, |x| x);
+        check_type!(i64, widen);
+        check_type!(isize, |x| widen(x) as isize);
+    }
+}
+
+mod sort_floats {
+    use crate::common::test_patterns;
+
+    #[cfg(not(miri))]
+    const TEST_LENGTHS: &[usize] = &[0, 1, 2, 7, 20, 33, 64, 100, 1_000, 10_000];
+
+    #[cfg(miri)]
+    const TEST_LENGTHS: &[usize] = &[0, 1, 2, 7, 20, 33, 64];
+
+    macro_rules! check_type {
+        ($t:ty) => {
+            // Values that are equal or unordered with `PartialOrd`, but ordered with `total_cmp`.
+            let special = [
+                <$t>::NAN,
+                -<$t>::NAN,
+                0.0,
+                -0.0,
+                <$t>::INFINITY,
+                <$t>::NEG_INFINITY,
+                <$t>::MIN_POSITIVE,
+                <$t>::MAX,
+                <$t>::MIN,
+            ];
+
+            for pattern_fn in test_patterns() {
+                for len in TEST_LENGTHS {
+                    let mut v: Vec<$t> = pattern_fn(*len)
+                        .into_iter()
+                        .enumerate()
+                        .map(|(i, x)| {
+                            if x % 7 == 0 {
+                                special[i % special.len()]
+                            } else {
+                                x as $t / 3.0
+                            }
+                        })
+                        .collect();
+
+                    let mut expected = v.clone();
+                    expected.sort_by(<$t>::total_cmp);
+
+                    ipnsort::sort_floats(&mut v);
+
+                    let to_bits = |v: &[$t]| v.iter().map(|x| x.to_bits()).collect::<Vec<_>>();
+                    assert_eq!(
+                        to_bits(&v),
+                        to_bits(&expected),
+                        "type: {} len: {len}",
+                        stringify!($t)
+                    );
+                }
+            }
+        };
+    }
+
+    #[test]
+    fn f32() {
+        check_type!(f32);
+    }
+
+    #[test]
+    fn f64() {
+        check_type!(f64);
+    }
+}
+
+// --- trace ---
+
+#[cfg(feature = "trace")]
+mod trace {
+    use std::panic::{self, AssertUnwindSafe};
+
+    use ipnsort::trace::{self, Event, Observer};
+    use sort_test_tools::patterns;
+
+    use crate::common::test_patterns;
+
+    #[cfg(not(miri))]
+    const TEST_LENGTHS: &[usize] = &[
+        0, 1, 2, 3, 7, 16, 17, 20, 21, 33, 50, 100, 500, 1_000, 10_000,
+    ];
+
+    #[cfg(miri)]
+    const TEST_LENGTHS: &[usize] = &[0, 1, 2, 3, 7, 16, 17, 20, 21, 33, 50];
+
+    #[derive(Default)]
+    struct Recorder(Vec<Event>);
+
+    impl Observer for Recorder {
+        fn event(&mut self, event: Event) {
+            self.0.push(event);
+        }
+    }
+
+    fn record(v: &mut [i32]) -> Vec<Event> {
+        let mut recorder = Recorder::default();
+        trace::with_observer(&mut recorder, || ipnsort::sort(v));
+        recorder.0
+    }
+
+    #[test]
+    fn every_element_accounted_for() {
+        // Every element ends up either as pivot, in an equal partition, in a small-sort, in a
+        // heapsort fallback, in a radix sort or in an existing run that covers the whole input.
+        for pattern_fn in test_patterns() {
+            for len in TEST_LENGTHS {
+                let mut v = pattern_fn(*len);
+                let events = record(&mut v);
+
+                let mut covered = 0;
+                for event in &events {
+                    match *event {
+                        Event::ExistingRun { len, run_len, .. } => {
+                            assert!(run_len <= len);
+                            if run_len == len {
+                                covered += len;
+                            }
+                        }
+                        Event::PivotChosen { len, pivot_pos } => assert!(pivot_pos < len),
+                        Event::Partition { len, num_lt } => {
+                            assert!(num_lt < len);
+                            covered += 1;
+                        }
+                        Event::EqualPartition { len, num_le } => {
+                            assert!(num_le <= len);
+                            covered += num_le;
+                        }
+                        Event::HeapsortFallback { len } | Event::SmallSort { len } => {
+                            covered += len;
+                        }
+                        Event::RadixSort { len } => {
+                            // The events of the buckets that are sorted by comparison afterwards
+                            // cover the same elements again.
+                            covered += len;
+                            break;
+                        }
+                        _ => {}
+                    }
+                }
+
+                let expected_covered = if *len < 2 { 0 } else { *len };
+                assert_eq!(covered, expected_covered, "len: {len} events: {events:?}");
+                assert!(v.windows(2).all(|w| w[0] <= w[1]));
+            }
+        }
+    }
+
+    #[test]
+    fn small_input() {
+        let mut v = patterns::random(20);
+        assert_eq!(record(&mut v), [Event::SmallSort { len: 20 }]);
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn existing_run() {
+        let mut v = patterns::descending(1_000);
+        let events = record(&mut v);
+        assert_eq!(
+            events,
+            [Event::ExistingRun {
+                len: 1_000,
+                run_len: 1_000,
+                descending: true
+            }]
+        );
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn equal_partition() {
+        // Short enough to not be radix sorted.
+        let mut v = patterns::random_uniform(2_000, 0..2);
+        let events = record(&mut v);
+        assert!(events
+            .iter()
+            .any(|event| matches!(event, Event::EqualPartition { .. })));
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    #[cfg(not(feature = "stable"))]
+    fn radix_sort() {
+        let mut v = patterns::random(10_000);
+        assert_eq!(record(&mut v)[0], Event::RadixSort { len: 10_000 });
+
+        let mut v = patterns::random_sorted(10_000, 95.0);
+        assert!(!record(&mut v)
+            .iter()
+            .any(|event| matches!(event, Event::RadixSort { .. })));
+    }
+
+    #[test]
+    fn observer_restored() {
+        let mut recorder = Recorder::default();
+        let res = panic::catch_unwind(AssertUnwindSafe(|| {
+            trace::with_observer(&mut recorder, || {
+                ipnsort::sort(&mut patterns::random(100));
+                panic!();
+            })
+        }));
+        assert!(res.is_err());
+
+        let events_len = recorder.0.len();
+        assert!(events_len > 0);
+
+        // The observer must no longer be active.
+        ipnsort::sort(&mut patterns::random(100));
+        assert_eq!(recorder.0.len(), events_len);
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn nested_sort_in_observer() {
+        struct Sorting(usize);
+
+        impl Observer for Sorting {
+            fn event(&mut self, _event: Event) {
+                self.0 += 1;
+                ipnsort::sort(&mut patterns::random(100));
+            }
+        }
+
+        let mut observer = Sorting(0);
+        let mut v = patterns::random(1_000);
+        let mut expected = v.clone();
+        expected.sort();
+
+        trace::with_observer(&mut observer, || ipnsort::sort(&mut v));
+
+        // Would recurse endlessly if the sorts in the observer were observed.
+        assert_eq!(v, expected);
+        assert!(observer.0 > 0);
+    }
+}
@@ -0,0 +1,58 @@
+#ifndef IPNSORT_H
+#define IPNSORT_H
+
+/* Generated by cbindgen from ipnsort_capi/src/lib.rs, do not edit manually. */
+
+#include <stddef.h>
+#include <stdint.h>
+
+// Comparison function with the same signature as the one used by `qsort`.
+typedef int (*IpnsortCmpFn)(const void*, const void*);
+
+// Comparison function with the same signature as the one used by glibc `qsort_r`.
+typedef int (*IpnsortCmpFnR)(const void*, const void*, void*);
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+// Sorts the array `base` of `nmemb` elements of `size` bytes each, using `compar` for
+// comparisons, same as `qsort`.
+//
+// The sort is unstable. `compar` must return a negative value, zero or a positive value if the
+// first argument is considered less than, equal to or greater than the second one. If `compar`
+// does not implement a total order, the resulting order is unspecified, but all original elements
+// will remain in `base`.
+//
+// # Safety
+//
+// `base` must be valid for reads and writes of `nmemb * size` bytes, or `nmemb` must be zero.
+void ipnsort_qsort(void *base, size_t nmemb, size_t size, IpnsortCmpFn compar);
+
+// Same as `ipnsort_qsort`, but passes `arg` as third argument to every `compar` call. The
+// argument order follows glibc `qsort_r`.
+//
+// # Safety
+//
+// `base` must be valid for reads and writes of `nmemb * size` bytes, or `nmemb` must be zero.
+void ipnsort_qsort_r(void *base, size_t nmemb, size_t size, IpnsortCmpFnR compar, void *arg);
+
+// Sorts `len` elements of type `int32_t` in ascending order.
+//
+// # Safety
+//
+// `data` must be valid for reads and writes of `len` elements, or `len` must be zero.
+void ipnsort_sort_i32(int32_t *data, size_t len);
+
+// Sorts `len` elements of type `uint64_t` in ascending order.
+//
+// # Safety
+//
+// `data` must be valid for reads and writes of `len` elements, or `len` must be zero.
+void ipnsort_sort_u64(uint64_t *data, size_t len);
+
+#ifdef __cplusplus
+}  // extern "C"
+#endif  // __cplusplus
+
+#endif  /* IPNSORT_H */
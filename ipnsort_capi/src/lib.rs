@@ -0,0 +1,209 @@
+//! C ABI for ipnsort, so that C and C++ code can use the Rust implementation directly.
+//!
+//! The header `include/ipnsort.h` is generated by the build script into `OUT_DIR`, and the
+//! `header_up_to_date` test checks that the checked-in copy matches it.
+
+use core::cmp::Ordering;
+use core::ffi::{c_int, c_void};
+use core::mem::{self, MaybeUninit};
+use core::ptr;
+use core::slice;
+use std::panic::{self, AssertUnwindSafe};
+
+/// Comparison function with the same signature as the one used by `qsort`.
+pub type IpnsortCmpFn = unsafe extern "C" fn(*const c_void, *const c_void) -> c_int;
+
+/// Comparison function with the same signature as the one used by glibc `qsort_r`.
+pub type IpnsortCmpFnR = unsafe extern "C" fn(*const c_void, *const c_void, *mut c_void) -> c_int;
+
+/// Sorts the array `base` of `nmemb` elements of `size` bytes each, using `compar` for
+/// comparisons, same as `qsort`.
+///
+/// The sort is unstable. `compar` must return a negative value, zero or a positive value if the
+/// first argument is considered less than, equal to or greater than the second one. If `compar`
+/// does not implement a total order, the resulting order is unspecified, but all original elements
+/// will remain in `base`.
+///
+/// # Safety
+///
+/// `base` must be valid for reads and writes of `nmemb * size` bytes, or `nmemb` must be zero.
+#[no_mangle]
+pub unsafe extern "C" fn ipnsort_qsort(
+    base: *mut c_void,
+    nmemb: usize,
+    size: usize,
+    compar: IpnsortCmpFn,
+) {
+    // SAFETY: Upheld by the caller.
+    unsafe {
+        sort_raw(base as *mut u8, nmemb, size, &mut |a, b| compar(a, b));
+    }
+}
+
+/// Same as `ipnsort_qsort`, but passes `arg` as third argument to every `compar` call. The
+/// argument order follows glibc `qsort_r`.
+///
+/// # Safety
+///
+/// `base` must be valid for reads and writes of `nmemb * size` bytes, or `nmemb` must be zero.
+#[no_mangle]
+pub unsafe extern "C" fn ipnsort_qsort_r(
+    base: *mut c_void,
+    nmemb: usize,
+    size: usize,
+    compar: IpnsortCmpFnR,
+    arg: *mut c_void,
+) {
+    // SAFETY: Upheld by the caller.
+    unsafe {
+        sort_raw(base as *mut u8, nmemb, size, &mut |a, b| compar(a, b, arg));
+    }
+}
+
+/// Sorts `len` elements of type `int32_t` in ascending order.
+///
+/// # Safety
+///
+/// `data` must be valid for reads and writes of `len` elements, or `len` must be zero.
+#[no_mangle]
+pub unsafe extern "C" fn ipnsort_sort_i32(data: *mut i32, len: usize) {
+    if len == 0 {
+        return;
+    }
+
+    // SAFETY: Upheld by the caller.
+    ipnsort::sort(unsafe { slice::from_raw_parts_mut(data, len) });
+}
+
+/// Sorts `len` elements of type `uint64_t` in ascending order.
+///
+/// # Safety
+///
+/// `data` must be valid for reads and writes of `len` elements, or `len` must be zero.
+#[no_mangle]
+pub unsafe extern "C" fn ipnsort_sort_u64(data: *mut u64, len: usize) {
+    if len == 0 {
+        return;
+    }
+
+    // SAFETY: Upheld by the caller.
+    ipnsort::sort(unsafe { slice::from_raw_parts_mut(data, len) });
+}
+
+/// Element of `size_of::<T>()` bytes with unspecified content, which may include padding. Only ever
+/// passed to the comparison function by address, `T` is only used for size and alignment.
+#[derive(Copy, Clone)]
+#[repr(transparent)]
+struct Elem<T: Copy>(MaybeUninit<T>);
+
+/// Sorts the raw array, with `cmp` returning a C style ordering for two element pointers.
+///
+/// SAFETY: The caller must guarantee that `base` is valid for reads and writes of `nmemb * size`
+/// bytes, or that `nmemb` is zero.
+unsafe fn sort_raw<F>(base: *mut u8, nmemb: usize, size: usize, cmp: &mut F)
+where
+    F: FnMut(*const c_void, *const c_void) -> c_int,
+{
+    if nmemb < 2 || size == 0 {
+        return;
+    }
+
+    // A comparison function that does not implement a total order can make the sort panic, which
+    // must not unwind into C. The sort guarantees that all elements remain in `base` in that case.
+    let _ = panic::catch_unwind(AssertUnwindSafe(|| {
+        // SAFETY: Upheld by the caller.
+        unsafe { sort_raw_impl(base, nmemb, size, cmp) }
+    }));
+}
+
+/// See [`sort_raw`].
+unsafe fn sort_raw_impl<F>(base: *mut u8, nmemb: usize, size: usize, cmp: &mut F)
+where
+    F: FnMut(*const c_void, *const c_void) -> c_int,
+{
+    // Elements that are stored in a properly aligned array with the size of an integer type, can
+    // be sorted directly. The comparison function may rely on the alignment of the element type,
+    // which is why only the alignment that is actually present in the input is assumed. Elements
+    // may be passed to `cmp` from temporary copies, which is allowed for `qsort`.
+    macro_rules! sort_direct {
+        ($($t:ty),*) => {
+            $(
+                if size == mem::size_of::<$t>() && base.align_offset(mem::align_of::<$t>()) == 0 {
+                    // SAFETY: `base` is aligned and valid for `nmemb` elements of this size.
+                    // `Elem` permits any content and is never interpreted beyond passing its
+                    // address to `cmp`.
+                    let v = unsafe { slice::from_raw_parts_mut(base as *mut Elem<$t>, nmemb) };
+                    ipnsort::sort_by(v, |a, b| {
+                        let a_ptr = a as *const Elem<$t> as *const c_void;
+                        let b_ptr = b as *const Elem<$t> as *const c_void;
+                        ordering_from_c(cmp(a_ptr, b_ptr))
+                    });
+                    return;
+                }
+            )*
+        };
+    }
+
+    sort_direct!(u8, u16, u32, u64, u128);
+
+    // SAFETY: Upheld by the caller.
+    unsafe { sort_indirect(base, nmemb, size, cmp) };
+}
+
+/// Sorts element indices with the elements left in place, and then moves every element to its
+/// final position. Used for element sizes and alignments that have no matching Rust type.
+///
+/// SAFETY: See [`sort_raw`].
+unsafe fn sort_indirect<F>(base: *mut u8, nmemb: usize, size: usize, cmp: &mut F)
+where
+    F: FnMut(*const c_void, *const c_void) -> c_int,
+{
+    let elem_ptr = |i: usize| -> *mut u8 {
+        // SAFETY: Only called with indices in `0..nmemb`.
+        unsafe { base.add(i * size) }
+    };
+
+    let mut perm: Vec<usize> = (0..nmemb).collect();
+    ipnsort::sort_by(&mut perm, |&a, &b| {
+        ordering_from_c(cmp(
+            elem_ptr(a) as *const c_void,
+            elem_ptr(b) as *const c_void,
+        ))
+    });
+
+    // Apply the permutation, where `perm[i]` is the index of the element that belongs at `i`,
+    // one cycle at a time. Placed elements are marked by setting `perm[i] = i`. No user code runs
+    // from here on.
+    let mut tmp = vec![0u8; size];
+
+    // SAFETY: `perm` is a permutation of `0..nmemb`, so all element pointers are in-bounds. `tmp`
+    // holds `size` bytes and does not alias `base`.
+    unsafe {
+        for leader in 0..nmemb {
+            if perm[leader] == leader {
+                continue;
+            }
+
+            ptr::copy_nonoverlapping(elem_ptr(leader), tmp.as_mut_ptr(), size);
+            let mut pos = leader;
+
+            loop {
+                let src = perm[pos];
+                perm[pos] = pos;
+
+                if src == leader {
+                    ptr::copy_nonoverlapping(tmp.as_ptr(), elem_ptr(pos), size);
+                    break;
+                }
+
+                ptr::copy_nonoverlapping(elem_ptr(src), elem_ptr(pos), size);
+                pos = src;
+            }
+        }
+    }
+}
+
+#[inline(always)]
+fn ordering_from_c(val: c_int) -> Ordering {
+    val.cmp(&0)
+}
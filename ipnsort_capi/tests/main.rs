@@ -0,0 +1,276 @@
+use std::cmp::Ordering;
+use std::ffi::{c_int, c_void};
+
+use ipnsort_capi::{ipnsort_qsort, ipnsort_qsort_r, ipnsort_sort_i32, ipnsort_sort_u64};
+use sort_test_tools::patterns;
+
+#[cfg(not(miri))]
+const TEST_LENGTHS: &[usize] = &[0, 1, 2, 3, 7, 16, 17, 20, 33, 50, 100, 500, 1_000, 10_000];
+
+#[cfg(miri)]
+const TEST_LENGTHS: &[usize] = &[0, 1, 2, 3, 7, 16, 17, 20, 33, 50];
+
+fn test_patterns() -> Vec<fn(usize) -> Vec<i32>> {
+    vec![
+        patterns::random,
+        |len| patterns::random_uniform(len, 0..16),
+        |len| patterns::random_sorted(len, 95.0),
+        patterns::ascending,
+        patterns::descending,
+        patterns::pipe_organ,
+    ]
+}
+
+unsafe extern "C" fn cmp_i32(a: *const c_void, b: *const c_void) -> c_int {
+    unsafe { (*(a as *const i32)).cmp(&*(b as *const i32)) as c_int }
+}
+
+/// Compares `i32` values, in descending order if the context points to `true`, and counts the
+/// number of calls.
+unsafe extern "C" fn cmp_i32_r(a: *const c_void, b: *const c_void, arg: *mut c_void) -> c_int {
+    unsafe {
+        let ctx = &mut *(arg as *mut (bool, usize));
+        ctx.1 += 1;
+
+        let ord = (*(a as *const i32)).cmp(&*(b as *const i32));
+        if ctx.0 {
+            ord.reverse() as c_int
+        } else {
+            ord as c_int
+        }
+    }
+}
+
+#[test]
+fn qsort_i32() {
+    for pattern_fn in test_patterns() {
+        for len in TEST_LENGTHS {
+            let mut v = pattern_fn(*len);
+            let mut expected = v.clone();
+            expected.sort();
+
+            unsafe {
+                ipnsort_qsort(v.as_mut_ptr() as *mut c_void, v.len(), 4, cmp_i32);
+            }
+
+            assert_eq!(v, expected);
+        }
+    }
+}
+
+#[test]
+fn qsort_r_context() {
+    for len in TEST_LENGTHS {
+        let mut v = patterns::random(*len);
+        let mut expected = v.clone();
+        expected.sort_by(|a, b| b.cmp(a));
+
+        let mut ctx = (true, 0usize);
+        unsafe {
+            ipnsort_qsort_r(
+                v.as_mut_ptr() as *mut c_void,
+                v.len(),
+                4,
+                cmp_i32_r,
+                &mut ctx as *mut (bool, usize) as *mut c_void,
+            );
+        }
+
+        assert_eq!(v, expected);
+        if *len >= 2 {
+            assert!(ctx.1 > 0);
+        }
+    }
+}
+
+#[test]
+fn typed() {
+    for pattern_fn in test_patterns() {
+        for len in TEST_LENGTHS {
+            let mut v = pattern_fn(*len);
+            let mut expected = v.clone();
+            expected.sort();
+
+            unsafe { ipnsort_sort_i32(v.as_mut_ptr(), v.len()) };
+            assert_eq!(v, expected);
+
+            let mut v: Vec<u64> = pattern_fn(*len).iter().map(|x| *x as u64).collect();
+            let mut expected = v.clone();
+            expected.sort();
+
+            unsafe { ipnsort_sort_u64(v.as_mut_ptr(), v.len()) };
+            assert_eq!(v, expected);
+        }
+    }
+}
+
+#[test]
+fn empty() {
+    unsafe {
+        ipnsort_qsort(std::ptr::null_mut(), 0, 4, cmp_i32);
+        ipnsort_sort_i32(std::ptr::null_mut(), 0);
+        ipnsort_sort_u64(std::ptr::null_mut(), 0);
+    }
+}
+
+/// Struct elements, sizes without a matching integer type use the indirect path.
+#[repr(C)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+struct Odd<const N: usize> {
+    key: i32,
+    payload: [u8; N],
+}
+
+unsafe extern "C" fn cmp_odd<const N: usize>(a: *const c_void, b: *const c_void) -> c_int {
+    unsafe {
+        (*(a as *const Odd<N>))
+            .key
+            .cmp(&(*(b as *const Odd<N>)).key) as c_int
+    }
+}
+
+fn check_odd_size<const N: usize>() {
+    for pattern_fn in test_patterns() {
+        for len in TEST_LENGTHS {
+            let v_orig: Vec<Odd<N>> = pattern_fn(*len)
+                .into_iter()
+                .map(|key| Odd {
+                    key,
+                    payload: [key as u8; N],
+                })
+                .collect();
+
+            let mut v = v_orig.clone();
+            unsafe {
+                ipnsort_qsort(
+                    v.as_mut_ptr() as *mut c_void,
+                    v.len(),
+                    std::mem::size_of::<Odd<N>>(),
+                    cmp_odd::<N>,
+                );
+            }
+
+            let mut expected = v_orig;
+            expected.sort();
+            assert_eq!(v, expected);
+        }
+    }
+}
+
+#[test]
+fn odd_sizes() {
+    // Sizes 8, 12, 24 and 36 bytes, with 4 byte alignment. Only the first maps to an integer type.
+    check_odd_size::<4>();
+    check_odd_size::<8>();
+    check_odd_size::<20>();
+    check_odd_size::<32>();
+}
+
+#[test]
+fn misaligned() {
+    // `u32` sized elements in a buffer that is not aligned for `u32`.
+    unsafe extern "C" fn cmp_unaligned_u32(a: *const c_void, b: *const c_void) -> c_int {
+        unsafe {
+            let a = (a as *const u32).read_unaligned();
+            let b = (b as *const u32).read_unaligned();
+            a.cmp(&b) as c_int
+        }
+    }
+
+    for len in TEST_LENGTHS {
+        let values: Vec<u32> = patterns::random(*len).iter().map(|x| *x as u32).collect();
+
+        // A `u32` buffer is 4 byte aligned, so offsetting it by one byte guarantees misalignment.
+        let mut buf = vec![0u32; len + 1];
+        let buf_bytes =
+            unsafe { std::slice::from_raw_parts_mut(buf.as_mut_ptr() as *mut u8, (len + 1) * 4) };
+        let data = &mut buf_bytes[1..1 + len * 4];
+        for (i, val) in values.iter().enumerate() {
+            data[i * 4..(i + 1) * 4].copy_from_slice(&val.to_ne_bytes());
+        }
+
+        unsafe {
+            ipnsort_qsort(data.as_mut_ptr() as *mut c_void, *len, 4, cmp_unaligned_u32);
+        }
+
+        let result: Vec<u32> = data
+            .chunks_exact(4)
+            .map(|c| u32::from_ne_bytes(c.try_into().unwrap()))
+            .collect();
+
+        let mut expected = values;
+        expected.sort();
+        assert_eq!(result, expected);
+    }
+}
+
+#[test]
+fn violate_ord_retain_orig_set() {
+    unsafe extern "C" fn cmp_random(
+        _a: *const c_void,
+        _b: *const c_void,
+        arg: *mut c_void,
+    ) -> c_int {
+        unsafe {
+            let (orderings, idx) = &mut *(arg as *mut (Vec<i32>, usize));
+            *idx = (*idx + 1) % orderings.len();
+            [Ordering::Less, Ordering::Equal, Ordering::Greater][orderings[*idx] as usize] as c_int
+        }
+    }
+
+    for len in TEST_LENGTHS {
+        let v_orig = patterns::random(*len);
+
+        let mut v = v_orig.clone();
+        let mut ctx = (patterns::random_uniform(10_000, 0..3), 0usize);
+        unsafe {
+            ipnsort_qsort_r(
+                v.as_mut_ptr() as *mut c_void,
+                v.len(),
+                4,
+                cmp_random,
+                &mut ctx as *mut (Vec<i32>, usize) as *mut c_void,
+            );
+        }
+
+        let mut v_sorted = v;
+        v_sorted.sort();
+        let mut expected = v_orig;
+        expected.sort();
+        assert_eq!(v_sorted, expected);
+
+        // The indirect path.
+        let mut v: Vec<Odd<8>> = expected
+            .iter()
+            .map(|&key| Odd {
+                key,
+                payload: [key as u8; 8],
+            })
+            .collect();
+        let v_orig = v.clone();
+        unsafe {
+            ipnsort_qsort_r(
+                v.as_mut_ptr() as *mut c_void,
+                v.len(),
+                std::mem::size_of::<Odd<8>>(),
+                cmp_random,
+                &mut ctx as *mut (Vec<i32>, usize) as *mut c_void,
+            );
+        }
+
+        v.sort();
+        assert_eq!(v, v_orig);
+    }
+}
+
+#[test]
+fn header_up_to_date() {
+    let generated = include_str!(concat!(env!("OUT_DIR"), "/ipnsort.h"));
+    let checked_in = include_str!("../include/ipnsort.h");
+
+    // Copy `ipnsort.h` from the build directory into `include/` after changing the C ABI.
+    assert!(
+        generated == checked_in,
+        "include/ipnsort.h is out of date, generated header:\n{generated}"
+    );
+}
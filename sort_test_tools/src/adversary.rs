@@ -0,0 +1,94 @@
+//! McIlroy's adaptive adversary from "A Killer Adversary for Quicksort" (1999).
+//!
+//! Instead of sorting a fixed input, the adversary decides the values of the elements while the
+//! sort runs. All elements start out as "gas", which compares greater than every decided value.
+//! When two gas elements are compared, one of them is frozen to the next smallest value, preferring
+//! to keep the last known pivot candidate as gas. Quicksort implementations that pick their pivot
+//! from a few compared elements are driven into quadratic behavior this way, unless they guard
+//! against it.
+
+use std::cmp::Ordering;
+
+use crate::Sort;
+
+pub struct Adversary {
+    values: Vec<usize>,
+    gas: usize,
+    num_solid: usize,
+    candidate: usize,
+    comparisons: usize,
+}
+
+impl Adversary {
+    /// Creates an adversary for the elements `0..len`, all of them gas except for the first two.
+    /// Those are decided up front in descending order. Otherwise the first comparisons would decide
+    /// the input to be ascending, which sorts that detect existing runs handle in linear time.
+    pub fn new(len: usize) -> Self {
+        let mut adversary = Self {
+            values: vec![len; len],
+            gas: len,
+            num_solid: 0,
+            candidate: 0,
+            comparisons: 0,
+        };
+
+        if len >= 2 {
+            adversary.freeze(1);
+            adversary.freeze(0);
+        }
+
+        adversary
+    }
+
+    /// Compares the elements `a` and `b`, which are indices into `0..len`.
+    pub fn compare(&mut self, a: usize, b: usize) -> Ordering {
+        self.comparisons += 1;
+
+        if self.values[a] == self.gas && self.values[b] == self.gas {
+            if a == self.candidate {
+                self.freeze(a);
+            } else {
+                self.freeze(b);
+            }
+        }
+
+        if self.values[a] == self.gas {
+            self.candidate = a;
+        } else if self.values[b] == self.gas {
+            self.candidate = b;
+        }
+
+        self.values[a].cmp(&self.values[b])
+    }
+
+    /// Number of comparisons answered so far.
+    pub fn comparisons(&self) -> usize {
+        self.comparisons
+    }
+
+    /// Returns the input the adversary committed to, which makes a deterministic sort perform the
+    /// same comparisons again. Elements that are still gas are all equal and greater than the rest.
+    pub fn into_input(self) -> Vec<i32> {
+        self.values.into_iter().map(|val| val as i32).collect()
+    }
+
+    fn freeze(&mut self, x: usize) {
+        self.values[x] = self.num_solid;
+        self.num_solid += 1;
+    }
+}
+
+/// Sorts `len` elements with `S::sort_by`, while the adversary answers the comparisons. Checks
+/// that the result is sorted according to the answers.
+pub fn antiqsort<S: Sort>(len: usize) -> Adversary {
+    let mut adversary = Adversary::new(len);
+    let mut v: Vec<usize> = (0..len).collect();
+
+    <S as Sort>::sort_by(&mut v, |a, b| adversary.compare(*a, *b));
+
+    assert!(v
+        .windows(2)
+        .all(|w| adversary.values[w[0]] <= adversary.values[w[1]]));
+
+    adversary
+}
@@ -0,0 +1,61 @@
+//! A global allocator that counts the allocations of every thread, so that the tests can check
+//! [`crate::Sort::allocates`]. Test binaries opt in with:
+//!
+//! ```ignore
+//! #[global_allocator]
+//! static ALLOC: sort_test_tools::counting_alloc::CountingAlloc =
+//!     sort_test_tools::counting_alloc::CountingAlloc;
+//! ```
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::Cell;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// Forwards to [`System`] and counts every `alloc`, `alloc_zeroed` and `realloc` call.
+pub struct CountingAlloc;
+
+// Set by the first allocation, which happens long before any test runs.
+static INSTALLED: AtomicBool = AtomicBool::new(false);
+
+thread_local! {
+    static ALLOCATIONS: Cell<usize> = const { Cell::new(0) };
+}
+
+fn count() {
+    INSTALLED.store(true, Ordering::Relaxed);
+    // The thread local is inaccessible while the thread is torn down.
+    let _ = ALLOCATIONS.try_with(|count| count.set(count.get() + 1));
+}
+
+unsafe impl GlobalAlloc for CountingAlloc {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        count();
+        // SAFETY: Forwarded with the caller's guarantees.
+        unsafe { System.alloc(layout) }
+    }
+
+    unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+        count();
+        // SAFETY: Forwarded with the caller's guarantees.
+        unsafe { System.alloc_zeroed(layout) }
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        count();
+        // SAFETY: Forwarded with the caller's guarantees.
+        unsafe { System.realloc(ptr, layout, new_size) }
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        // SAFETY: Forwarded with the caller's guarantees.
+        unsafe { System.dealloc(ptr, layout) }
+    }
+}
+
+/// Returns the number of allocations the calling thread made so far, or `None` if
+/// [`CountingAlloc`] isn't the global allocator.
+pub fn allocations() -> Option<usize> {
+    INSTALLED
+        .load(Ordering::Relaxed)
+        .then(|| ALLOCATIONS.with(Cell::get))
+}
@@ -40,6 +40,9 @@ impl FFIString {
         }
     }
 
+    /// # Safety
+    ///
+    /// The value must not be in a moved from state, e.g. left behind by a C++ sort implementation.
     pub unsafe fn as_str_unchecked(&self) -> &str {
         // SAFETY: The value is valid by construction so from a Rust interface perspective it is a
         // safe function. However it's possible that C++ sort implementations leave the value in a
@@ -10,8 +10,114 @@ pub trait Sort {
     fn sort_by<T, F>(v: &mut [T], compare: F)
     where
         F: FnMut(&T, &T) -> std::cmp::Ordering;
+
+    /// Defaults to calling `sort_by` with the key extraction function applied to both sides of
+    /// every comparison.
+    fn sort_by_key<T, K, F>(v: &mut [T], mut f: F)
+    where
+        K: Ord,
+        F: FnMut(&T) -> K,
+    {
+        Self::sort_by(v, |a, b| f(a).cmp(&f(b)));
+    }
+
+    /// Defaults to calling `sort` on `(key, index)` pairs and applying the resulting permutation
+    /// in-place, same as `slice::sort_by_cached_key`. The key extraction function is called
+    /// exactly once per element, and the indices make the result stable regardless of the
+    /// stability of the implementation.
+    fn sort_by_cached_key<T, K, F>(v: &mut [T], f: F)
+    where
+        K: Ord,
+        F: FnMut(&T) -> K,
+    {
+        let len = v.len();
+        if len < 2 {
+            return;
+        }
+
+        let mut indices: Vec<(K, usize)> =
+            v.iter().map(f).enumerate().map(|(i, k)| (k, i)).collect();
+
+        Self::sort(&mut indices);
+
+        for i in 0..len {
+            let mut index = indices[i].1;
+            while index < i {
+                index = indices[index].1;
+            }
+            indices[i].1 = index;
+            v.swap(i, index);
+        }
+    }
+
+    /// Whether elements that compare equal retain their original order. Defaults to the naming
+    /// convention of the implementations, which mark unstable sorts with `unstable` in the name.
+    fn is_stable() -> bool {
+        !Self::name().contains("unstable")
+    }
+
+    /// Whether `sort_by`, and the functions that default to it, are implemented.
+    fn supports_sort_by() -> bool {
+        true
+    }
+
+    /// Whether `sort` and `sort_by` are implemented for `T`. Implementations that specialize on a
+    /// set of types panic for all other types.
+    fn supports_type<T>() -> bool {
+        true
+    }
+
+    /// Whether `sort` and `sort_by` may allocate heap memory for `T`. Checked by the tests in
+    /// binaries that install [`crate::counting_alloc::CountingAlloc`].
+    fn allocates<T>() -> bool {
+        true
+    }
+
+    /// Whether all elements remain in the slice exactly once, if the comparison function panics.
+    fn is_panic_safe() -> bool {
+        true
+    }
+
+    /// Whether a comparison function that mutates elements through its shared references, without
+    /// an `UnsafeCell`, sees every mutation reflected in the result. That is undefined behavior in
+    /// Rust, but valid for `mutable` members in C and C++.
+    fn observes_mutation_without_cell() -> bool {
+        false
+    }
+}
+
+/// Partitions `arr` so that all elements less than `pivot` come first, and returns their count.
+pub trait Partition {
+    fn name() -> String;
+
+    fn partition<T: Ord>(arr: &mut [T], pivot: &T) -> usize;
+
+    fn partition_by<T, F: FnMut(&T, &T) -> bool>(
+        arr: &mut [T],
+        pivot: &T,
+        is_less: &mut F,
+    ) -> usize;
+}
+
+/// Returns the number of elements of the sorted `arr` that are less than `val`.
+pub trait PartitionPoint {
+    fn name() -> String;
+
+    fn partition_point<T>(arr: &[T], val: &T) -> usize
+    where
+        T: Ord;
+
+    fn partition_point_by<T, F>(arr: &[T], val: &T, compare: F) -> usize
+    where
+        F: FnMut(&T, &T) -> core::cmp::Ordering;
 }
 
+pub mod adversary;
+pub mod counting_alloc;
 pub mod ffi_types;
+pub mod minimize;
+pub mod partition_point_tests;
+pub mod partition_tests;
 pub mod patterns;
+pub mod sorting_network;
 pub mod tests;
@@ -0,0 +1,177 @@
+//! Shrinking of failing test inputs, to turn a failure on a large pattern into a test case that is
+//! small enough to debug.
+//!
+//! The shared tests call this when they fail, and print the result as a ready-to-paste `#[test]`.
+
+use std::any;
+use std::cell::Cell;
+use std::cmp::Ordering;
+use std::fmt::{Debug, Write};
+use std::panic;
+use std::sync::Once;
+use std::thread;
+
+/// Upper bound for the calls to the `fails` function of [`minimize`]. Every call sorts a candidate
+/// input, which is expensive for the largest test sizes in debug builds.
+const MAX_ATTEMPTS: usize = 20_000;
+
+/// Repeatedly shrinks `input` while `fails` returns `true` for the shrunk input, and returns the
+/// smallest failing input that was found. `fails` must return `true` for `input` itself.
+///
+/// Each round removes chunks of elements, from half the length down to single elements, then
+/// replaces the values by their rank among the distinct values, and finally lowers every value
+/// towards zero. The rounds are repeated until none of them makes progress. Panics inside `fails`
+/// are not caught, but their messages are suppressed if they happen on the calling thread.
+pub fn minimize(input: &[i32], mut fails: impl FnMut(&[i32]) -> bool) -> Vec<i32> {
+    let mut attempts = 0;
+    let mut try_candidate = |candidate: &[i32]| {
+        attempts += 1;
+        attempts <= MAX_ATTEMPTS && fails(candidate)
+    };
+
+    silence_panics(|| {
+        let mut v = input.to_vec();
+
+        loop {
+            let v_before = v.clone();
+
+            let mut chunk_len = v.len() / 2;
+            while chunk_len > 0 {
+                let mut start = 0;
+                while start < v.len() {
+                    let end = (start + chunk_len).min(v.len());
+                    let candidate: Vec<i32> = [&v[..start], &v[end..]].concat();
+                    if try_candidate(&candidate) {
+                        v = candidate;
+                    } else {
+                        start = end;
+                    }
+                }
+                chunk_len /= 2;
+            }
+
+            let ranks = ranked(&v);
+            if ranks != v && try_candidate(&ranks) {
+                v = ranks;
+            }
+
+            for i in 0..v.len() {
+                while v[i] != 0 {
+                    let mut candidate = v.clone();
+                    let lowered = [0, v[i] / 2, v[i] - v[i].signum()]
+                        .into_iter()
+                        .find(|&val| {
+                            candidate[i] = val;
+                            try_candidate(&candidate)
+                        });
+
+                    match lowered {
+                        Some(val) => v[i] = val,
+                        None => break,
+                    }
+                }
+            }
+
+            if v == v_before {
+                return v;
+            }
+        }
+    })
+}
+
+/// Formats `values` as Rust vector literal of type `T`, or returns `None` if the [`Debug`] output
+/// of `T` is not a valid Rust literal.
+pub fn vec_literal<T: Debug>(values: &[T]) -> Option<String> {
+    const LITERAL_TYPES: &[&str] = &[
+        "i8", "i16", "i32", "i64", "i128", "isize", "u8", "u16", "u32", "u64", "u128", "usize",
+    ];
+
+    if !LITERAL_TYPES.contains(&any::type_name::<T>()) {
+        return None;
+    }
+
+    let elements: Vec<String> = values.iter().map(|val| format!("{val:?}")).collect();
+    Some(format!("vec![{}]", elements.join(", ")))
+}
+
+/// Formats `orderings` as byte string literal, with `L`, `E` and `G` for less, equal and greater.
+pub fn orderings_literal(orderings: &[Ordering]) -> String {
+    let chars: String = orderings
+        .iter()
+        .map(|ord| match ord {
+            Ordering::Less => 'L',
+            Ordering::Equal => 'E',
+            Ordering::Greater => 'G',
+        })
+        .collect();
+
+    format!("b\"{chars}\"")
+}
+
+/// Formats a `#[test]` function with `body`, named after the currently running test.
+pub fn format_test(body: &str) -> String {
+    let thread = thread::current();
+    let test_name = thread
+        .name()
+        .and_then(|name| name.rsplit("::").next())
+        .filter(|name| *name != "main")
+        .unwrap_or("sort");
+
+    let mut test = String::new();
+    writeln!(test, "#[test]").unwrap();
+    writeln!(test, "fn {test_name}_minimized() {{").unwrap();
+    for line in body.lines() {
+        if line.is_empty() {
+            writeln!(test).unwrap();
+        } else {
+            writeln!(test, "    {line}").unwrap();
+        }
+    }
+    writeln!(test, "}}").unwrap();
+
+    test
+}
+
+/// Returns the last path segment of the type name of `S`, the way a test in the same module refers
+/// to it.
+pub fn short_type_name<S>() -> &'static str {
+    let name = any::type_name::<S>();
+    name.rsplit("::").next().unwrap_or(name)
+}
+
+/// Replaces every value with its rank among the distinct values, which keeps the outcome of all
+/// comparisons.
+fn ranked(v: &[i32]) -> Vec<i32> {
+    let mut distinct = v.to_vec();
+    distinct.sort_unstable();
+    distinct.dedup();
+
+    v.iter()
+        .map(|val| distinct.binary_search(val).unwrap() as i32)
+        .collect()
+}
+
+thread_local! {
+    static PANICS_SILENCED: Cell<bool> = const { Cell::new(false) };
+}
+
+/// Runs `f` without printing the messages of panics inside of it. The panic hook is global, so it
+/// is replaced once by a hook that only drops the messages of threads inside `silence_panics`, and
+/// passes all others on to the previous hook.
+fn silence_panics<R>(f: impl FnOnce() -> R) -> R {
+    static INSTALL_HOOK: Once = Once::new();
+    INSTALL_HOOK.call_once(|| {
+        let prev_hook = panic::take_hook();
+        panic::set_hook(Box::new(move |info| {
+            if !PANICS_SILENCED.try_with(Cell::get).unwrap_or(false) {
+                prev_hook(info);
+            }
+        }));
+    });
+
+    let was_silenced = PANICS_SILENCED.replace(true);
+    let result = panic::catch_unwind(panic::AssertUnwindSafe(f));
+    PANICS_SILENCED.set(was_silenced);
+
+    result.unwrap_or_else(|err| panic::resume_unwind(err))
+}
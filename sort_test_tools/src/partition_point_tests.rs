@@ -0,0 +1,289 @@
+//! Tests for [`PartitionPoint`] implementations, see [`instantiate_partition_point_tests`].
+
+use std::fmt::Debug;
+
+use crate::ffi_types::FFIString;
+use crate::patterns;
+use crate::tests::{ffi_string_from_i32, i32_from_i32, write_info_with_name_to_stdout};
+use crate::PartitionPoint;
+
+// Every length is tested, so that every shape of the search is covered at least once.
+const MAX_TEST_LEN: usize = if cfg!(miri) { 40 } else { 1_000 };
+
+fn write_info_to_stdout<P: PartitionPoint>() -> u64 {
+    write_info_with_name_to_stdout(<P as PartitionPoint>::name)
+}
+
+/// Returns the values every input is searched for. These are the smallest and largest value, the
+/// values next to them that are not part of the input, the first value that fits into a gap
+/// between two elements, and elements in the middle and at a random position. Runs of equal
+/// elements are covered by patterns with many duplicates.
+fn query_values(sorted: &[i32]) -> Vec<i32> {
+    let len = sorted.len();
+    if len == 0 {
+        return vec![0];
+    }
+
+    let random_idx = patterns::random_uniform(1, 0..len as i32)[0] as usize;
+    let mut queries = vec![
+        sorted[0],
+        sorted[0].saturating_sub(1),
+        sorted[len - 1],
+        sorted[len - 1].saturating_add(1),
+        sorted[len / 2],
+        sorted[random_idx],
+    ];
+
+    if let Some(w) = sorted.windows(2).find(|w| w[0].saturating_add(1) < w[1]) {
+        queries.push(w[0] + 1);
+    }
+
+    queries
+}
+
+/// Calls `test_fn` with the sorted pattern and the query values for every length up to
+/// [`MAX_TEST_LEN`].
+fn for_each_len(pattern_fn: fn(usize) -> Vec<i32>, mut test_fn: impl FnMut(&[i32], &[i32])) {
+    for len in 0..=MAX_TEST_LEN {
+        // Not every pattern supports empty inputs.
+        let mut sorted = if len == 0 {
+            Vec::new()
+        } else {
+            pattern_fn(len)
+        };
+        sorted.sort_unstable();
+
+        test_fn(&sorted, &query_values(&sorted));
+    }
+}
+
+/// Generates $base_name_pattern_name_impl functions calling the test_fn for every pattern.
+macro_rules! gen_partition_point_test_fns {
+    (
+        $base_name:ident,
+        $test_fn:expr,
+        [$(($pattern_name:ident, $pattern_fn:expr)),* $(,)?] $(,)?
+    ) => {
+        $(fn ${concat($base_name, _, $pattern_name, _impl)}<P: PartitionPoint>() {
+            write_info_to_stdout::<P>();
+
+            $test_fn($pattern_fn);
+        })*
+    };
+}
+
+/// Generates $base_name_pattern_name_impl functions calling the test_fn for every pattern, with
+/// the default set of patterns of the sort tests and all equal elements.
+macro_rules! gen_partition_point_test_fns_with_default_patterns {
+    ($base_name:ident, $test_fn:expr $(,)?) => {
+        gen_partition_point_test_fns!(
+            $base_name,
+            $test_fn,
+            [
+                (random, patterns::random),
+                (random_z1, |len| patterns::random_zipf(len, 1.0)),
+                (random_d2, |len| patterns::random_uniform(len, 0..2)),
+                (random_d20, |len| patterns::random_uniform(len, 0..16)),
+                (random_s95, |len| patterns::random_sorted(len, 95.0)),
+                (ascending, patterns::ascending),
+                (descending, patterns::descending),
+                (saw_mixed, |len| patterns::saw_mixed(
+                    len,
+                    ((len as f64).log2().round()) as usize
+                )),
+                (all_equal, patterns::all_equal),
+            ]
+        );
+    };
+}
+
+// --- TESTS ---
+
+fn basic_impl<P: PartitionPoint>() {
+    write_info_to_stdout::<P>();
+
+    assert_eq!(P::partition_point::<i32>(&[], &0), 0);
+    assert_eq!(P::partition_point(&[3], &2), 0);
+    assert_eq!(P::partition_point(&[3], &3), 0);
+    assert_eq!(P::partition_point(&[3], &4), 1);
+    assert_eq!(P::partition_point(&[1, 2, 2, 2, 5], &2), 1);
+    assert_eq!(P::partition_point(&[1, 2, 2, 2, 5], &3), 4);
+    assert_eq!(P::partition_point(&[i32::MIN, i32::MAX], &i32::MAX), 1);
+}
+
+fn correct<T: Ord + Debug, P: PartitionPoint>(
+    type_into_fn: fn(i32) -> T,
+    pattern_fn: fn(usize) -> Vec<i32>,
+) {
+    for_each_len(pattern_fn, |sorted, queries| {
+        let v: Vec<T> = sorted.iter().copied().map(type_into_fn).collect();
+
+        for &query in queries {
+            let expected = sorted.iter().take_while(|&&x| x < query).count();
+            let val = type_into_fn(query);
+
+            assert_eq!(P::partition_point(&v, &val), expected, "{v:?} {val:?}");
+            assert_eq!(
+                P::partition_point_by(&v, &val, |a, b| a.cmp(b)),
+                expected,
+                "{v:?} {val:?}"
+            );
+        }
+    });
+}
+
+gen_partition_point_test_fns_with_default_patterns!(correct_i32, |pattern_fn| {
+    correct::<i32, P>(i32_from_i32, pattern_fn)
+});
+
+gen_partition_point_test_fns_with_default_patterns!(correct_ffi_string, |pattern_fn| {
+    correct::<FFIString, P>(ffi_string_from_i32, pattern_fn)
+});
+
+fn by_reversed<P: PartitionPoint>(pattern_fn: fn(usize) -> Vec<i32>) {
+    // A reversed order makes sure the comparison function is actually used.
+    for_each_len(pattern_fn, |sorted, queries| {
+        let v: Vec<i32> = sorted.iter().rev().copied().collect();
+
+        for &query in queries {
+            let expected = v.iter().take_while(|&&x| x > query).count();
+
+            let result = P::partition_point_by(&v, &query, |a, b| b.cmp(a));
+            assert_eq!(result, expected, "{v:?} {query}");
+        }
+    });
+}
+
+gen_partition_point_test_fns_with_default_patterns!(by_reversed, by_reversed::<P>);
+
+fn by_key<P: PartitionPoint>(pattern_fn: fn(usize) -> Vec<i32>) {
+    // Comparing only a part of the value creates runs of elements that compare equal to the query,
+    // without being equal to it.
+    let key = |val: &i32| val.div_euclid(8);
+
+    for_each_len(pattern_fn, |sorted, queries| {
+        for &query in queries {
+            let expected = sorted.iter().take_while(|x| key(x) < key(&query)).count();
+
+            let result = P::partition_point_by(sorted, &query, |a, b| key(a).cmp(&key(b)));
+            assert_eq!(result, expected, "{sorted:?} {query}");
+        }
+    });
+}
+
+gen_partition_point_test_fns_with_default_patterns!(by_key, by_key::<P>);
+
+fn by_comparison_order<P: PartitionPoint>(pattern_fn: fn(usize) -> Vec<i32>) {
+    // The element is passed as first argument and the searched value as second.
+    for_each_len(pattern_fn, |sorted, queries| {
+        for &query in queries {
+            let expected = sorted.iter().take_while(|&&x| x < query).count();
+
+            let result = P::partition_point_by(sorted, &query, |elem, val| {
+                assert_eq!(*val, query);
+                elem.cmp(val)
+            });
+            assert_eq!(result, expected);
+        }
+    });
+}
+
+gen_partition_point_test_fns!(
+    by_comparison_order,
+    by_comparison_order::<P>,
+    [(random, patterns::random)]
+);
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! instantiate_partition_point_test_inner {
+    ($partition_point_impl:ty, miri_yes, $test_fn_name:ident) => {
+        #[test]
+        fn $test_fn_name() {
+            sort_test_tools::partition_point_tests::$test_fn_name::<$partition_point_impl>();
+        }
+    };
+    ($partition_point_impl:ty, miri_no, $test_fn_name:ident) => {
+        #[test]
+        #[cfg_attr(miri, ignore)]
+        fn $test_fn_name() {
+            sort_test_tools::partition_point_tests::$test_fn_name::<$partition_point_impl>();
+        }
+    };
+}
+
+// Using this construct allows us to get warnings for unused test functions.
+macro_rules! define_instantiate_partition_point_tests {
+    ($([$miri_use:ident, $test_fn_name:ident]),*,) => {
+        $(pub fn $test_fn_name<P: PartitionPoint>() {
+            ${concat($test_fn_name, _impl)}::<P>();
+        })*
+
+        #[doc(hidden)]
+        #[macro_export]
+        macro_rules! instantiate_partition_point_tests_gen {
+            ($partition_point_impl:ty) => {
+                $(
+                    sort_test_tools::instantiate_partition_point_test_inner!(
+                        $partition_point_impl,
+                        $miri_use,
+                        $test_fn_name
+                    );
+                )*
+            }
+        }
+    };
+}
+
+define_instantiate_partition_point_tests!(
+    [miri_yes, basic],
+    [miri_no, correct_i32_random],
+    [miri_yes, correct_i32_random_z1],
+    [miri_no, correct_i32_random_d2],
+    [miri_no, correct_i32_random_d20],
+    [miri_no, correct_i32_random_s95],
+    [miri_no, correct_i32_ascending],
+    [miri_no, correct_i32_descending],
+    [miri_no, correct_i32_saw_mixed],
+    [miri_no, correct_i32_all_equal],
+    [miri_no, correct_ffi_string_random],
+    [miri_yes, correct_ffi_string_random_z1],
+    [miri_no, correct_ffi_string_random_d2],
+    [miri_no, correct_ffi_string_random_d20],
+    [miri_no, correct_ffi_string_random_s95],
+    [miri_no, correct_ffi_string_ascending],
+    [miri_no, correct_ffi_string_descending],
+    [miri_no, correct_ffi_string_saw_mixed],
+    [miri_no, correct_ffi_string_all_equal],
+    [miri_no, by_reversed_random],
+    [miri_yes, by_reversed_random_z1],
+    [miri_no, by_reversed_random_d2],
+    [miri_no, by_reversed_random_d20],
+    [miri_no, by_reversed_random_s95],
+    [miri_no, by_reversed_ascending],
+    [miri_no, by_reversed_descending],
+    [miri_no, by_reversed_saw_mixed],
+    [miri_no, by_reversed_all_equal],
+    [miri_no, by_key_random],
+    [miri_yes, by_key_random_z1],
+    [miri_no, by_key_random_d2],
+    [miri_no, by_key_random_d20],
+    [miri_no, by_key_random_s95],
+    [miri_no, by_key_ascending],
+    [miri_no, by_key_descending],
+    [miri_no, by_key_saw_mixed],
+    [miri_no, by_key_all_equal],
+    [miri_yes, by_comparison_order_random],
+);
+
+/// Instantiates the partition point tests for `$partition_point_impl`, which has to implement
+/// [`PartitionPoint`].
+///
+/// Every test searches sorted inputs of all lengths up to 1000 and compares the result with a
+/// linear scan.
+#[macro_export]
+macro_rules! instantiate_partition_point_tests {
+    ($partition_point_impl:ty) => {
+        sort_test_tools::instantiate_partition_point_tests_gen!($partition_point_impl);
+    };
+}
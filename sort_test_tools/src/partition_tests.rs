@@ -0,0 +1,482 @@
+//! Tests for [`Partition`] implementations, see [`instantiate_partition_tests`].
+
+use std::cell::Cell;
+use std::cmp::Ordering;
+use std::fmt::Debug;
+use std::panic::{self, AssertUnwindSafe};
+
+use crate::ffi_types::FFIString;
+use crate::patterns;
+use crate::tests::{
+    cell_i32_from_i32, extend_i32_to_u64, ffi_string_from_i32, i32_from_cell_i32,
+    i32_from_ffi_string, i32_from_i32, i32_from_i32_ref, invalid_ord_comp_functions,
+    reverse_shift_i32_to_u32, should_test_for_strong_exception_safety,
+    write_info_with_name_to_stdout, CompCount,
+};
+use crate::Partition;
+
+// Partitioning is linear in the input length, the lengths are chosen to cover the edges of common
+// block and unroll sizes.
+#[cfg(miri)]
+const TEST_LENGTHS: &[usize] = &[1, 2, 3, 7, 15, 16, 17, 33, 64, 100];
+
+#[cfg(not(miri))]
+const TEST_LENGTHS: &[usize] = &[
+    1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 15, 16, 17, 20, 24, 31, 32, 33, 50, 63, 64, 65, 100, 127, 128,
+    129, 200, 255, 256, 257, 500, 1_000, 2_048, 5_000, 10_000,
+];
+
+fn write_info_to_stdout<P: Partition>() -> u64 {
+    write_info_with_name_to_stdout(<P as Partition>::name)
+}
+
+fn u64_from_i32(val: i32) -> u64 {
+    extend_i32_to_u64(val)
+}
+
+fn i32_from_u64(val: &u64) -> i32 {
+    reverse_shift_i32_to_u32((val / i32::MAX as u64) as u32)
+}
+
+/// Returns the pivots every input is partitioned with. The smallest and the largest value put all
+/// elements on one side, and a value larger than all of them makes every element less.
+fn pivot_values(pattern: &[i32]) -> Vec<i32> {
+    let mut sorted = pattern.to_vec();
+    sorted.sort_unstable();
+
+    let len = sorted.len();
+    let random_idx = patterns::random_uniform(1, 0..len as i32)[0] as usize;
+
+    vec![
+        sorted[0],
+        sorted[len / 2],
+        sorted[len - 1],
+        pattern[random_idx],
+        sorted[len - 1].saturating_add(1),
+    ]
+}
+
+/// Generates $base_name_pattern_name_impl functions calling the test_fn for all test_len.
+macro_rules! gen_partition_test_fns {
+    (
+        $base_name:ident,
+        $test_fn:expr,
+        [$(($pattern_name:ident, $pattern_fn:expr)),* $(,)?] $(,)?
+    ) => {
+        $(fn ${concat($base_name, _, $pattern_name, _impl)}<P: Partition>() {
+            write_info_to_stdout::<P>();
+
+            for test_len in TEST_LENGTHS {
+                $test_fn(*test_len, $pattern_fn);
+            }
+        })*
+    };
+}
+
+/// Generates $base_name_pattern_name_impl functions calling the test_fn for all test_len, with the
+/// same default set of patterns as the sort tests.
+macro_rules! gen_partition_test_fns_with_default_patterns {
+    ($base_name:ident, $test_fn:expr $(,)?) => {
+        gen_partition_test_fns!(
+            $base_name,
+            $test_fn,
+            [
+                (random, patterns::random),
+                (random_z1, |len| patterns::random_zipf(len, 1.0)),
+                (random_d2, |len| patterns::random_uniform(len, 0..2)),
+                (random_d20, |len| patterns::random_uniform(len, 0..16)),
+                (random_s95, |len| patterns::random_sorted(len, 95.0)),
+                (ascending, patterns::ascending),
+                (descending, patterns::descending),
+                (saw_mixed, |len| patterns::saw_mixed(
+                    len,
+                    ((len as f64).log2().round()) as usize
+                )),
+            ]
+        );
+    };
+}
+
+/// Generates $base_name_type_pattern_name_impl functions calling the test_fn for all test_len, with
+/// the default set of patterns, for each of the given types.
+macro_rules! gen_partition_test_fns_with_types {
+    (
+        $base_name:ident,
+        $test_fn:ident,
+        [$(($type_name:ident, $type:ty, $type_into_fn:expr, $type_from_fn:expr)),* $(,)?] $(,)?
+    ) => {
+        $(gen_partition_test_fns_with_default_patterns!(
+            ${concat($base_name, _, $type_name)},
+            |len, pattern_fn| $test_fn::<$type, P>(len, $type_into_fn, $type_from_fn, pattern_fn),
+        );)*
+    };
+}
+
+// --- TESTS ---
+
+fn basic_impl<P: Partition>() {
+    write_info_to_stdout::<P>();
+
+    // Empty inputs are not tested, many implementations rely on the caller to handle them.
+    assert_eq!(P::partition(&mut [3], &3), 0);
+    assert_eq!(P::partition(&mut [3], &4), 1);
+
+    let mut v = [5, 1, 4, 2, 3];
+    assert_eq!(P::partition(&mut v, &3), 2);
+    assert!(v[..2].iter().all(|&x| x < 3) && v[2..].iter().all(|&x| x >= 3));
+}
+
+fn correct<T: Ord + Debug, P: Partition>(
+    len: usize,
+    type_into_fn: impl Fn(i32) -> T,
+    type_from_fn: impl Fn(&T) -> i32,
+    pattern_fn: fn(usize) -> Vec<i32>,
+) {
+    let pattern = pattern_fn(len);
+
+    let mut pattern_sorted = pattern.clone();
+    pattern_sorted.sort_unstable();
+
+    for pivot_val in pivot_values(&pattern) {
+        let mut v: Vec<T> = pattern.iter().copied().map(&type_into_fn).collect();
+        let pivot = type_into_fn(pivot_val);
+
+        let num_lt = P::partition(&mut v, &pivot);
+
+        assert_eq!(num_lt, pattern_sorted.partition_point(|&x| x < pivot_val));
+        assert!(v[..num_lt].iter().all(|x| *x < pivot), "{v:?} {pivot:?}");
+        assert!(v[num_lt..].iter().all(|x| *x >= pivot), "{v:?} {pivot:?}");
+
+        // The original set of elements must be retained.
+        let mut values: Vec<i32> = v.iter().map(&type_from_fn).collect();
+        values.sort_unstable();
+        assert_eq!(values, pattern_sorted);
+    }
+}
+
+gen_partition_test_fns_with_types!(
+    correct,
+    correct,
+    [
+        (i32, i32, i32_from_i32, i32_from_i32_ref),
+        (u64, u64, u64_from_i32, i32_from_u64),
+        (
+            ffi_string,
+            FFIString,
+            ffi_string_from_i32,
+            i32_from_ffi_string
+        ),
+    ]
+);
+
+fn by<P: Partition>(len: usize, pattern_fn: fn(usize) -> Vec<i32>) {
+    // A reversed order makes sure the comparison function is actually used, and puts the elements
+    // greater than the pivot first.
+    let pattern = pattern_fn(len);
+
+    for pivot_val in pivot_values(&pattern) {
+        let mut v = pattern.clone();
+
+        let num_gt = P::partition_by(&mut v, &pivot_val, &mut |a, b| b < a);
+
+        assert_eq!(num_gt, pattern.iter().filter(|&&x| x > pivot_val).count());
+        assert!(
+            v[..num_gt].iter().all(|&x| x > pivot_val),
+            "{v:?} {pivot_val}"
+        );
+        assert!(
+            v[num_gt..].iter().all(|&x| x <= pivot_val),
+            "{v:?} {pivot_val}"
+        );
+    }
+}
+
+gen_partition_test_fns_with_default_patterns!(by, by::<P>);
+
+fn panic_retain_orig_set<T: Ord, P: Partition>(
+    len: usize,
+    type_into_fn: impl Fn(i32) -> T,
+    type_from_fn: impl Fn(&T) -> i32,
+    pattern_fn: fn(usize) -> Vec<i32>,
+) {
+    let pattern = pattern_fn(len);
+    let sum_before: i64 = pattern.iter().map(|&x| x as i64).sum();
+
+    for pivot_val in pivot_values(&pattern) {
+        let pivot = type_into_fn(pivot_val);
+
+        let mut required_comps = 0;
+        let mut v: Vec<T> = pattern.iter().copied().map(&type_into_fn).collect();
+        P::partition_by(&mut v, &pivot, &mut |a, b| {
+            required_comps += 1;
+            a < b
+        });
+
+        if required_comps == 0 {
+            continue;
+        }
+
+        // Ensure that the panic can happen at any of the comparisons.
+        let panic_threshold = patterns::random_uniform(1, 1..=required_comps)[0] - 1;
+
+        let mut test_data: Vec<T> = pattern.iter().copied().map(&type_into_fn).collect();
+        let mut comp_counter = 0;
+
+        let res = panic::catch_unwind(AssertUnwindSafe(|| {
+            P::partition_by(&mut test_data, &pivot, &mut |a, b| {
+                if comp_counter == panic_threshold {
+                    panic!();
+                }
+                comp_counter += 1;
+
+                a < b
+            });
+        }));
+
+        assert!(res.is_err());
+
+        if should_test_for_strong_exception_safety() {
+            // If the sum before and after don't match, it means the set of elements hasn't
+            // remained the same.
+            let sum_after: i64 = test_data.iter().map(|x| type_from_fn(x) as i64).sum();
+            assert_eq!(sum_before, sum_after);
+        }
+    }
+}
+
+gen_partition_test_fns_with_types!(
+    panic_retain_orig_set,
+    panic_retain_orig_set,
+    [
+        (i32, i32, i32_from_i32, i32_from_i32_ref),
+        (cell_i32, Cell<i32>, cell_i32_from_i32, i32_from_cell_i32),
+        (ffi_string, FFIString, ffi_string_from_i32, i32_from_ffi_string),
+    ]
+);
+
+fn violate_ord_retain_orig_set<T: Ord, P: Partition>(
+    len: usize,
+    type_into_fn: impl Fn(i32) -> T,
+    type_from_fn: impl Fn(&T) -> i32,
+    pattern_fn: fn(usize) -> Vec<i32>,
+) {
+    // See the sort test of the same name. The comparison functions are turned into `is_less`
+    // functions the same way a sort implementation would do it.
+    let random_orderings_len = if cfg!(miri) { 200 } else { 10_000 };
+    let random_orderings = patterns::random_uniform(random_orderings_len, 0..2);
+
+    let pattern = pattern_fn(len);
+    let sum_before: i64 = pattern.iter().map(|&x| x as i64).sum();
+
+    for pivot_val in pivot_values(&pattern) {
+        let pivot = type_into_fn(pivot_val);
+
+        for mut comp_func in invalid_ord_comp_functions(&type_from_fn, &random_orderings) {
+            let mut test_data: Vec<T> = pattern.iter().copied().map(&type_into_fn).collect();
+
+            // It's ok to panic on Ord violation or to complete.
+            let _ = panic::catch_unwind(AssertUnwindSafe(|| {
+                let num_lt = P::partition_by(&mut test_data, &pivot, &mut |a, b| {
+                    comp_func(a, b) == Ordering::Less
+                });
+                assert!(num_lt <= test_data.len());
+            }));
+
+            let sum_after: i64 = test_data.iter().map(|x| type_from_fn(x) as i64).sum();
+            assert_eq!(sum_before, sum_after);
+        }
+    }
+}
+
+gen_partition_test_fns_with_types!(
+    violate_ord_retain_orig_set,
+    violate_ord_retain_orig_set,
+    [
+        (i32, i32, i32_from_i32, i32_from_i32_ref),
+        (cell_i32, Cell<i32>, cell_i32_from_i32, i32_from_cell_i32),
+        (ffi_string, FFIString, ffi_string_from_i32, i32_from_ffi_string),
+    ]
+);
+
+fn observable_is_less<P: Partition>(len: usize, pattern_fn: fn(usize) -> Vec<i32>) {
+    // Every modification done by the comparison function must be visible in the partitioned
+    // slice, see the sort test of the same name.
+    let pattern = pattern_fn(len);
+
+    for pivot_val in pivot_values(&pattern) {
+        let mut test_input: Vec<CompCount> = pattern.iter().copied().map(CompCount::new).collect();
+        let pivot = CompCount::new(pivot_val);
+
+        let mut comp_count_global = 0;
+
+        P::partition_by(&mut test_input, &pivot, &mut |a, b| {
+            a.comp_count.replace(a.comp_count.get() + 1);
+            b.comp_count.replace(b.comp_count.get() + 1);
+            comp_count_global += 1;
+
+            a.val < b.val
+        });
+
+        let total_inner: u64 = test_input
+            .iter()
+            .chain([&pivot])
+            .map(|c| c.comp_count.get() as u64)
+            .sum();
+
+        assert_eq!(total_inner, comp_count_global * 2);
+    }
+}
+
+gen_partition_test_fns_with_default_patterns!(observable_is_less, observable_is_less::<P>);
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! instantiate_partition_test_inner {
+    ($partition_impl:ty, [$(#[$attr:meta])*], miri_yes, $test_fn_name:ident) => {
+        #[test]
+        $(#[$attr])*
+        fn $test_fn_name() {
+            sort_test_tools::partition_tests::$test_fn_name::<$partition_impl>();
+        }
+    };
+    ($partition_impl:ty, [$(#[$attr:meta])*], miri_no, $test_fn_name:ident) => {
+        #[test]
+        #[cfg_attr(miri, ignore)]
+        $(#[$attr])*
+        fn $test_fn_name() {
+            sort_test_tools::partition_tests::$test_fn_name::<$partition_impl>();
+        }
+    };
+}
+
+// Using this construct allows us to get warnings for unused test functions.
+macro_rules! define_instantiate_partition_tests {
+    ($([$miri_use:ident, $test_fn_name:ident]),*,) => {
+        $(pub fn $test_fn_name<P: Partition>() {
+            ${concat($test_fn_name, _impl)}::<P>();
+        })*
+
+        #[doc(hidden)]
+        #[macro_export]
+        macro_rules! instantiate_partition_tests_gen {
+            ($partition_impl:ty, $attrs:tt) => {
+                $(
+                    sort_test_tools::instantiate_partition_test_inner!(
+                        $partition_impl,
+                        $attrs,
+                        $miri_use,
+                        $test_fn_name
+                    );
+                )*
+            }
+        }
+    };
+}
+
+define_instantiate_partition_tests!(
+    [miri_yes, basic],
+    [miri_no, correct_i32_random],
+    [miri_yes, correct_i32_random_z1],
+    [miri_no, correct_i32_random_d2],
+    [miri_no, correct_i32_random_d20],
+    [miri_no, correct_i32_random_s95],
+    [miri_no, correct_i32_ascending],
+    [miri_no, correct_i32_descending],
+    [miri_no, correct_i32_saw_mixed],
+    [miri_no, correct_u64_random],
+    [miri_yes, correct_u64_random_z1],
+    [miri_no, correct_u64_random_d2],
+    [miri_no, correct_u64_random_d20],
+    [miri_no, correct_u64_random_s95],
+    [miri_no, correct_u64_ascending],
+    [miri_no, correct_u64_descending],
+    [miri_no, correct_u64_saw_mixed],
+    [miri_no, correct_ffi_string_random],
+    [miri_yes, correct_ffi_string_random_z1],
+    [miri_no, correct_ffi_string_random_d2],
+    [miri_no, correct_ffi_string_random_d20],
+    [miri_no, correct_ffi_string_random_s95],
+    [miri_no, correct_ffi_string_ascending],
+    [miri_no, correct_ffi_string_descending],
+    [miri_no, correct_ffi_string_saw_mixed],
+    [miri_no, by_random],
+    [miri_yes, by_random_z1],
+    [miri_no, by_random_d2],
+    [miri_no, by_random_d20],
+    [miri_no, by_random_s95],
+    [miri_no, by_ascending],
+    [miri_no, by_descending],
+    [miri_no, by_saw_mixed],
+    [miri_no, panic_retain_orig_set_i32_random],
+    [miri_yes, panic_retain_orig_set_i32_random_z1],
+    [miri_no, panic_retain_orig_set_i32_random_d2],
+    [miri_no, panic_retain_orig_set_i32_random_d20],
+    [miri_no, panic_retain_orig_set_i32_random_s95],
+    [miri_no, panic_retain_orig_set_i32_ascending],
+    [miri_no, panic_retain_orig_set_i32_descending],
+    [miri_no, panic_retain_orig_set_i32_saw_mixed],
+    [miri_no, panic_retain_orig_set_cell_i32_random],
+    [miri_yes, panic_retain_orig_set_cell_i32_random_z1],
+    [miri_no, panic_retain_orig_set_cell_i32_random_d2],
+    [miri_no, panic_retain_orig_set_cell_i32_random_d20],
+    [miri_no, panic_retain_orig_set_cell_i32_random_s95],
+    [miri_no, panic_retain_orig_set_cell_i32_ascending],
+    [miri_no, panic_retain_orig_set_cell_i32_descending],
+    [miri_no, panic_retain_orig_set_cell_i32_saw_mixed],
+    [miri_no, panic_retain_orig_set_ffi_string_random],
+    [miri_yes, panic_retain_orig_set_ffi_string_random_z1],
+    [miri_no, panic_retain_orig_set_ffi_string_random_d2],
+    [miri_no, panic_retain_orig_set_ffi_string_random_d20],
+    [miri_no, panic_retain_orig_set_ffi_string_random_s95],
+    [miri_no, panic_retain_orig_set_ffi_string_ascending],
+    [miri_no, panic_retain_orig_set_ffi_string_descending],
+    [miri_no, panic_retain_orig_set_ffi_string_saw_mixed],
+    [miri_no, violate_ord_retain_orig_set_i32_random],
+    [miri_yes, violate_ord_retain_orig_set_i32_random_z1],
+    [miri_no, violate_ord_retain_orig_set_i32_random_d2],
+    [miri_no, violate_ord_retain_orig_set_i32_random_d20],
+    [miri_no, violate_ord_retain_orig_set_i32_random_s95],
+    [miri_no, violate_ord_retain_orig_set_i32_ascending],
+    [miri_no, violate_ord_retain_orig_set_i32_descending],
+    [miri_no, violate_ord_retain_orig_set_i32_saw_mixed],
+    [miri_no, violate_ord_retain_orig_set_cell_i32_random],
+    [miri_yes, violate_ord_retain_orig_set_cell_i32_random_z1],
+    [miri_no, violate_ord_retain_orig_set_cell_i32_random_d2],
+    [miri_no, violate_ord_retain_orig_set_cell_i32_random_d20],
+    [miri_no, violate_ord_retain_orig_set_cell_i32_random_s95],
+    [miri_no, violate_ord_retain_orig_set_cell_i32_ascending],
+    [miri_no, violate_ord_retain_orig_set_cell_i32_descending],
+    [miri_no, violate_ord_retain_orig_set_cell_i32_saw_mixed],
+    [miri_no, violate_ord_retain_orig_set_ffi_string_random],
+    [miri_yes, violate_ord_retain_orig_set_ffi_string_random_z1],
+    [miri_no, violate_ord_retain_orig_set_ffi_string_random_d2],
+    [miri_no, violate_ord_retain_orig_set_ffi_string_random_d20],
+    [miri_no, violate_ord_retain_orig_set_ffi_string_random_s95],
+    [miri_no, violate_ord_retain_orig_set_ffi_string_ascending],
+    [miri_no, violate_ord_retain_orig_set_ffi_string_descending],
+    [miri_no, violate_ord_retain_orig_set_ffi_string_saw_mixed],
+    [miri_no, observable_is_less_random],
+    [miri_yes, observable_is_less_random_z1],
+    [miri_no, observable_is_less_random_d2],
+    [miri_no, observable_is_less_random_d20],
+    [miri_no, observable_is_less_random_s95],
+    [miri_no, observable_is_less_ascending],
+    [miri_no, observable_is_less_descending],
+    [miri_no, observable_is_less_saw_mixed],
+);
+
+/// Instantiates the partition tests for `$partition_impl`, which has to implement [`Partition`].
+///
+/// Every test partitions each input with several pivots, including ones that put all elements on
+/// one side. Tests that count or inject comparisons use `partition_by`. Implementations with known
+/// defects can be instantiated with `ignore = "reason"`, which keeps their tests runnable with
+/// `--ignored`.
+#[macro_export]
+macro_rules! instantiate_partition_tests {
+    ($partition_impl:ty) => {
+        sort_test_tools::instantiate_partition_tests_gen!($partition_impl, []);
+    };
+    ($partition_impl:ty, ignore = $reason:literal) => {
+        sort_test_tools::instantiate_partition_tests_gen!($partition_impl, [#[ignore = $reason]]);
+    };
+}
@@ -9,8 +9,8 @@ use rand::prelude::*;
 
 use zipf::ZipfDistribution;
 
-/// Provides a set of patterns useful for testing and benchmarking sorting algorithms.
-/// Currently limited to i32 values.
+// Provides a set of patterns useful for testing and benchmarking sorting algorithms.
+// Currently limited to i32 values.
 
 // --- Public ---
 
@@ -391,8 +391,10 @@ impl Hasher for IdentityHasher {
 
 type IdentityBuildHasher = BuildHasherDefault<IdentityHasher>;
 
+type KeyedCacheMap = HashMap<u64, Arc<Vec<i32>>, IdentityBuildHasher>;
+
 struct KeyedVecCache {
-    keyed_caches: Mutex<Option<HashMap<u64, Arc<Vec<i32>>, IdentityBuildHasher>>>,
+    keyed_caches: Mutex<Option<KeyedCacheMap>>,
 }
 
 impl KeyedVecCache {
@@ -446,7 +448,7 @@ impl KeyedVecCache {
             let v_cached = keyed_caches_lock
                 .get_or_insert_with(Default::default)
                 .entry(key_hash)
-                .or_insert_with(Default::default);
+                .or_default();
 
             // Only insert the generated value if no better value was inserted in the meantime by
             // another thread.
@@ -0,0 +1,110 @@
+//! Checks for sorting networks, based on the 0-1 principle: A comparator network sorts all inputs
+//! if and only if it sorts all inputs that consist of only 0s and 1s.
+
+/// Returns the comparators `(a, b)` with `a < b`, that `sort_by` applies to a slice of `len`
+/// elements, if it compares the same positions in the same order for every input and places the
+/// smaller element at `a`. Otherwise returns `None`.
+///
+/// Positions are identified by the addresses of the elements passed to the comparison function, so
+/// `sort_by` has to sort in-place.
+pub fn record_comparators(
+    len: usize,
+    mut sort_by: impl FnMut(&mut [u32], &mut dyn FnMut(&u32, &u32) -> std::cmp::Ordering),
+) -> Option<Vec<(usize, usize)>> {
+    let inputs: [Vec<u32>; 3] = [
+        (0..len as u32).collect(),
+        (0..len as u32).rev().collect(),
+        crate::patterns::random(len)
+            .into_iter()
+            .map(|val| val as u32)
+            .collect(),
+    ];
+
+    let mut recorded: Option<Vec<(usize, usize)>> = None;
+
+    for mut v in inputs {
+        let mut expected = v.clone();
+        expected.sort();
+
+        let base = v.as_ptr() as usize;
+        let pos = |elem: &u32| (elem as *const u32 as usize - base) / std::mem::size_of::<u32>();
+
+        let mut comparators = Vec::new();
+        sort_by(&mut v, &mut |a, b| {
+            let (a_pos, b_pos) = (pos(a), pos(b));
+            comparators.push((a_pos.min(b_pos), a_pos.max(b_pos)));
+            a.cmp(b)
+        });
+
+        assert_eq!(v, expected);
+
+        match &recorded {
+            Some(recorded) if *recorded != comparators => return None,
+            _ => recorded = Some(comparators),
+        }
+    }
+
+    recorded
+}
+
+/// Returns `true` if the comparator network sorts all 2^`len` inputs of 0s and 1s, and thus all
+/// inputs. Every comparator `(a, b)` places the smaller element at `a`, and must satisfy
+/// `a < b < len`.
+///
+/// This is not generic, so it is compiled with the optimization level of this crate rather than
+/// the one of the caller.
+pub fn sorts_all_zero_one_inputs(comparators: &[(usize, usize)], len: usize) -> bool {
+    assert!(len < 64);
+    assert!(comparators.iter().all(|&(a, b)| a < b && b < len));
+
+    // Every line holds one bit of many inputs at once, input `i` of a chunk starting at input
+    // `base` is `base + i`. The lowest 6 bits of the inputs are the same in every word, and the
+    // remaining bits are the same within a word.
+    const CHUNK_WORDS: usize = 64;
+    const LOW_BIT_PATTERNS: [u64; 6] = [
+        0xAAAA_AAAA_AAAA_AAAA,
+        0xCCCC_CCCC_CCCC_CCCC,
+        0xF0F0_F0F0_F0F0_F0F0,
+        0xFF00_FF00_FF00_FF00,
+        0xFFFF_0000_FFFF_0000,
+        0xFFFF_FFFF_0000_0000,
+    ];
+
+    let num_inputs = 1u64 << len;
+    let mut lines = vec![[0u64; CHUNK_WORDS]; len];
+
+    let mut base = 0;
+    while base < num_inputs {
+        for (bit, line) in lines.iter_mut().enumerate() {
+            for (word_idx, word) in line.iter_mut().enumerate() {
+                let word_base = base + (word_idx as u64 * 64);
+                *word = if bit < 6 {
+                    LOW_BIT_PATTERNS[bit]
+                } else if (word_base >> bit) & 1 == 1 {
+                    !0
+                } else {
+                    0
+                };
+            }
+        }
+
+        for &(a, b) in comparators {
+            let (lo, hi) = lines.split_at_mut(b);
+            for (x, y) in lo[a].iter_mut().zip(hi[0].iter_mut()) {
+                (*x, *y) = (*x & *y, *x | *y);
+            }
+        }
+
+        // Inputs beyond `num_inputs` in the last chunk are duplicates of valid inputs.
+        let sorted = lines
+            .windows(2)
+            .all(|w| w[0].iter().zip(w[1].iter()).all(|(x, y)| x & !y == 0));
+        if !sorted {
+            return false;
+        }
+
+        base += CHUNK_WORDS as u64 * 64;
+    }
+
+    true
+}
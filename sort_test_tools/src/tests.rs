@@ -10,7 +10,9 @@ use std::rc::Rc;
 use std::sync::Mutex;
 use std::sync::OnceLock;
 
+use crate::counting_alloc;
 use crate::ffi_types::{FFIOneKibiByte, FFIString, F128};
+use crate::minimize;
 use crate::patterns;
 use crate::Sort;
 
@@ -32,6 +34,10 @@ const TEST_LENGTHS: &[usize] = &[
 ];
 
 fn write_info_to_stdout<S: Sort>() -> u64 {
+    write_info_with_name_to_stdout(<S as Sort>::name)
+}
+
+pub(crate) fn write_info_with_name_to_stdout(name_fn: fn() -> String) -> u64 {
     static SEED_WRITTEN: Mutex<bool> = Mutex::new(false);
     let seed = patterns::random_init_seed();
 
@@ -39,7 +45,7 @@ fn write_info_to_stdout<S: Sort>() -> u64 {
     if !*seed_writer {
         // Always write the seed before doing anything to ensure reproducibility of crashes.
         io::stdout()
-            .write_all(format!("\nSeed: {seed}\nTesting: {}\n\n", <S as Sort>::name()).as_bytes())
+            .write_all(format!("\nSeed: {seed}\nTesting: {}\n\n", name_fn()).as_bytes())
             .unwrap();
         io::stdout().flush().unwrap();
 
@@ -52,6 +58,10 @@ fn write_info_to_stdout<S: Sort>() -> u64 {
 fn check_is_sorted<T: Ord + Clone + Debug, S: Sort>(v: &mut [T]) {
     let seed = write_info_to_stdout::<S>();
 
+    if !is_supported::<T, S>(false) {
+        return;
+    }
+
     let is_small_test = v.len() <= 100;
     let v_orig = v.to_vec();
 
@@ -98,13 +108,55 @@ fn check_is_sorted<T: Ord + Clone + Debug, S: Sort>(v: &mut [T]) {
 fn test_is_sorted<T: Ord + Clone + Debug, S: Sort>(
     test_len: usize,
     map_fn: impl Fn(i32) -> T,
+    map_fn_path: &str,
     pattern_fn: impl Fn(usize) -> Vec<i32>,
 ) {
-    let mut test_data: Vec<T> = pattern_fn(test_len).into_iter().map(map_fn).collect();
-    check_is_sorted::<T, S>(test_data.as_mut_slice());
+    let pattern = pattern_fn(test_len);
+    let mut test_data: Vec<T> = pattern.iter().copied().map(&map_fn).collect();
+
+    let result = panic::catch_unwind(AssertUnwindSafe(|| {
+        check_is_sorted::<T, S>(test_data.as_mut_slice());
+    }));
+
+    if let Err(err) = result {
+        let minimized = minimize::minimize(&pattern, |candidate| {
+            let mut v: Vec<T> = candidate.iter().copied().map(&map_fn).collect();
+            let sort_result = panic::catch_unwind(AssertUnwindSafe(|| <S as Sort>::sort(&mut v)));
+            sort_result.is_err() || v.windows(2).any(|w| w[0] > w[1])
+        });
+
+        let minimized_data: Vec<T> = minimized.iter().copied().map(&map_fn).collect();
+        let body = format!(
+            "{}
+<{} as sort_test_tools::Sort>::sort(&mut v);
+
+assert!(v.windows(2).all(|w| w[0] <= w[1]), \"{{v:?}}\");",
+            minimized_vec_decl(&minimized, &minimized_data, map_fn_path),
+            minimize::short_type_name::<S>(),
+        );
+        eprintln!(
+            "\nMinimized failing input to len {}:\n\n{}",
+            minimized.len(),
+            minimize::format_test(&body)
+        );
+
+        panic::resume_unwind(err);
+    }
 }
 
-trait DynTrait: Debug {
+/// Declares `v` with the values of `data`. If `T` has no literal syntax, `data` is created from
+/// `values` with the function at `map_fn_path`, which has to be reachable from outside this crate.
+fn minimized_vec_decl<T: Debug>(values: &[i32], data: &[T], map_fn_path: &str) -> String {
+    match minimize::vec_literal(data) {
+        Some(literal) => format!("let mut v = {literal};"),
+        None => format!(
+            "let mut v: Vec<_> = {}.into_iter().map({map_fn_path}).collect();",
+            minimize::vec_literal(values).unwrap(),
+        ),
+    }
+}
+
+pub trait DynTrait: Debug {
     fn get_val(&self) -> i32;
 }
 
@@ -154,23 +206,23 @@ fn shift_i32_to_u32(val: i32) -> u32 {
     (val as i64 + (i32::MAX as i64 + 1)) as u32
 }
 
-fn reverse_shift_i32_to_u32(val: u32) -> i32 {
+pub(crate) fn reverse_shift_i32_to_u32(val: u32) -> i32 {
     (val as i64 - (i32::MAX as i64 + 1)) as i32
 }
 
-fn extend_i32_to_u64(val: i32) -> u64 {
+pub fn extend_i32_to_u64(val: i32) -> u64 {
     // Extends the value into the 64 bit range,
     // while preserving input order.
     (shift_i32_to_u32(val) as u64) * i32::MAX as u64
 }
 
-fn extend_i32_to_u128(val: i32) -> u128 {
+pub fn extend_i32_to_u128(val: i32) -> u128 {
     // Extends the value into the 64 bit range,
     // while preserving input order.
     (shift_i32_to_u32(val) as u128) * i64::MAX as u128
 }
 
-fn dyn_trait_from_i32(val: i32) -> Rc<dyn DynTrait> {
+pub fn dyn_trait_from_i32(val: i32) -> Rc<dyn DynTrait> {
     if val % 2 == 0 {
         Rc::new(DynValA { value: val })
     } else {
@@ -180,27 +232,31 @@ fn dyn_trait_from_i32(val: i32) -> Rc<dyn DynTrait> {
     }
 }
 
-fn i32_from_i32(val: i32) -> i32 {
+pub(crate) fn i32_from_i32(val: i32) -> i32 {
     val
 }
 
-fn i32_from_i32_ref(val: &i32) -> i32 {
+pub(crate) fn i32_from_i32_ref(val: &i32) -> i32 {
     *val
 }
 
-fn ffi_string_from_i32(val: i32) -> FFIString {
-    FFIString::new(format!("{:010}", shift_i32_to_u32(val)))
+pub fn ffi_string_from_i32(val: i32) -> FFIString {
+    FFIString::new(string_from_i32(val))
+}
+
+pub fn string_from_i32(val: i32) -> String {
+    format!("{:010}", shift_i32_to_u32(val))
 }
 
-fn i32_from_ffi_string(val: &FFIString) -> i32 {
+pub(crate) fn i32_from_ffi_string(val: &FFIString) -> i32 {
     reverse_shift_i32_to_u32(val.as_str().unwrap().parse::<u32>().unwrap())
 }
 
-fn cell_i32_from_i32(val: i32) -> Cell<i32> {
+pub(crate) fn cell_i32_from_i32(val: i32) -> Cell<i32> {
     Cell::new(val)
 }
 
-fn i32_from_cell_i32(val: &Cell<i32>) -> i32 {
+pub(crate) fn i32_from_cell_i32(val: &Cell<i32>) -> i32 {
     val.get()
 }
 
@@ -216,7 +272,13 @@ fn calc_comps_required<T, S: Sort>(v: &mut [T], mut cmp_fn: impl FnMut(&T, &T) -
     comp_counter
 }
 
-fn should_test_for_strong_exception_safety() -> bool {
+/// Returns whether `S` can sort `T`, using `sort_by` if `uses_sort_by` is set. Tests return early
+/// otherwise, it would be great to mark them as skipped, but that isn't possible as of now.
+fn is_supported<T, S: Sort>(uses_sort_by: bool) -> bool {
+    <S as Sort>::supports_type::<T>() && (!uses_sort_by || <S as Sort>::supports_sort_by())
+}
+
+pub(crate) fn should_test_for_strong_exception_safety() -> bool {
     static VAL: OnceLock<bool> = OnceLock::new();
 
     *VAL.get_or_init(|| env::var("ONLY_CHECK_BASIC_EXCEPTION_SAFETY").is_err())
@@ -224,13 +286,13 @@ fn should_test_for_strong_exception_safety() -> bool {
 
 #[derive(PartialEq, Eq, Debug, Clone)]
 #[repr(C)]
-struct CompCount {
-    val: i32,
-    comp_count: Cell<u32>,
+pub(crate) struct CompCount {
+    pub(crate) val: i32,
+    pub(crate) comp_count: Cell<u32>,
 }
 
 impl CompCount {
-    fn new(val: i32) -> Self {
+    pub(crate) fn new(val: i32) -> Self {
         Self {
             val,
             comp_count: Cell::new(0),
@@ -398,6 +460,10 @@ fn int_edge_impl<S: Sort>() {
 fn sort_vs_sort_by_impl<S: Sort>() {
     write_info_to_stdout::<S>();
 
+    if !is_supported::<i32, S>(true) {
+        return;
+    }
+
     // Ensure that sort and sort_by produce the same result.
     let mut input_normal = [800, 3, -801, 5, -801, -3, 60, 200, 50, 7, 10];
     let expected = [-801, -801, -3, 3, 5, 7, 10, 50, 60, 200, 800];
@@ -413,7 +479,12 @@ fn sort_vs_sort_by_impl<S: Sort>() {
 
 gen_sort_test_fns_with_default_patterns!(
     correct_i32,
-    |len, pattern_fn| test_is_sorted::<i32, S>(len, |val| val, pattern_fn),
+    |len, pattern_fn| test_is_sorted::<i32, S>(
+        len,
+        |val| val,
+        "std::convert::identity",
+        pattern_fn
+    ),
     TEST_LENGTHS,
     [
         (random_d4, |len| patterns::random_uniform(len, 0..4)),
@@ -438,21 +509,36 @@ gen_sort_test_fns_with_default_patterns!(
 
 gen_sort_test_fns_with_default_patterns!(
     correct_u64,
-    |len, pattern_fn| test_is_sorted::<u64, S>(len, extend_i32_to_u64, pattern_fn),
+    |len, pattern_fn| test_is_sorted::<u64, S>(
+        len,
+        extend_i32_to_u64,
+        "sort_test_tools::tests::extend_i32_to_u64",
+        pattern_fn
+    ),
     TEST_LENGTHS,
     []
 );
 
 gen_sort_test_fns_with_default_patterns!(
     correct_u128,
-    |len, pattern_fn| test_is_sorted::<u128, S>(len, extend_i32_to_u128, pattern_fn),
+    |len, pattern_fn| test_is_sorted::<u128, S>(
+        len,
+        extend_i32_to_u128,
+        "sort_test_tools::tests::extend_i32_to_u128",
+        pattern_fn
+    ),
     &TEST_LENGTHS[..TEST_LENGTHS.len() - 2],
     []
 );
 
 gen_sort_test_fns_with_default_patterns!(
     correct_cell_i32,
-    |len, pattern_fn| test_is_sorted::<Cell<i32>, S>(len, Cell::new, pattern_fn),
+    |len, pattern_fn| test_is_sorted::<Cell<i32>, S>(
+        len,
+        Cell::new,
+        "std::cell::Cell::new",
+        pattern_fn
+    ),
     &TEST_LENGTHS[..TEST_LENGTHS.len() - 2],
     []
 );
@@ -461,7 +547,8 @@ gen_sort_test_fns_with_default_patterns!(
     correct_ffi_string,
     |len, pattern_fn| test_is_sorted::<FFIString, S>(
         len,
-        |val| FFIString::new(format!("{:010}", shift_i32_to_u32(val))),
+        ffi_string_from_i32,
+        "sort_test_tools::tests::ffi_string_from_i32",
         pattern_fn
     ),
     &TEST_LENGTHS[..TEST_LENGTHS.len() - 2],
@@ -470,14 +557,24 @@ gen_sort_test_fns_with_default_patterns!(
 
 gen_sort_test_fns_with_default_patterns!(
     correct_f128,
-    |len, pattern_fn| test_is_sorted::<F128, S>(len, F128::new, pattern_fn),
+    |len, pattern_fn| test_is_sorted::<F128, S>(
+        len,
+        F128::new,
+        "sort_test_tools::ffi_types::F128::new",
+        pattern_fn
+    ),
     &TEST_LENGTHS[..TEST_LENGTHS.len() - 2],
     []
 );
 
 gen_sort_test_fns_with_default_patterns!(
     correct_1k,
-    |len, pattern_fn| test_is_sorted::<FFIOneKibiByte, S>(len, FFIOneKibiByte::new, pattern_fn),
+    |len, pattern_fn| test_is_sorted::<FFIOneKibiByte, S>(
+        len,
+        FFIOneKibiByte::new,
+        "sort_test_tools::ffi_types::FFIOneKibiByte::new",
+        pattern_fn
+    ),
     &TEST_LENGTHS[..TEST_LENGTHS.len() - 2],
     []
 );
@@ -486,7 +583,8 @@ gen_sort_test_fns_with_default_patterns!(
     correct_string,
     |len, pattern_fn| test_is_sorted::<String, S>(
         len,
-        |val| format!("{:010}", shift_i32_to_u32(val)),
+        string_from_i32,
+        "sort_test_tools::tests::string_from_i32",
         pattern_fn
     ),
     &TEST_LENGTHS[..TEST_LENGTHS.len() - 2],
@@ -496,7 +594,12 @@ gen_sort_test_fns_with_default_patterns!(
 // Dyn values are fat pointers, something the implementation might have overlooked.
 gen_sort_test_fns_with_default_patterns!(
     correct_dyn_val,
-    |len, pattern_fn| test_is_sorted::<Rc<dyn DynTrait>, S>(len, dyn_trait_from_i32, pattern_fn),
+    |len, pattern_fn| test_is_sorted::<Rc<dyn DynTrait>, S>(
+        len,
+        dyn_trait_from_i32,
+        "sort_test_tools::tests::dyn_trait_from_i32",
+        pattern_fn
+    ),
     &TEST_LENGTHS[..TEST_LENGTHS.len() - 2],
     []
 );
@@ -507,8 +610,7 @@ fn stability_legacy_impl<S: Sort>() {
 
     write_info_to_stdout::<S>();
 
-    if <S as Sort>::name().contains("unstable") {
-        // It would be great to mark the test as skipped, but that isn't possible as of now.
+    if !<S as Sort>::is_stable() || !is_supported::<u64, S>(true) {
         return;
     }
 
@@ -585,8 +687,7 @@ fn stability_with_patterns<T: Ord + Clone, S: Sort>(
     _type_from_fn: impl Fn(&T) -> i32,
     pattern_fn: fn(usize) -> Vec<i32>,
 ) {
-    if <S as Sort>::name().contains("unstable") {
-        // It would be great to mark the test as skipped, but that isn't possible as of now.
+    if !<S as Sort>::is_stable() || !is_supported::<(T, i32), S>(false) {
         return;
     }
 
@@ -623,6 +724,43 @@ fn stability_with_patterns<T: Ord + Clone, S: Sort>(
 
 gen_sort_test_fns_with_default_patterns_3_ty!(stability, stability_with_patterns, []);
 
+fn no_alloc_with_patterns<T: Ord, S: Sort>(
+    len: usize,
+    type_into_fn: impl Fn(i32) -> T,
+    _type_from_fn: impl Fn(&T) -> i32,
+    pattern_fn: fn(usize) -> Vec<i32>,
+) {
+    // Only test binaries that install `CountingAlloc` can check this.
+    if <S as Sort>::allocates::<T>()
+        || !is_supported::<T, S>(false)
+        || counting_alloc::allocations().is_none()
+    {
+        return;
+    }
+
+    let mut test_data: Vec<T> = pattern_fn(len).into_iter().map(&type_into_fn).collect();
+    let mut test_data_by: Vec<T> = pattern_fn(len).into_iter().map(&type_into_fn).collect();
+
+    // The counter only covers the calling thread, so the other tests running in parallel don't
+    // disturb it.
+    let allocations_before = counting_alloc::allocations().unwrap();
+    <S as Sort>::sort(&mut test_data);
+    if <S as Sort>::supports_sort_by() {
+        <S as Sort>::sort_by(&mut test_data_by, |a, b| b.cmp(a));
+    }
+    let allocations_after = counting_alloc::allocations().unwrap();
+
+    assert_eq!(
+        allocations_after - allocations_before,
+        0,
+        "{} allocated while sorting {len} elements of type {}",
+        <S as Sort>::name(),
+        std::any::type_name::<T>()
+    );
+}
+
+gen_sort_test_fns_with_default_patterns_3_ty!(no_alloc, no_alloc_with_patterns, []);
+
 fn observable_is_less_u64<S: Sort>(len: usize, pattern_fn: fn(usize) -> Vec<i32>) {
     // Technically this is unsound as per Rust semantics, but the only way to do this that works
     // across C FFI. In C and C++ it would be valid to have some trivial POD containing an int that
@@ -630,7 +768,7 @@ fn observable_is_less_u64<S: Sort>(len: usize, pattern_fn: fn(usize) -> Vec<i32>
     // access to a const reference. Now this int could be a pointer that was cleared inside the
     // comparison function, but this clearing is potentially not observable after the sort and it
     // will be freed again. C and C++ have no concept similar to UnsafeCell.
-    if <S as Sort>::name().starts_with("rust_") {
+    if !<S as Sort>::observes_mutation_without_cell() || !is_supported::<u64, S>(true) {
         // It would be great to mark the test as skipped, but that isn't possible as of now.
         return;
     }
@@ -654,7 +792,7 @@ fn observable_is_less_u64<S: Sort>(len: usize, pattern_fn: fn(usize) -> Vec<i32>
     let pattern = pattern_fn(len);
     let mut test_input = pattern
         .into_iter()
-        .map(|val| CompCountU64::new(val).to_u64())
+        .map(|val| CompCountU64::new(val).into_u64())
         .collect::<Vec<_>>();
 
     let mut comp_count_global = 0;
@@ -692,7 +830,7 @@ fn observable_is_less_u64<S: Sort>(len: usize, pattern_fn: fn(usize) -> Vec<i32>
             }
         }
 
-        fn to_u64(self) -> u64 {
+        fn into_u64(self) -> u64 {
             // SAFETY: See above asserts.
             unsafe { mem::transmute::<Self, u64>(self) }
         }
@@ -719,11 +857,12 @@ fn observable_is_less<S: Sort>(len: usize, pattern_fn: fn(usize) -> Vec<i32>) {
     // code. Eg T == Mutex<Option<Box<str>>> which replaces the pointer with none in the comparison
     // function, which would not be observed in the original slice and would lead to a double free.
 
+    if !is_supported::<CompCount, S>(true) {
+        return;
+    }
+
     let pattern = pattern_fn(len);
-    let mut test_input = pattern
-        .into_iter()
-        .map(|val| CompCount::new(val))
-        .collect::<Vec<_>>();
+    let mut test_input = pattern.into_iter().map(CompCount::new).collect::<Vec<_>>();
 
     let mut comp_count_global = 0;
 
@@ -753,6 +892,10 @@ fn panic_retain_orig_set<T: Ord + Clone, S: Sort>(
     type_from_fn: impl Fn(&T) -> i32,
     pattern_fn: fn(usize) -> Vec<i32>,
 ) {
+    if !<S as Sort>::is_panic_safe() || !is_supported::<T, S>(true) {
+        return;
+    }
+
     let mut test_data: Vec<T> = pattern_fn(len).into_iter().map(type_into_fn).collect();
 
     let sum_before: i64 = test_data.iter().map(|x| type_from_fn(x) as i64).sum();
@@ -801,9 +944,13 @@ fn panic_observable_is_less<S: Sort>(len: usize, pattern_fn: fn(usize) -> Vec<i3
     // code. Eg T == Mutex<Option<Box<str>>> which replaces the pointer with none in the comparison
     // function, which would not be observed in the original slice and would lead to a double free.
 
+    if !<S as Sort>::is_panic_safe() || !is_supported::<CompCount, S>(true) {
+        return;
+    }
+
     let mut test_input = pattern_fn(len)
         .into_iter()
-        .map(|val| CompCount::new(val))
+        .map(CompCount::new)
         .collect::<Vec<_>>();
 
     let sum_before: i64 = test_input.iter().map(|x| x.val as i64).sum();
@@ -867,9 +1014,13 @@ fn deterministic<T: Ord + Clone + Debug, S: Sort>(
     // This test only tests that results are deterministic across runs, it does not test determinism
     // on different platforms and with different toolchains.
 
+    if !is_supported::<T, S>(true) {
+        return;
+    }
+
     let mut test_input = pattern_fn(len)
         .into_iter()
-        .map(|val| type_into_fn(val))
+        .map(type_into_fn)
         .collect::<Vec<_>>();
 
     let mut test_input_clone = test_input.clone();
@@ -901,9 +1052,13 @@ fn self_cmp<T: Ord + Clone + Debug, S: Sort>(
     // It's possible for comparisons to run into problems if the values of `a` and `b` passed into
     // the comparison function are the same reference. So this tests that they never are.
 
+    if !is_supported::<T, S>(true) {
+        return;
+    }
+
     let mut test_input = pattern_fn(len)
         .into_iter()
-        .map(|val| type_into_fn(val))
+        .map(type_into_fn)
         .collect::<Vec<_>>();
 
     let comparison_fn = |a: &T, b: &T| {
@@ -921,26 +1076,15 @@ fn self_cmp<T: Ord + Clone + Debug, S: Sort>(
 
 gen_sort_test_fns_with_default_patterns_3_ty!(self_cmp, self_cmp, []);
 
-fn violate_ord_retain_orig_set<T: Ord, S: Sort>(
-    len: usize,
-    type_into_fn: impl Fn(i32) -> T + Copy,
-    type_from_fn: impl Fn(&T) -> i32,
-    pattern_fn: fn(usize) -> Vec<i32>,
-) {
-    // A user may implement Ord incorrectly for a type or violate it by calling sort_by with a
-    // comparison function that violates Ord with the orderings it returns. Even under such
-    // circumstances the input must retain its original set of elements.
-
-    // Ord implies a strict total order see https://en.wikipedia.org/wiki/Total_order.
-
-    // Generating random numbers with miri is quite expensive.
-    let random_orderings_len = if cfg!(miri) { 200 } else { 10_000 };
-
-    // Make sure we get a good distribution of random orderings, that are repeatable with the seed.
-    // Just using random_uniform with the same len and range will always yield the same value.
-    let random_orderings = patterns::random_uniform(random_orderings_len, 0..2);
+pub(crate) type CompFn<'a, T> = Box<dyn FnMut(&T, &T) -> Ordering + 'a>;
 
-    let get_random_0_1_or_2 = |random_idx: &mut usize| {
+/// Returns comparison functions that violate Ord in different ways. Every call returns functions
+/// with fresh state, so that a run can be repeated.
+pub(crate) fn invalid_ord_comp_functions<'a, T: Ord>(
+    type_from_fn: &'a impl Fn(&T) -> i32,
+    random_orderings: &'a [i32],
+) -> Vec<CompFn<'a, T>> {
+    let get_random_0_1_or_2 = move |random_idx: &mut usize| {
         let ridx = *random_idx;
         *random_idx += 1;
         if ridx + 1 == random_orderings.len() {
@@ -965,8 +1109,8 @@ fn violate_ord_retain_orig_set<T: Ord, S: Sort>(
 
     // Examples, a = 3, b = 5, c = 9.
     // Correct Ord -> 10010 | is_less(a, b) is_less(a, a) is_less(b, a) is_less(a, c) is_less(c, a)
-    let mut invalid_ord_comp_functions: Vec<Box<dyn FnMut(&T, &T) -> Ordering>> = vec![
-        Box::new(|_a, _b| -> Ordering {
+    vec![
+        Box::new(move |_a, _b| -> Ordering {
             // random
             // Eg. is_less(3, 5) == true, is_less(3, 5) == false
 
@@ -994,7 +1138,7 @@ fn violate_ord_retain_orig_set<T: Ord, S: Sort>(
                 Ordering::Greater
             }
         }),
-        Box::new(|a, b| -> Ordering {
+        Box::new(move |a, b| -> Ordering {
             // Transitive breaker. remember last element -> 10001
             let lea = last_element_a;
             let leb = last_element_b;
@@ -1011,7 +1155,7 @@ fn violate_ord_retain_orig_set<T: Ord, S: Sort>(
                 a.cmp(b)
             }
         }),
-        Box::new(|a, b| -> Ordering {
+        Box::new(move |a, b| -> Ordering {
             // Sampled random 1% of comparisons are reversed.
             rand_counter_b += get_random_0_1_or_2(&mut random_idx_b);
             if rand_counter_b >= 100 {
@@ -1021,7 +1165,7 @@ fn violate_ord_retain_orig_set<T: Ord, S: Sort>(
                 a.cmp(b)
             }
         }),
-        Box::new(|a, b| -> Ordering {
+        Box::new(move |a, b| -> Ordering {
             // Sampled random 33% of comparisons are reversed.
             rand_counter_c += get_random_0_1_or_2(&mut random_idx_c);
             if rand_counter_c >= 3 {
@@ -1031,7 +1175,7 @@ fn violate_ord_retain_orig_set<T: Ord, S: Sort>(
                 a.cmp(b)
             }
         }),
-        Box::new(|a, b| -> Ordering {
+        Box::new(move |a, b| -> Ordering {
             // STREAK_LEN comparisons yield a.cmp(b) then STREAK_LEN comparisons less. This can
             // discover bugs that neither, random Ord, or just Less or Greater can find. Because it
             // can push a pointer further than expected. Random Ord will average out how far a
@@ -1049,7 +1193,7 @@ fn violate_ord_retain_orig_set<T: Ord, S: Sort>(
                 Ordering::Less
             }
         }),
-        Box::new(|a, b| -> Ordering {
+        Box::new(move |a, b| -> Ordering {
             // See above.
             const STREAK_LEN: usize = 50;
 
@@ -1063,22 +1207,125 @@ fn violate_ord_retain_orig_set<T: Ord, S: Sort>(
                 Ordering::Greater
             }
         }),
-    ];
+    ]
+}
 
-    for comp_func in &mut invalid_ord_comp_functions {
-        let mut test_data: Vec<T> = pattern_fn(len).into_iter().map(type_into_fn).collect();
-        let sum_before: i64 = test_data.iter().map(|x| type_from_fn(x) as i64).sum();
+/// Sorts the elements created from `values` with `comp_func`, and returns whether the set of
+/// elements changed, together with the sequence of orderings returned by `comp_func`.
+fn sort_changes_set<T: Ord, S: Sort>(
+    values: &[i32],
+    type_into_fn: impl Fn(i32) -> T,
+    type_from_fn: impl Fn(&T) -> i32,
+    comp_func: &mut CompFn<T>,
+) -> (bool, Vec<Ordering>) {
+    let mut test_data: Vec<T> = values.iter().copied().map(type_into_fn).collect();
+    let sum_before: i64 = test_data.iter().map(|x| type_from_fn(x) as i64).sum();
 
-        // It's ok to panic on Ord violation or to complete.
-        // In both cases the original elements must still be present.
-        let _ = panic::catch_unwind(AssertUnwindSafe(|| {
-            <S as Sort>::sort_by(&mut test_data, &mut *comp_func);
-        }));
+    let mut orderings = Vec::new();
 
-        // If the sum before and after don't match, it means the set of elements hasn't remained the
-        // same.
-        let sum_after: i64 = test_data.iter().map(|x| type_from_fn(x) as i64).sum();
-        assert_eq!(sum_before, sum_after);
+    // It's ok to panic on Ord violation or to complete.
+    // In both cases the original elements must still be present.
+    let _ = panic::catch_unwind(AssertUnwindSafe(|| {
+        <S as Sort>::sort_by(&mut test_data, |a, b| {
+            let ord = comp_func(a, b);
+            orderings.push(ord);
+            ord
+        });
+    }));
+
+    // If the sum before and after don't match, it means the set of elements hasn't remained the
+    // same.
+    let sum_after: i64 = test_data.iter().map(|x| type_from_fn(x) as i64).sum();
+
+    (sum_before != sum_after, orderings)
+}
+
+/// Minimizes `pattern` while `run` reports a changed set of elements, and prints a test that
+/// replays the orderings seen by the minimized run, with the elements created by `type_into_fn`.
+fn report_minimized_violate_ord<T: Debug, S: Sort>(
+    pattern: &[i32],
+    type_into_fn: impl Fn(i32) -> T,
+    type_into_fn_path: &str,
+    mut run: impl FnMut(&[i32]) -> (bool, Vec<Ordering>),
+) {
+    let minimized = minimize::minimize(pattern, |values| run(values).0);
+    let (_, orderings) = run(&minimized);
+    let minimized_data: Vec<T> = minimized.iter().copied().map(type_into_fn).collect();
+
+    let body = format!(
+        "{}
+let mut orderings = {}.iter();
+
+let mut expected = v.clone();
+expected.sort();
+
+let _ = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {{
+    <{} as sort_test_tools::Sort>::sort_by(&mut v, |_, _| match orderings.next() {{
+        Some(b'L') => std::cmp::Ordering::Less,
+        Some(b'G') => std::cmp::Ordering::Greater,
+        _ => std::cmp::Ordering::Equal,
+    }});
+}}));
+
+v.sort();
+assert_eq!(v, expected);",
+        minimized_vec_decl(&minimized, &minimized_data, type_into_fn_path),
+        minimize::orderings_literal(&orderings),
+        minimize::short_type_name::<S>(),
+    );
+    eprintln!(
+        "\nMinimized failing input to len {}:\n\n{}",
+        minimized.len(),
+        minimize::format_test(&body)
+    );
+}
+
+fn violate_ord_retain_orig_set<T: Ord + Debug, S: Sort>(
+    len: usize,
+    type_into_fn: impl Fn(i32) -> T + Copy,
+    type_into_fn_path: &str,
+    type_from_fn: impl Fn(&T) -> i32 + Copy,
+    pattern_fn: fn(usize) -> Vec<i32>,
+) {
+    // A user may implement Ord incorrectly for a type or violate it by calling sort_by with a
+    // comparison function that violates Ord with the orderings it returns. Even under such
+    // circumstances the input must retain its original set of elements.
+
+    // Ord implies a strict total order see https://en.wikipedia.org/wiki/Total_order.
+
+    if !is_supported::<T, S>(true) {
+        return;
+    }
+
+    // Generating random numbers with miri is quite expensive.
+    let random_orderings_len = if cfg!(miri) { 200 } else { 10_000 };
+
+    // Make sure we get a good distribution of random orderings, that are repeatable with the seed.
+    // Just using random_uniform with the same len and range will always yield the same value.
+    let random_orderings = patterns::random_uniform(random_orderings_len, 0..2);
+
+    let comp_functions = invalid_ord_comp_functions(&type_from_fn, &random_orderings);
+
+    for (comp_idx, mut comp_func) in comp_functions.into_iter().enumerate() {
+        let pattern = pattern_fn(len);
+        let (set_changed, _) =
+            sort_changes_set::<T, S>(&pattern, type_into_fn, type_from_fn, &mut comp_func);
+
+        if set_changed {
+            report_minimized_violate_ord::<T, S>(
+                &pattern,
+                type_into_fn,
+                type_into_fn_path,
+                |values| {
+                    let mut comp_func =
+                        invalid_ord_comp_functions(&type_from_fn, &random_orderings)
+                            .swap_remove(comp_idx);
+                    sort_changes_set::<T, S>(values, type_into_fn, type_from_fn, &mut comp_func)
+                },
+            );
+        }
+
+        assert!(!set_changed, "the set of elements changed");
 
         if cfg!(miri) {
             // This test is prohibitively expensive in miri, so only run one of the comparison
@@ -1089,12 +1336,141 @@ fn violate_ord_retain_orig_set<T: Ord, S: Sort>(
     }
 }
 
-gen_sort_test_fns_with_default_patterns_3_ty!(
-    violate_ord_retain_orig_set,
-    violate_ord_retain_orig_set,
+// Same types as `gen_sort_test_fns_with_default_patterns_3_ty`, but with the path of the
+// conversion function for the printed test case.
+gen_sort_test_fns_with_default_patterns!(
+    violate_ord_retain_orig_set_i32,
+    |len, pattern_fn| violate_ord_retain_orig_set::<i32, S>(
+        len,
+        i32_from_i32,
+        "std::convert::identity",
+        i32_from_i32_ref,
+        pattern_fn
+    ),
+    &TEST_LENGTHS[..TEST_LENGTHS.len() - 2],
+    []
+);
+
+gen_sort_test_fns_with_default_patterns!(
+    violate_ord_retain_orig_set_cell_i32,
+    |len, pattern_fn| violate_ord_retain_orig_set::<Cell<i32>, S>(
+        len,
+        cell_i32_from_i32,
+        "std::cell::Cell::new",
+        i32_from_cell_i32,
+        pattern_fn
+    ),
+    &TEST_LENGTHS[..TEST_LENGTHS.len() - 3],
+    []
+);
+
+gen_sort_test_fns_with_default_patterns!(
+    violate_ord_retain_orig_set_ffi_string,
+    |len, pattern_fn| violate_ord_retain_orig_set::<FFIString, S>(
+        len,
+        ffi_string_from_i32,
+        "sort_test_tools::tests::ffi_string_from_i32",
+        i32_from_ffi_string,
+        pattern_fn
+    ),
+    &TEST_LENGTHS[..TEST_LENGTHS.len() - 3],
+    []
+);
+
+fn sort_by_key_with_patterns<S: Sort>(len: usize, pattern_fn: fn(usize) -> Vec<i32>) {
+    if !is_supported::<i32, S>(true) {
+        return;
+    }
+
+    // Use a key that differs from the natural order of the values, to make sure the key extraction
+    // function is actually used for the comparison.
+    let key_fn = |val: &i32| val.wrapping_abs() % 10_000;
+
+    let pattern = pattern_fn(len);
+    let mut v = pattern.clone();
+
+    <S as Sort>::sort_by_key(&mut v, key_fn);
+
+    assert!(v.windows(2).all(|w| key_fn(&w[0]) <= key_fn(&w[1])));
+
+    // The original set of elements must be retained.
+    let mut v_sorted = v.clone();
+    v_sorted.sort();
+    let mut pattern_sorted = pattern;
+    pattern_sorted.sort();
+    assert_eq!(v_sorted, pattern_sorted);
+}
+
+gen_sort_test_fns_with_default_patterns!(
+    sort_by_key,
+    sort_by_key_with_patterns::<S>,
+    &TEST_LENGTHS[..TEST_LENGTHS.len() - 2],
+    []
+);
+
+fn sort_by_cached_key_with_patterns<S: Sort>(len: usize, pattern_fn: fn(usize) -> Vec<i32>) {
+    // The default implementation sorts the keys together with the indices of the elements.
+    if !is_supported::<(String, usize), S>(false) {
+        return;
+    }
+
+    // Use an allocating key that differs from the natural order of the values, to make sure the
+    // key extraction function is actually used for the comparison.
+    let mut key_calls = 0;
+    let mut key_fn = |val: &i32| {
+        key_calls += 1;
+        format!("{:05}", val.wrapping_abs() % 10_000)
+    };
+
+    let pattern = pattern_fn(len);
+    let mut v = pattern.clone();
+
+    <S as Sort>::sort_by_cached_key(&mut v, &mut key_fn);
+
+    // The key must only be computed once per element.
+    assert_eq!(key_calls, len);
+
+    // Caching the key with the index of the element yields the same result as a stable sort.
+    let mut expected = pattern;
+    expected.sort_by_key(|val| val.wrapping_abs() % 10_000);
+    assert_eq!(v, expected);
+}
+
+gen_sort_test_fns_with_default_patterns!(
+    sort_by_cached_key,
+    sort_by_cached_key_with_patterns::<S>,
+    &TEST_LENGTHS[..TEST_LENGTHS.len() - 2],
     []
 );
 
+/// Sorts inputs that are decided by [`crate::adversary`] while the sort runs, and checks that the
+/// number of comparisons stays within `max_factor * n * log2(n)`.
+pub fn adversary_comparisons<S: Sort>(max_factor: f64) {
+    write_info_to_stdout::<S>();
+
+    if !is_supported::<usize, S>(true) {
+        return;
+    }
+
+    for len in [100, 1_000, 10_000, 100_000] {
+        let comparisons = crate::adversary::antiqsort::<S>(len).comparisons();
+        let n_log2_n = len as f64 * (len as f64).log2();
+
+        assert!(
+            comparisons as f64 <= max_factor * n_log2_n,
+            "len: {len} comparisons: {comparisons} ({:.2} * n * log2(n))",
+            comparisons as f64 / n_log2_n
+        );
+    }
+}
+
+fn adversary_n_log_n_impl<S: Sort>() {
+    // Leaves room for sorts that fall back to heapsort only after a few unbalanced partitions. A
+    // sort that goes quadratic needs thousands of times n * log2(n) comparisons at the largest
+    // length.
+    adversary_comparisons::<S>(6.0);
+}
+
 #[doc(hidden)]
 #[macro_export]
 macro_rules! instantiate_sort_test_inner {
@@ -1253,6 +1629,30 @@ define_instantiate_sort_tests!(
     [miri_no, stability_ffi_string_ascending],
     [miri_no, stability_ffi_string_descending],
     [miri_no, stability_ffi_string_saw_mixed],
+    [miri_no, no_alloc_i32_random],
+    [miri_yes, no_alloc_i32_random_z1],
+    [miri_no, no_alloc_i32_random_d2],
+    [miri_no, no_alloc_i32_random_d20],
+    [miri_no, no_alloc_i32_random_s95],
+    [miri_no, no_alloc_i32_ascending],
+    [miri_no, no_alloc_i32_descending],
+    [miri_no, no_alloc_i32_saw_mixed],
+    [miri_no, no_alloc_cell_i32_random],
+    [miri_yes, no_alloc_cell_i32_random_z1],
+    [miri_no, no_alloc_cell_i32_random_d2],
+    [miri_no, no_alloc_cell_i32_random_d20],
+    [miri_no, no_alloc_cell_i32_random_s95],
+    [miri_no, no_alloc_cell_i32_ascending],
+    [miri_no, no_alloc_cell_i32_descending],
+    [miri_no, no_alloc_cell_i32_saw_mixed],
+    [miri_no, no_alloc_ffi_string_random],
+    [miri_yes, no_alloc_ffi_string_random_z1],
+    [miri_no, no_alloc_ffi_string_random_d2],
+    [miri_no, no_alloc_ffi_string_random_d20],
+    [miri_no, no_alloc_ffi_string_random_s95],
+    [miri_no, no_alloc_ffi_string_ascending],
+    [miri_no, no_alloc_ffi_string_descending],
+    [miri_no, no_alloc_ffi_string_saw_mixed],
     [miri_no, observable_is_less_u64_random],
     [miri_no, observable_is_less_u64_random_z1],
     [miri_no, observable_is_less_u64_random_d2],
@@ -1373,8 +1773,29 @@ define_instantiate_sort_tests!(
     [miri_no, violate_ord_retain_orig_set_ffi_string_ascending],
     [miri_no, violate_ord_retain_orig_set_ffi_string_descending],
     [miri_no, violate_ord_retain_orig_set_ffi_string_saw_mixed],
+    [miri_no, sort_by_key_random],
+    [miri_yes, sort_by_key_random_z1],
+    [miri_no, sort_by_key_random_d2],
+    [miri_no, sort_by_key_random_d20],
+    [miri_no, sort_by_key_random_s95],
+    [miri_no, sort_by_key_ascending],
+    [miri_no, sort_by_key_descending],
+    [miri_no, sort_by_key_saw_mixed],
+    [miri_no, sort_by_cached_key_random],
+    [miri_yes, sort_by_cached_key_random_z1],
+    [miri_no, sort_by_cached_key_random_d2],
+    [miri_no, sort_by_cached_key_random_d20],
+    [miri_no, sort_by_cached_key_random_s95],
+    [miri_no, sort_by_cached_key_ascending],
+    [miri_no, sort_by_cached_key_descending],
+    [miri_no, sort_by_cached_key_saw_mixed],
+    [miri_no, adversary_n_log_n],
 );
 
+/// Instantiates the sort tests for `$sort_impl`, which has to implement [`Sort`].
+///
+/// Tests that need a capability the implementation doesn't report, e.g. [`Sort::is_stable`] or
+/// [`Sort::supports_type`] for the sorted type, pass without running.
 #[macro_export]
 macro_rules! instantiate_sort_tests {
     ($sort_impl:ty) => {
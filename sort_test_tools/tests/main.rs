@@ -1,6 +1,12 @@
+#![feature(iter_partition_in_place)]
+
+use sort_test_tools::counting_alloc::CountingAlloc;
 use sort_test_tools::instantiate_sort_tests;
 use sort_test_tools::Sort;
 
+#[global_allocator]
+static ALLOC: CountingAlloc = CountingAlloc;
+
 struct SortImpl {}
 
 impl Sort for SortImpl {
@@ -21,6 +27,166 @@ impl Sort for SortImpl {
     {
         arr.sort_by(compare);
     }
+
+    fn sort_by_key<T, K, F>(arr: &mut [T], f: F)
+    where
+        K: Ord,
+        F: FnMut(&T) -> K,
+    {
+        arr.sort_by_key(f);
+    }
+
+    fn sort_by_cached_key<T, K, F>(arr: &mut [T], f: F)
+    where
+        K: Ord,
+        F: FnMut(&T) -> K,
+    {
+        arr.sort_by_cached_key(f);
+    }
 }
 
 instantiate_sort_tests!(SortImpl);
+
+mod partition {
+    use sort_test_tools::instantiate_partition_tests;
+    use sort_test_tools::Partition;
+
+    struct PartitionImpl {}
+
+    impl Partition for PartitionImpl {
+        fn name() -> String {
+            "rust_std_partition_in_place".into()
+        }
+
+        fn partition<T: Ord>(arr: &mut [T], pivot: &T) -> usize {
+            Self::partition_by(arr, pivot, &mut |a, b| a.lt(b))
+        }
+
+        fn partition_by<T, F: FnMut(&T, &T) -> bool>(
+            arr: &mut [T],
+            pivot: &T,
+            is_less: &mut F,
+        ) -> usize {
+            arr.iter_mut()
+                .partition_in_place(|elem| is_less(elem, pivot))
+        }
+    }
+
+    instantiate_partition_tests!(PartitionImpl);
+}
+
+mod minimize {
+    use sort_test_tools::minimize;
+    use sort_test_tools::patterns;
+
+    #[test]
+    fn shrinks_to_minimal_input() {
+        // Fails if any element is larger than its successor by more than 5.
+        let input = patterns::random_uniform(500, 0..1_000);
+        let minimized = minimize::minimize(&input, |v| v.windows(2).any(|w| w[0] > w[1] + 5));
+
+        assert_eq!(minimized, [6, 0]);
+    }
+
+    #[test]
+    fn literals() {
+        use std::cmp::Ordering;
+
+        assert_eq!(
+            minimize::vec_literal(&[3u64, 0, 7]).as_deref(),
+            Some("vec![3, 0, 7]")
+        );
+        assert_eq!(minimize::vec_literal(&[String::from("a")]), None);
+        assert_eq!(
+            minimize::orderings_literal(&[Ordering::Less, Ordering::Equal, Ordering::Greater]),
+            "b\"LEG\""
+        );
+    }
+}
+
+mod partition_point {
+    use sort_test_tools::instantiate_partition_point_tests;
+    use sort_test_tools::PartitionPoint;
+
+    struct PartitionPointImpl {}
+
+    impl PartitionPoint for PartitionPointImpl {
+        fn name() -> String {
+            "rust_std_partition_point".into()
+        }
+
+        fn partition_point<T>(arr: &[T], val: &T) -> usize
+        where
+            T: Ord,
+        {
+            arr.partition_point(|elem| elem < val)
+        }
+
+        fn partition_point_by<T, F>(arr: &[T], val: &T, mut compare: F) -> usize
+        where
+            F: FnMut(&T, &T) -> std::cmp::Ordering,
+        {
+            arr.partition_point(|elem| compare(elem, val).is_lt())
+        }
+    }
+
+    instantiate_partition_point_tests!(PartitionPointImpl);
+}
+
+mod capabilities {
+    use std::any;
+
+    use sort_test_tools::instantiate_sort_tests;
+    use sort_test_tools::Sort;
+
+    struct SortImpl {}
+
+    // Panics for everything the capabilities exclude, like the sorts that specialize on a few
+    // types. All tests must still pass.
+    impl Sort for SortImpl {
+        fn name() -> String {
+            "rust_std_i32_only_unstable".into()
+        }
+
+        fn sort<T>(arr: &mut [T])
+        where
+            T: Ord,
+        {
+            assert!(Self::supports_type::<T>(), "Type not supported");
+            arr.sort_unstable();
+        }
+
+        fn sort_by<T, F>(_arr: &mut [T], _compare: F)
+        where
+            F: FnMut(&T, &T) -> std::cmp::Ordering,
+        {
+            panic!("sort_by not supported");
+        }
+
+        fn supports_sort_by() -> bool {
+            false
+        }
+
+        fn supports_type<T>() -> bool {
+            any::type_name::<T>() == "i32"
+        }
+
+        fn allocates<T>() -> bool {
+            false
+        }
+    }
+
+    instantiate_sort_tests!(SortImpl);
+
+    #[test]
+    fn defaults() {
+        assert!(super::SortImpl::is_stable());
+        assert!(super::SortImpl::supports_sort_by());
+        assert!(super::SortImpl::supports_type::<String>());
+        assert!(super::SortImpl::allocates::<i32>());
+        assert!(super::SortImpl::is_panic_safe());
+        assert!(!super::SortImpl::observes_mutation_without_cell());
+
+        assert!(!SortImpl::is_stable());
+    }
+}
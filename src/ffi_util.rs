@@ -62,7 +62,23 @@ macro_rules! ffi_sort_impl {
 
         use sort_test_tools::ffi_types::{CompResult, FFIOneKibiByte, FFIString, F128};
 
-        sort_impl!($name);
+        sort_impl!($name, {
+            fn supports_type<T>() -> bool {
+                <T as CppSort>::is_supported()
+            }
+
+            // A panic in the comparison function is turned into a C++ exception, and C and C++
+            // sorts only give the basic exception guarantee.
+            fn is_panic_safe() -> bool {
+                false
+            }
+
+            // C and C++ have `mutable` members, so their sorts must not compare copies that are
+            // dropped without being written back.
+            fn observes_mutation_without_cell() -> bool {
+                true
+            }
+        });
 
         paste::paste! {
             extern "C" {
@@ -106,6 +122,7 @@ macro_rules! ffi_sort_impl {
             trait CppSort: Sized {
                 fn sort(data: &mut [Self]);
                 fn sort_by<F: FnMut(&Self, &Self) -> Ordering>(data: &mut [Self], compare: F);
+                fn is_supported() -> bool;
             }
 
             impl<T> CppSort for T {
@@ -116,6 +133,10 @@ macro_rules! ffi_sort_impl {
                 default fn sort_by<F: FnMut(&T, &T) -> Ordering>(_data: &mut [T], _compare: F) {
                     panic!("Type not supported");
                 }
+
+                default fn is_supported() -> bool {
+                    false
+                }
             }
 
             impl CppSort for i32 {
@@ -128,6 +149,10 @@ macro_rules! ffi_sort_impl {
                 fn sort_by<F: FnMut(&Self, &Self) -> Ordering>(data: &mut [Self], compare: F) {
                     make_cpp_sort_by!([<$sort_name_prefix _i32_by>], data, compare, Self);
                 }
+
+                fn is_supported() -> bool {
+                    true
+                }
             }
 
             impl CppSort for u64 {
@@ -140,6 +165,10 @@ macro_rules! ffi_sort_impl {
                 fn sort_by<F: FnMut(&Self, &Self) -> Ordering>(data: &mut [Self], compare: F) {
                     make_cpp_sort_by!([<$sort_name_prefix _u64_by>], data, compare, Self);
                 }
+
+                fn is_supported() -> bool {
+                    true
+                }
             }
 
             impl CppSort for FFIString {
@@ -152,6 +181,10 @@ macro_rules! ffi_sort_impl {
                 fn sort_by<F: FnMut(&Self, &Self) -> Ordering>(data: &mut [Self], compare: F) {
                     make_cpp_sort_by!([<$sort_name_prefix _ffi_string_by>], data, compare, Self);
                 }
+
+                fn is_supported() -> bool {
+                    true
+                }
             }
 
             impl CppSort for F128 {
@@ -164,6 +197,10 @@ macro_rules! ffi_sort_impl {
                 fn sort_by<F: FnMut(&Self, &Self) -> Ordering>(data: &mut [Self], compare: F) {
                     make_cpp_sort_by!([<$sort_name_prefix _f128_by>], data, compare, Self);
                 }
+
+                fn is_supported() -> bool {
+                    true
+                }
             }
 
             impl CppSort for FFIOneKibiByte {
@@ -176,6 +213,10 @@ macro_rules! ffi_sort_impl {
                 fn sort_by<F: FnMut(&Self, &Self) -> Ordering>(data: &mut [Self], compare: F) {
                     make_cpp_sort_by!([<$sort_name_prefix _1k_by>], data, compare, Self);
                 }
+
+                fn is_supported() -> bool {
+                    true
+                }
             }
 
             pub fn sort<T: Ord>(data: &mut [T]) {
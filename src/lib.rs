@@ -16,8 +16,10 @@
     generic_const_exprs
 )]
 
+// The optional block overrides the capabilities of `sort_test_tools::Sort`, such as
+// `supports_type`, for implementations that don't support everything.
 macro_rules! sort_impl {
-    ($name:expr) => {
+    ($name:expr $(, { $($capabilities:tt)* })?) => {
         pub struct SortImpl;
 
         impl sort_test_tools::Sort for SortImpl {
@@ -40,6 +42,8 @@ macro_rules! sort_impl {
             {
                 sort_by(arr, compare);
             }
+
+            $($($capabilities)*)?
         }
     };
 }
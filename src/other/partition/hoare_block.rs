@@ -89,7 +89,7 @@ fn partition<T, F: FnMut(&T, &T) -> bool>(v: &mut [T], pivot: &T, is_less: &mut
 
         if start_l == end_l {
             // Trace `block_l` elements from the left side.
-            start_l = mem::MaybeUninit::slice_as_mut_ptr(&mut offsets_l);
+            start_l = offsets_l.as_mut_ptr().cast::<u8>();
             end_l = start_l;
             let mut elem = l;
 
@@ -115,7 +115,7 @@ fn partition<T, F: FnMut(&T, &T) -> bool>(v: &mut [T], pivot: &T, is_less: &mut
 
         if start_r == end_r {
             // Trace `block_r` elements from the right side.
-            start_r = mem::MaybeUninit::slice_as_mut_ptr(&mut offsets_r);
+            start_r = offsets_r.as_mut_ptr().cast::<u8>();
             end_r = start_r;
             let mut elem = r;
 
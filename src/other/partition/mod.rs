@@ -4,17 +4,9 @@ use std::alloc;
 use std::cell::RefCell;
 use std::ptr::NonNull;
 
-pub trait Partition {
-    fn name() -> String;
-
-    fn partition<T: Ord>(arr: &mut [T], pivot: &T) -> usize;
-
-    fn partition_by<T, F: FnMut(&T, &T) -> bool>(
-        arr: &mut [T],
-        pivot: &T,
-        is_less: &mut F,
-    ) -> usize;
-}
+// Defined in sort_test_tools, so that the partition tests can be instantiated for the
+// implementations.
+pub use sort_test_tools::Partition;
 
 macro_rules! partition_impl {
     ($name:expr) => {
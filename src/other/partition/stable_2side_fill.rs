@@ -61,7 +61,7 @@ where
 
     // SAFETY: TODO
     unsafe {
-        let scratch_ptr = MaybeUninit::slice_as_mut_ptr(scratch);
+        let scratch_ptr = scratch.as_mut_ptr().cast::<T>();
 
         // lt == less than, ge == greater or equal
         let mut lt_count = 0;
@@ -23,5 +23,6 @@ where
         b = b.wrapping_add((should_add as usize) << k);
     }
 
-    b + 1
+    // `b` is still `usize::MAX` if no element satisfies `pred`.
+    b.wrapping_add(1)
 }
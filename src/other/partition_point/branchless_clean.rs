@@ -36,6 +36,7 @@ where
         n -= n / 2;
     }
 
-    // [i+1, i+1+n) contains our result, and n == 1.
-    i + 1
+    // [i+1, i+1+n) contains our result, and n == 1. `i` is still -1 if no element satisfies
+    // `pred`.
+    i.wrapping_add(1)
 }
@@ -1,14 +1,6 @@
-pub trait PartitionPoint {
-    fn name() -> String;
-
-    fn partition_point<T>(arr: &[T], val: &T) -> usize
-    where
-        T: Ord;
-
-    fn partition_point_by<T, F>(arr: &[T], val: &T, compare: F) -> usize
-    where
-        F: FnMut(&T, &T) -> core::cmp::Ordering;
-}
+// Defined in sort_test_tools, so that the partition point tests can be instantiated for the
+// implementations.
+pub use sort_test_tools::PartitionPoint;
 
 macro_rules! partition_point_impl {
     ($name:expr) => {
@@ -2,16 +2,29 @@ use std::cmp::Ordering;
 
 use afsort::AFSortable;
 
-sort_impl!("rust_afsort_radix");
+sort_impl!("rust_afsort_radix", {
+    fn supports_sort_by() -> bool {
+        false
+    }
+
+    fn supports_type<T>() -> bool {
+        <T as AFSort>::is_supported()
+    }
+});
 
 trait AFSort: Sized {
     fn sort(data: &mut [Self]);
+    fn is_supported() -> bool;
 }
 
 impl<T> AFSort for T {
     default fn sort(_data: &mut [Self]) {
         panic!("Type not supported");
     }
+
+    default fn is_supported() -> bool {
+        false
+    }
 }
 
 impl<T> AFSort for T
@@ -21,6 +34,10 @@ where
     fn sort(data: &mut [Self]) {
         data.af_sort_unstable();
     }
+
+    fn is_supported() -> bool {
+        true
+    }
 }
 
 pub fn sort<T: Ord>(data: &mut [T]) {
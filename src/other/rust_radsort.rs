@@ -1,21 +1,38 @@
 use std::cmp::Ordering;
 
-sort_impl!("rust_radsort_radix");
+sort_impl!("rust_radsort_radix", {
+    fn supports_sort_by() -> bool {
+        false
+    }
+
+    fn supports_type<T>() -> bool {
+        <T as RadSort>::is_supported()
+    }
+});
 
 trait RadSort: Sized {
     fn sort(data: &mut [Self]);
+    fn is_supported() -> bool;
 }
 
 impl<T> RadSort for T {
     default fn sort(_data: &mut [Self]) {
         panic!("Type not supported");
     }
+
+    default fn is_supported() -> bool {
+        false
+    }
 }
 
 impl<T: radsort::Key> RadSort for T {
     fn sort(data: &mut [Self]) {
         radsort::sort(data);
     }
+
+    fn is_supported() -> bool {
+        true
+    }
 }
 
 pub fn sort<T: Ord>(data: &mut [T]) {
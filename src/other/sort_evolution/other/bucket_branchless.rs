@@ -6,6 +6,10 @@ impl BucketSort for u64 {
     fn sort(v: &mut [Self]) {
         bucket_sort(v);
     }
+
+    fn is_supported() -> bool {
+        true
+    }
 }
 
 fn bucket_sort(v: &mut [u64]) {
@@ -9,6 +9,10 @@ impl<T: Clone + Ord> BucketSort for T {
     fn sort(v: &mut [Self]) {
         bucket_sort(v);
     }
+
+    fn is_supported() -> bool {
+        true
+    }
 }
 
 fn bucket_sort<T: Clone + Ord>(v: &mut [T]) {
@@ -11,6 +11,10 @@ impl<T: Clone + Ord + Hash> BucketSort for T {
     fn sort(v: &mut [Self]) {
         bucket_sort(v);
     }
+
+    fn is_supported() -> bool {
+        true
+    }
 }
 
 fn bucket_sort<T: Clone + Ord + Hash>(v: &mut [T]) {
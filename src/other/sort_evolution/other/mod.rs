@@ -1,6 +1,14 @@
 macro_rules! bucket_sort {
     ($name:expr) => {
-        sort_impl!($name);
+        sort_impl!($name, {
+            fn supports_sort_by() -> bool {
+                false
+            }
+
+            fn supports_type<T>() -> bool {
+                <T as BucketSort>::is_supported()
+            }
+        });
 
         #[inline]
         pub fn sort<T: Ord>(v: &mut [T]) {
@@ -14,12 +22,17 @@ macro_rules! bucket_sort {
 
         trait BucketSort: Sized {
             fn sort(v: &mut [Self]);
+            fn is_supported() -> bool;
         }
 
         impl<T> BucketSort for T {
             default fn sort(_v: &mut [Self]) {
                 panic!("not supported");
             }
+
+            default fn is_supported() -> bool {
+                false
+            }
         }
     };
 }
@@ -1,6 +1,10 @@
 use std::cmp::Ordering::{self, Equal, Greater, Less};
 
-sort_impl!("rust_grailsort_stable");
+sort_impl!("rust_grailsort_stable", {
+    fn supports_type<T>() -> bool {
+        <T as GrailSort>::is_supported()
+    }
+});
 
 pub fn sort<T: Ord>(data: &mut [T]) {
     <T as GrailSort>::sort(data);
@@ -13,6 +17,7 @@ pub fn sort_by<T, F: FnMut(&T, &T) -> Ordering>(data: &mut [T], compare: F) {
 trait GrailSort: Sized {
     fn sort(data: &mut [Self]);
     fn sort_by<F: FnMut(&Self, &Self) -> Ordering>(data: &mut [Self], compare: F);
+    fn is_supported() -> bool;
 }
 
 impl<T> GrailSort for T {
@@ -23,6 +28,10 @@ impl<T> GrailSort for T {
     default fn sort_by<F: FnMut(&Self, &Self) -> Ordering>(_data: &mut [Self], _compare: F) {
         panic!("Type not supported by grailsort");
     }
+
+    default fn is_supported() -> bool {
+        false
+    }
 }
 
 impl<T: Sortable> GrailSort for T {
@@ -33,6 +42,10 @@ impl<T: Sortable> GrailSort for T {
     fn sort_by<F: FnMut(&Self, &Self) -> Ordering>(data: &mut [Self], compare: F) {
         grail_sort_by(data, data.len(), compare);
     }
+
+    fn is_supported() -> bool {
+        true
+    }
 }
 
 //Creates a trait alias Sortable to combine the requirement for Ordered and Copyable values,
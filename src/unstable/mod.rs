@@ -1,5 +1,25 @@
 pub mod rust_ipnsort;
 
+// Call ipnsort through its C ABI.
+#[cfg(feature = "rust_ipnsort_capi")]
+pub mod rust_ipnsort_capi;
+
+// Multi-threaded ipnsort.
+#[cfg(feature = "rust_ipnsort_par")]
+pub mod rust_ipnsort_par;
+
+// ipnsort minimizing the number of comparisons.
+#[cfg(feature = "rust_ipnsort_min_cmp")]
+pub mod rust_ipnsort_min_cmp;
+
+// ipnsort merging appended elements into a sorted prefix.
+#[cfg(feature = "rust_ipnsort_appended")]
+pub mod rust_ipnsort_appended;
+
+// ipnsort with non-default SortConfig tuning parameters.
+#[cfg(feature = "rust_ipnsort_config")]
+pub mod rust_ipnsort_config;
+
 pub mod rust_std;
 
 #[cfg(feature = "rust_std_vendored")]
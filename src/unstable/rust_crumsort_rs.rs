@@ -2,22 +2,36 @@ use std::cmp::Ordering;
 use std::ptr;
 use std::sync::Mutex;
 
-sort_impl!("rust_crumsort_rs_unstable");
+// `sort_by` supports every type, but the capabilities only distinguish types for both.
+sort_impl!("rust_crumsort_rs_unstable", {
+    fn supports_type<T>() -> bool {
+        <T as Crumsort>::is_supported()
+    }
+});
 
 trait Crumsort: Sized {
     fn sort(data: &mut [Self]);
+    fn is_supported() -> bool;
 }
 
 impl<T> Crumsort for T {
     default fn sort(_data: &mut [Self]) {
         panic!("Type not supported.");
     }
+
+    default fn is_supported() -> bool {
+        false
+    }
 }
 
 impl<T: Copy + Default + Send + Ord> Crumsort for T {
     fn sort(data: &mut [Self]) {
         crumsort::ParCrumSort::par_crumsort(data);
     }
+
+    fn is_supported() -> bool {
+        true
+    }
 }
 
 struct OrdWrapper<T> {
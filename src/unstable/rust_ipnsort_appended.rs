@@ -0,0 +1,21 @@
+// ipnsort sort_appended, which only sorts the elements after an already sorted prefix and merges
+// them in place. The prefix is found with a linear scan, real use-cases know its length without
+// that. Only interesting for patterns with a long sorted prefix like random_s95 and random_snl_*.
+
+use std::cmp::Ordering;
+
+sort_impl!("rust_ipnsort_appended_unstable");
+
+pub fn sort<T: Ord>(data: &mut [T]) {
+    let sorted_prefix_len = data.windows(2).take_while(|w| w[0] <= w[1]).count() + 1;
+    ipnsort::sort_appended(data, sorted_prefix_len.min(data.len()));
+}
+
+pub fn sort_by<T, F: FnMut(&T, &T) -> Ordering>(data: &mut [T], mut compare: F) {
+    let sorted_prefix_len = data
+        .windows(2)
+        .take_while(|w| compare(&w[0], &w[1]) != Ordering::Greater)
+        .count()
+        + 1;
+    ipnsort::sort_appended_by(data, sorted_prefix_len.min(data.len()), compare);
+}
@@ -0,0 +1,92 @@
+// ipnsort called through its C ABI, the same way C and C++ code would use it. Elements are moved
+// bytewise and may be compared as temporary copies, same as with the other C sort
+// implementations.
+
+use std::any::Any;
+use std::cmp::Ordering;
+use std::ffi::{c_int, c_void};
+use std::mem;
+use std::panic::{self, AssertUnwindSafe};
+
+use ipnsort_capi::{ipnsort_qsort_r, ipnsort_sort_i32, ipnsort_sort_u64};
+
+sort_impl!("rust_ipnsort_capi_unstable");
+
+trait CApiSort: Sized {
+    fn sort(data: &mut [Self]);
+}
+
+impl<T: Ord> CApiSort for T {
+    default fn sort(data: &mut [T]) {
+        sort_by(data, T::cmp);
+    }
+}
+
+impl CApiSort for i32 {
+    fn sort(data: &mut [Self]) {
+        unsafe {
+            ipnsort_sort_i32(data.as_mut_ptr(), data.len());
+        }
+    }
+}
+
+impl CApiSort for u64 {
+    fn sort(data: &mut [Self]) {
+        unsafe {
+            ipnsort_sort_u64(data.as_mut_ptr(), data.len());
+        }
+    }
+}
+
+struct CmpCtx<F> {
+    compare: F,
+    panic: Option<Box<dyn Any + Send>>,
+}
+
+unsafe extern "C" fn cmp_trampoline<T, F: FnMut(&T, &T) -> Ordering>(
+    a: *const c_void,
+    b: *const c_void,
+    arg: *mut c_void,
+) -> c_int {
+    let ctx = &mut *(arg as *mut CmpCtx<F>);
+
+    // Unwinding into C is not allowed, so the panic is stored and resumed once the sort returns.
+    // All remaining comparisons report equal to finish quickly.
+    if ctx.panic.is_some() {
+        return 0;
+    }
+
+    let (a, b) = (&*(a as *const T), &*(b as *const T));
+    match panic::catch_unwind(AssertUnwindSafe(|| (ctx.compare)(a, b))) {
+        Ok(val) => val as c_int,
+        Err(err) => {
+            ctx.panic = Some(err);
+            0
+        }
+    }
+}
+
+pub fn sort<T: Ord>(data: &mut [T]) {
+    CApiSort::sort(data);
+}
+
+pub fn sort_by<T, F: FnMut(&T, &T) -> Ordering>(data: &mut [T], compare: F) {
+    let mut ctx = CmpCtx {
+        compare,
+        panic: None,
+    };
+
+    unsafe {
+        ipnsort_qsort_r(
+            data.as_mut_ptr() as *mut c_void,
+            data.len(),
+            mem::size_of::<T>(),
+            cmp_trampoline::<T, F>,
+            &mut ctx as *mut CmpCtx<F> as *mut c_void,
+        );
+    }
+
+    if let Some(err) = ctx.panic {
+        panic::resume_unwind(err);
+    }
+}
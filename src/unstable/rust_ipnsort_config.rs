@@ -0,0 +1,52 @@
+// ipnsort with non-default compile-time configurations, to compare tuning parameters side by side
+// with the default configuration in `rust_ipnsort`.
+
+macro_rules! config_sort_impl {
+    ($mod_name:ident, $name:expr, { $(const $const_name:ident: $ty:ty = $val:expr;)* }) => {
+        pub mod $mod_name {
+            use std::cmp::Ordering;
+
+            pub struct Config;
+
+            impl ipnsort::SortConfig for Config {
+                $(const $const_name: $ty = $val;)*
+            }
+
+            sort_impl!($name);
+
+            pub fn sort<T: Ord>(data: &mut [T]) {
+                ipnsort::sort_with_config::<Config, T>(data);
+            }
+
+            pub fn sort_by<T, F: FnMut(&T, &T) -> Ordering>(data: &mut [T], compare: F) {
+                ipnsort::sort_by_with_config::<Config, T, F>(data, compare);
+            }
+        }
+    };
+}
+
+config_sort_impl!(small_sort_16, "rust_ipnsort_s16_unstable", {
+    const SMALL_SORT_GENERAL_THRESHOLD: usize = 16;
+    const SMALL_SORT_NETWORK_THRESHOLD: usize = 16;
+});
+
+config_sort_impl!(small_sort_64, "rust_ipnsort_s64_unstable", {
+    const SMALL_SORT_GENERAL_THRESHOLD: usize = 64;
+    const SMALL_SORT_NETWORK_THRESHOLD: usize = 64;
+});
+
+config_sort_impl!(no_insertion_sort, "rust_ipnsort_no_isort_unstable", {
+    const MAX_LEN_ALWAYS_INSERTION_SORT: usize = 0;
+});
+
+config_sort_impl!(imbalance_limit_1, "rust_ipnsort_il1_unstable", {
+    const IMBALANCE_LIMIT_FACTOR: u32 = 1;
+});
+
+config_sort_impl!(no_indirect_sort, "rust_ipnsort_no_indirect_unstable", {
+    const INDIRECT_SORT_MIN_SIZE: usize = usize::MAX;
+});
+
+config_sort_impl!(bounded_stack, "rust_ipnsort_bounded_stack_unstable", {
+    const BOUNDED_STACK: bool = true;
+});
@@ -0,0 +1,13 @@
+// ipnsort minimizing the number of comparisons, for expensive comparison functions.
+
+use std::cmp::Ordering;
+
+sort_impl!("rust_ipnsort_min_cmp_unstable");
+
+pub fn sort<T: Ord>(data: &mut [T]) {
+    ipnsort::sort_by_min_cmp(data, T::cmp);
+}
+
+pub fn sort_by<T, F: FnMut(&T, &T) -> Ordering>(data: &mut [T], compare: F) {
+    ipnsort::sort_by_min_cmp(data, compare);
+}
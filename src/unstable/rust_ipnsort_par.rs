@@ -0,0 +1,48 @@
+// Multi-threaded ipnsort, which requires the sorted type to be Send.
+
+use std::cmp::Ordering;
+
+// `ipnsort::par_sort_by` calls the comparison function from several threads at once, which needs
+// `Fn + Sync` instead of the `FnMut` that `sort_by` takes.
+sort_impl!("rust_ipnsort_par_unstable", {
+    fn supports_sort_by() -> bool {
+        false
+    }
+
+    fn supports_type<T>() -> bool {
+        <T as ParSort>::is_supported()
+    }
+});
+
+trait ParSort: Sized {
+    fn sort(data: &mut [Self]);
+    fn is_supported() -> bool;
+}
+
+impl<T> ParSort for T {
+    default fn sort(_data: &mut [T]) {
+        panic!("Type not supported");
+    }
+
+    default fn is_supported() -> bool {
+        false
+    }
+}
+
+impl<T: Ord + Send> ParSort for T {
+    fn sort(data: &mut [T]) {
+        ipnsort::par_sort(data);
+    }
+
+    fn is_supported() -> bool {
+        true
+    }
+}
+
+pub fn sort<T: Ord>(data: &mut [T]) {
+    ParSort::sort(data);
+}
+
+pub fn sort_by<T, F: FnMut(&T, &T) -> Ordering>(_data: &mut [T], _compare: F) {
+    panic!("sort_by not supported");
+}
@@ -1,6 +1,10 @@
 use std::cmp::Ordering;
 
-sort_impl!("rust_std_unstable");
+sort_impl!("rust_std_unstable", {
+    fn allocates<T>() -> bool {
+        false
+    }
+});
 
 #[inline]
 pub fn sort<T>(v: &mut [T])
@@ -11,7 +11,11 @@ use std::cmp::Ordering;
 use std::mem::{self, MaybeUninit};
 use std::ptr;
 
-sort_impl!("rust_std_vendored_unstable");
+sort_impl!("rust_std_vendored_unstable", {
+    fn allocates<T>() -> bool {
+        false
+    }
+});
 
 /// Sorts the slice, but might not preserve the order of equal elements.
 ///
@@ -403,7 +407,7 @@ where
 
         if start_l == end_l {
             // Trace `block_l` elements from the left side.
-            start_l = MaybeUninit::slice_as_mut_ptr(&mut offsets_l);
+            start_l = offsets_l.as_mut_ptr().cast::<u8>();
             end_l = start_l;
             let mut elem = l;
 
@@ -429,7 +433,7 @@ where
 
         if start_r == end_r {
             // Trace `block_r` elements from the right side.
-            start_r = MaybeUninit::slice_as_mut_ptr(&mut offsets_r);
+            start_r = offsets_r.as_mut_ptr().cast::<u8>();
             end_r = start_r;
             let mut elem = r;
 
@@ -1,5 +1,125 @@
+use sort_test_tools::counting_alloc::CountingAlloc;
 use sort_test_tools::instantiate_sort_tests;
 
+#[global_allocator]
+static ALLOC: CountingAlloc = CountingAlloc;
+
 type TestSort = sort_research_rs::unstable::rust_ipnsort::SortImpl;
 
 instantiate_sort_tests!(TestSort);
+
+// Sorts that claim not to allocate, which the `no_alloc_*` tests check.
+
+mod rust_std {
+    sort_test_tools::instantiate_sort_tests!(sort_research_rs::unstable::rust_std::SortImpl);
+}
+
+#[cfg(feature = "rust_std_vendored")]
+mod rust_std_vendored {
+    sort_test_tools::instantiate_sort_tests!(
+        sort_research_rs::unstable::rust_std_vendored::SortImpl
+    );
+}
+
+// Sorts that only support some types or no `sort_by`, tests they can't run pass without running.
+
+#[cfg(feature = "rust_radsort")]
+mod rust_radsort {
+    sort_test_tools::instantiate_sort_tests!(sort_research_rs::other::rust_radsort::SortImpl);
+}
+
+#[cfg(feature = "rust_ipnsort_par")]
+mod rust_ipnsort_par {
+    sort_test_tools::instantiate_sort_tests!(
+        sort_research_rs::unstable::rust_ipnsort_par::SortImpl
+    );
+}
+
+#[cfg(feature = "evolution")]
+mod bucket_btree {
+    sort_test_tools::instantiate_sort_tests!(
+        sort_research_rs::other::sort_evolution::other::bucket_btree::SortImpl
+    );
+}
+
+#[cfg(feature = "evolution")]
+mod bucket_hash {
+    sort_test_tools::instantiate_sort_tests!(
+        sort_research_rs::other::sort_evolution::other::bucket_hash::SortImpl
+    );
+}
+
+#[cfg(feature = "partition")]
+mod partition {
+    macro_rules! instantiate_partition_tests_for {
+        ($partition_impl:ident $(, ignore = $reason:literal)?) => {
+            mod $partition_impl {
+                sort_test_tools::instantiate_partition_tests!(
+                    sort_research_rs::other::partition::$partition_impl::PartitionImpl
+                    $(, ignore = $reason)?
+                );
+            }
+        };
+    }
+
+    instantiate_partition_tests_for!(hoare_block);
+    instantiate_partition_tests_for!(hoare_branchy);
+    instantiate_partition_tests_for!(hoare_branchy_cyclic);
+    instantiate_partition_tests_for!(lomuto_branchless);
+    instantiate_partition_tests_for!(lomuto_branchless_cyclic);
+    instantiate_partition_tests_for!(lomuto_branchless_cyclic_opt);
+    instantiate_partition_tests_for!(lomuto_branchy);
+    instantiate_partition_tests_for!(lomuto_nanosort);
+    instantiate_partition_tests_for!(stable_2side_fill);
+
+    // Known defects, run them with `--ignored`.
+    instantiate_partition_tests_for!(
+        hoare_block_butterfly,
+        ignore = "copies overlapping elements when partitioning types that are not Copy or Freeze"
+    );
+    instantiate_partition_tests_for!(
+        hoare_crumsort,
+        ignore = "loses and duplicates elements if the comparison function panics"
+    );
+    instantiate_partition_tests_for!(
+        hoare_crumsort_rs,
+        ignore = "only implemented for 8 byte types, ignores the comparison function, and \
+                  miscounts the elements less than the pivot"
+    );
+    instantiate_partition_tests_for!(
+        hybrid_bitset_partition,
+        ignore = "copies overlapping elements when partitioning types that are not Copy or Freeze"
+    );
+    instantiate_partition_tests_for!(
+        hybrid_block_partition,
+        ignore = "miscounts the elements less than the pivot for inputs of 4096 and more elements"
+    );
+    instantiate_partition_tests_for!(
+        lomuto_iterleaved,
+        ignore = "puts elements on the wrong side and swaps overlapping elements"
+    );
+    instantiate_partition_tests_for!(
+        small_partition,
+        ignore = "limited to 128 elements, and copies overlapping elements for types that are not \
+                  Copy or Freeze"
+    );
+    instantiate_partition_tests_for!(
+        sum_is_less,
+        ignore = "only counts the elements less than the pivot, as baseline for the comparisons"
+    );
+}
+
+#[cfg(feature = "partition_point")]
+mod partition_point {
+    macro_rules! instantiate_partition_point_tests_for {
+        ($($partition_point_impl:ident),* $(,)?) => {
+            $(mod $partition_point_impl {
+                sort_test_tools::instantiate_partition_point_tests!(
+                    sort_research_rs::other::partition_point::$partition_point_impl::PartitionPointImpl
+                );
+            })*
+        };
+    }
+
+    instantiate_partition_point_tests_for!(branchless_bitwise, branchless_clean, std);
+}
@@ -1,49 +0,0 @@
-vals = """
-[(0,1),(2,3),(4,5),(6,7),(8,9),(10,11),(12,13),(14,15),(16,17),(18,19),(20,21),(22,23),(24,25),(26,27),(28,29),(30,31),(32,33),(34,35)]
-[(0,34),(1,35),(2,4),(3,5),(6,8),(7,9),(10,12),(11,13),(14,16),(15,17),(18,20),(19,21),(22,24),(23,25),(26,28),(27,29),(30,32),(31,33)]
-[(1,34),(2,6),(3,7),(4,8),(5,9),(10,14),(11,15),(12,16),(13,17),(18,22),(19,23),(20,24),(21,25),(26,30),(27,31),(28,32),(29,33)]
-[(2,10),(3,11),(4,12),(5,13),(6,14),(7,15),(8,16),(9,17),(18,26),(19,27),(20,28),(21,29),(22,30),(23,31),(24,32),(25,33)]
-[(2,18),(3,19),(4,20),(5,21),(6,22),(7,23),(8,24),(9,25),(10,26),(11,27),(12,28),(13,29),(14,30),(15,31),(16,32),(17,33)]
-[(0,2),(1,4),(5,7),(6,10),(8,26),(9,27),(11,19),(12,22),(13,23),(14,20),(15,21),(16,24),(25,29),(28,30),(31,34),(33,35)]
-[(1,18),(2,26),(4,30),(5,31),(7,22),(9,33),(11,20),(12,14),(13,28),(15,24),(17,34),(21,23)]
-[(1,6),(2,3),(4,9),(10,18),(13,15),(17,25),(20,22),(26,31),(29,34),(32,33)]
-[(1,2),(3,5),(4,8),(9,16),(14,18),(17,21),(19,26),(27,31),(30,32),(33,34)]
-[(2,10),(3,12),(4,11),(5,18),(7,19),(8,20),(9,13),(15,27),(16,28),(17,30),(22,26),(23,32),(24,31),(25,33)]
-[(3,6),(5,7),(8,9),(11,12),(13,16),(15,18),(17,20),(19,22),(23,24),(26,27),(28,30),(29,32)]
-[(2,3),(4,6),(5,10),(7,14),(8,11),(9,12),(13,18),(15,19),(16,20),(17,22),(21,28),(23,26),(24,27),(25,30),(29,31),(32,33)]
-[(4,5),(6,10),(7,8),(9,14),(11,15),(12,19),(13,17),(16,23),(18,22),(20,24),(21,26),(25,29),(27,28),(30,31)]
-[(3,4),(5,7),(8,11),(9,10),(12,15),(13,14),(16,17),(18,19),(20,23),(21,22),(24,27),(25,26),(28,30),(31,32)]
-[(4,5),(6,7),(8,9),(10,11),(12,13),(14,15),(16,18),(17,19),(20,21),(22,23),(24,25),(26,27),(28,29),(30,31)]
-[(5,6),(7,8),(9,10),(11,12),(13,14),(15,16),(17,18),(19,20),(21,22),(23,24),(25,26),(27,28),(29,30)]
-"""
-
-
-def make_swap_pair(pair):
-    parts = pair[1:].split(",")
-
-    a = int(parts[0])
-    b = int(parts[1])
-
-    return f"({a}, {b})"
-
-
-def make_layer(line):
-    swap_pairs = ",".join(
-        [
-            make_swap_pair(pair)
-            for pair in line.replace("[", "").replace("]", ",").split("),")
-            if len(pair) > 0
-        ]
-    )
-
-    return f"Layer::new([{swap_pairs}])"
-
-
-def make_network(vals):
-    layers = ",".join([make_layer(line) for line in vals.strip().split("\n")])
-
-    return f"Network::new([{layers}])"
-
-
-if __name__ == "__main__":
-    print(make_network(vals))